@@ -1,3 +1,4 @@
+use crate::audit::{self, AuditAction};
 use crate::cli::AdaptArgs;
 use crate::config::Config;
 use crate::context;
@@ -30,14 +31,14 @@ pub(crate) fn build_substitutions(
                 kind: "directory",
             });
 
-            if let (Some(s_home), Some(c_home)) = (extract_home(s), extract_home(c)) {
-                if s_home != c_home {
-                    subs.push(Substitution {
-                        from: s_home,
-                        to: c_home,
-                        kind: "home directory",
-                    });
-                }
+            if let (Some(s_home), Some(c_home)) = (extract_home(s), extract_home(c))
+                && s_home != c_home
+            {
+                subs.push(Substitution {
+                    from: s_home,
+                    to: c_home,
+                    kind: "home directory",
+                });
             }
         }
         _ => {}
@@ -99,6 +100,9 @@ fn show_diff(original: &str, adapted: &str) {
 
 pub fn adapt_script(args: AdaptArgs) -> Result<()> {
     let config = Config::load()?;
+    if args.output.is_none() && !args.dry_run {
+        config.ensure_writable()?;
+    }
     let storage = config.get_storage_backend()?;
 
     let script = load_scripts_local()?
@@ -228,6 +232,7 @@ pub fn adapt_script(args: AdaptArgs) -> Result<()> {
     let mut hasher = Sha256::new();
     hasher.update(updated.content.as_bytes());
     updated.metadata.hash = hex::encode(hasher.finalize());
+    updated.safety_acknowledged = false;
     updated.metadata.size_bytes = updated.content.len();
     updated.metadata.line_count = updated.content.lines().count();
     updated.updated_at = chrono::Utc::now();
@@ -253,6 +258,12 @@ pub fn adapt_script(args: AdaptArgs) -> Result<()> {
 
     let store = crate::versions::VersionStore::new(&Config::vault_dir()?);
     store.save_version(&updated)?;
+    audit::record(
+        &config,
+        AuditAction::Save,
+        &updated,
+        Some(updated.version.clone()),
+    )?;
 
     println!(
         "{} Adapted: {} {} -> {}",
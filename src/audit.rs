@@ -0,0 +1,280 @@
+use crate::cli::AuditArgs;
+use crate::config::Config;
+use crate::constants::default_author;
+use crate::script::Script;
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use colored::*;
+use serde::{Deserialize, Serialize};
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::Path;
+
+/// Values accepted by `sv audit --action`.
+pub const AUDIT_ACTIONS: [&str; 9] = [
+    "save",
+    "import",
+    "merge",
+    "checkout",
+    "delete",
+    "rename",
+    "share",
+    "archive",
+    "unarchive",
+];
+
+const DEFAULT_AUDIT_LIMIT: usize = 50;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AuditAction {
+    Save,
+    Import,
+    Merge,
+    Checkout,
+    Delete,
+    Rename,
+    Share,
+    Archive,
+    Unarchive,
+}
+
+impl AuditAction {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Save => "save",
+            Self::Import => "import",
+            Self::Merge => "merge",
+            Self::Checkout => "checkout",
+            Self::Delete => "delete",
+            Self::Rename => "rename",
+            Self::Share => "share",
+            Self::Archive => "archive",
+            Self::Unarchive => "unarchive",
+        }
+    }
+}
+
+/// One change to the vault, as stored in `events.jsonl`. Runs are not
+/// events; they live in the execution history.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AuditEvent {
+    pub at: DateTime<Utc>,
+    pub user: String,
+    pub action: AuditAction,
+    pub script_id: String,
+    /// The script's name when the event happened.
+    pub script: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub detail: Option<String>,
+}
+
+/// Appends an event for `script` to the audit log. The log is never rotated
+/// or rewritten, so it outlives the scripts and history it describes.
+pub fn record(
+    config: &Config,
+    action: AuditAction,
+    script: &Script,
+    detail: Option<String>,
+) -> Result<()> {
+    let event = AuditEvent {
+        at: Utc::now(),
+        user: config.username.clone().unwrap_or_else(default_author),
+        action,
+        script_id: script.id.clone(),
+        script: script.name.clone(),
+        detail,
+    };
+    append_event(&Config::events_path()?, &event)
+}
+
+fn append_event(path: &Path, event: &AuditEvent) -> Result<()> {
+    let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+    writeln!(file, "{}", serde_json::to_string(event)?)?;
+    Ok(())
+}
+
+/// Events in the log, oldest first, skipping lines that don't parse.
+fn read_events(path: &Path) -> Result<Vec<AuditEvent>> {
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    Ok(fs::read_to_string(path)?
+        .lines()
+        .filter(|l| !l.is_empty())
+        .filter_map(|line| serde_json::from_str(line).ok())
+        .collect())
+}
+
+/// Events matching the `sv audit` filters, newest first. `script_id` is the
+/// id of the script currently named `--script`, so its events from before a
+/// rename still match.
+fn filter_events<'a>(
+    events: &'a [AuditEvent],
+    args: &AuditArgs,
+    script_id: Option<&str>,
+) -> Vec<&'a AuditEvent> {
+    let since = args.since.map(|age| Utc::now() - age);
+    let mut matching: Vec<&AuditEvent> = events
+        .iter()
+        .filter(|e| {
+            args.script
+                .as_ref()
+                .is_none_or(|name| e.script == *name || Some(e.script_id.as_str()) == script_id)
+        })
+        .filter(|e| args.user.as_ref().is_none_or(|user| e.user == *user))
+        .filter(|e| {
+            args.action
+                .as_ref()
+                .is_none_or(|action| e.action.as_str() == action)
+        })
+        .filter(|e| since.is_none_or(|since| e.at >= since))
+        .collect();
+    matching.sort_by_key(|e| std::cmp::Reverse(e.at));
+    matching
+}
+
+pub fn show_audit(args: AuditArgs) -> Result<()> {
+    let events = read_events(&Config::events_path()?)?;
+    let script_id = match args.script {
+        Some(ref name) => Config::load()?
+            .get_storage_backend()?
+            .load_script_by_name(name)
+            .ok()
+            .map(|s| s.id),
+        None => None,
+    };
+    let matching = filter_events(&events, &args, script_id.as_deref());
+
+    if matching.is_empty() {
+        println!("No audit events found.");
+        return Ok(());
+    }
+
+    println!("{}", "Audit Log".cyan().bold());
+    println!();
+    println!(
+        "{:<20} {:<10} {:<22} {:<15} {}",
+        "TIME".bold(),
+        "ACTION".bold(),
+        "SCRIPT".bold(),
+        "USER".bold(),
+        "DETAIL".bold()
+    );
+    println!("{}", "─".repeat(80).dimmed());
+
+    let limit = args.limit.unwrap_or(DEFAULT_AUDIT_LIMIT);
+    for event in matching.iter().take(limit) {
+        let action = match event.action {
+            AuditAction::Delete => event.action.as_str().red(),
+            _ => event.action.as_str().normal(),
+        };
+        println!(
+            "{:<20} {:<10} {:<22} {:<15} {}",
+            event.at.format("%Y-%m-%d %H:%M:%S").to_string().dimmed(),
+            action,
+            event.script.yellow(),
+            event.user,
+            event.detail.as_deref().unwrap_or("").dimmed()
+        );
+    }
+
+    if matching.len() > limit {
+        println!();
+        println!(
+            "... and {} older events (use --limit to see more)",
+            matching.len() - limit
+        );
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn event(action: AuditAction, script: &str, user: &str, age_days: i64) -> AuditEvent {
+        AuditEvent {
+            at: Utc::now() - chrono::Duration::days(age_days),
+            user: user.to_string(),
+            action,
+            script_id: format!("id-{}", script),
+            script: script.to_string(),
+            detail: None,
+        }
+    }
+
+    fn audit_args() -> AuditArgs {
+        AuditArgs {
+            script: None,
+            user: None,
+            action: None,
+            since: None,
+            limit: None,
+        }
+    }
+
+    #[test]
+    fn test_events_append_and_read_back() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("events.jsonl");
+        assert!(read_events(&path).unwrap().is_empty());
+
+        append_event(&path, &event(AuditAction::Save, "deploy", "ana", 0)).unwrap();
+        let mut rename = event(AuditAction::Rename, "deploy", "ana", 0);
+        rename.detail = Some("deploy -> ship".to_string());
+        append_event(&path, &rename).unwrap();
+
+        let events = read_events(&path).unwrap();
+        assert_eq!(events.len(), 2);
+        assert_eq!(events[0].action, AuditAction::Save);
+        assert_eq!(events[1].detail.as_deref(), Some("deploy -> ship"));
+        let raw = fs::read_to_string(&path).unwrap();
+        assert!(raw.lines().next().unwrap().contains("\"action\":\"save\""));
+    }
+
+    #[test]
+    fn test_filter_events() {
+        let events = vec![
+            event(AuditAction::Save, "deploy", "ana", 10),
+            event(AuditAction::Save, "backup", "bo", 3),
+            event(AuditAction::Delete, "backup", "ana", 1),
+            AuditEvent {
+                script_id: "id-deploy".to_string(),
+                ..event(AuditAction::Rename, "ship", "ana", 0)
+            },
+        ];
+        let names = |args: &AuditArgs, id: Option<&str>| -> Vec<String> {
+            filter_events(&events, args, id)
+                .iter()
+                .map(|e| format!("{}:{}", e.action.as_str(), e.script))
+                .collect()
+        };
+
+        assert_eq!(
+            names(&audit_args(), None),
+            ["rename:ship", "delete:backup", "save:backup", "save:deploy"]
+        );
+        let by_user = AuditArgs {
+            user: Some("ana".to_string()),
+            action: Some("save".to_string()),
+            ..audit_args()
+        };
+        assert_eq!(names(&by_user, None), ["save:deploy"]);
+        let recent = AuditArgs {
+            since: Some(chrono::Duration::days(2)),
+            ..audit_args()
+        };
+        assert_eq!(names(&recent, None), ["rename:ship", "delete:backup"]);
+        // `deploy` was renamed to `ship`; its older events follow the id.
+        let renamed = AuditArgs {
+            script: Some("ship".to_string()),
+            ..audit_args()
+        };
+        assert_eq!(
+            names(&renamed, Some("id-deploy")),
+            ["rename:ship", "save:deploy"]
+        );
+    }
+}
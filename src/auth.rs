@@ -12,10 +12,10 @@ fn ureq_err(e: ureq::Error) -> anyhow::Error {
         ureq::Error::Status(401, _) => anyhow!("Invalid API key"),
         ureq::Error::Status(400, resp) => {
             let body = resp.into_string().unwrap_or_default();
-            if let Ok(v) = serde_json::from_str::<serde_json::Value>(&body) {
-                if let Some(msg) = v.get("error").and_then(|m| m.as_str()) {
-                    return anyhow!("{}", msg);
-                }
+            if let Ok(v) = serde_json::from_str::<serde_json::Value>(&body)
+                && let Some(msg) = v.get("error").and_then(|m| m.as_str())
+            {
+                return anyhow!("{}", msg);
             }
             anyhow!("Bad request")
         }
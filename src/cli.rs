@@ -1,3 +1,4 @@
+use crate::script::{ScriptLanguage, ScriptParam, Visibility};
 use clap::{Args, Parser, Subcommand};
 
 #[derive(Parser, Debug)]
@@ -8,6 +9,30 @@ use clap::{Args, Parser, Subcommand};
 pub struct Cli {
     #[command(subcommand)]
     pub command: Command,
+
+    #[arg(
+        long,
+        global = true,
+        value_name = "WHEN",
+        default_value = "auto",
+        value_parser = crate::color::COLOR_CHOICES,
+        help = "When to use colored output (auto, always, never)"
+    )]
+    pub color: String,
+
+    #[arg(
+        long,
+        global = true,
+        help = "Disable colored output (same as --color never)"
+    )]
+    pub no_color: bool,
+
+    #[arg(
+        long,
+        global = true,
+        help = "Refuse every change to the vault for this command (same as read_only in config)"
+    )]
+    pub read_only: bool,
 }
 
 #[derive(Subcommand, Debug)]
@@ -21,22 +46,41 @@ pub enum Command {
     Info(InfoArgs),
     Run(RunArgs),
     Delete(DeleteArgs),
+    Archive(ArchiveArgs),
+    Unarchive(ArchiveArgs),
+    Pin(PinArgs),
+    Unpin(PinArgs),
+    Trust(TrustArgs),
+    Untrust(TrustArgs),
+    TrustDangerous(TrustArgs),
+    UntrustDangerous(TrustArgs),
     Cat(CatArgs),
     Edit(EditArgs),
     Rename(RenameArgs),
     Copy(CopyArgs),
+    Merge(MergeArgs),
+    Share(ShareArgs),
     History(HistoryArgs),
+    #[command(about = "Show who saved, deleted, renamed, shared or archived scripts")]
+    Audit(AuditArgs),
     Stats(StatsArgs),
+    Validate(ValidateArgs),
+    Trend(TrendArgs),
     Versions(VersionArgs),
     Diff(DiffArgs),
     Checkout(CheckoutArgs),
     Context,
     Export(ExportArgs),
+    Import(ImportArgs),
+    #[command(about = "Save every script file in a directory")]
+    ImportDir(ImportDirArgs),
+    Template(TemplateCommand),
     Sync(SyncCommand),
     Storage(StorageCommand),
     Adapt(AdaptArgs),
-    Doctor,
+    Doctor(DoctorArgs),
     Status,
+    OpenConfig(OpenConfigArgs),
 }
 #[derive(Args, Debug)]
 pub struct AuthCommand {
@@ -68,8 +112,26 @@ pub struct LoginArgs {
 
 #[derive(Args, Debug)]
 pub struct SaveArgs {
-    #[arg(value_name = "FILE")]
-    pub file: String,
+    #[arg(value_name = "FILE", required_unless_present = "from_template")]
+    pub file: Option<String>,
+
+    #[arg(
+        long,
+        value_name = "TEMPLATE",
+        conflicts_with = "file",
+        requires = "name",
+        help = "Create the script from a template instead of a file (see 'sv template list')"
+    )]
+    pub from_template: Option<String>,
+
+    #[arg(
+        long = "var",
+        value_name = "KEY=VALUE",
+        value_parser = crate::templates::parse_var,
+        requires = "from_template",
+        help = "Value for a template placeholder; repeat for each. Missing ones are prompted for"
+    )]
+    pub vars: Vec<(String, String)>,
 
     #[arg(long, value_name = "NAME")]
     pub name: Option<String>,
@@ -80,10 +142,89 @@ pub struct SaveArgs {
     #[arg(long, value_name = "DESC")]
     pub description: Option<String>,
 
+    #[arg(
+        long,
+        value_name = "CMD",
+        help = "Command to run before the script; if it fails the script is not run"
+    )]
+    pub pre_run: Option<String>,
+
+    #[arg(
+        long,
+        value_name = "CMD",
+        help = "Command to run after the script completes successfully"
+    )]
+    pub post_run: Option<String>,
+
+    #[arg(
+        long = "param",
+        value_name = "NAME[:DESC][=DEFAULT]",
+        value_parser = ScriptParam::parse,
+        help = "Declare a positional parameter; repeat in order. Parameters without a default are required"
+    )]
+    pub params: Vec<ScriptParam>,
+
+    #[arg(
+        long = "script-env",
+        value_name = "KEY=VALUE",
+        value_parser = crate::templates::parse_var,
+        help = "Environment variable set on every run; repeat for each. Replaces the saved set"
+    )]
+    pub script_env: Vec<(String, String)>,
+
+    #[arg(
+        long = "success-codes",
+        value_name = "CODES",
+        value_delimiter = ',',
+        allow_negative_numbers = true,
+        help = "Comma-separated exit codes that count as success, e.g. 0,1 [default: 0]. Replaces the saved list"
+    )]
+    pub success_codes: Vec<i32>,
+
+    #[arg(
+        long,
+        value_name = "VISIBILITY",
+        help = "private, team or public [default: default_visibility from the config]. Re-saving keeps the current one"
+    )]
+    pub visibility: Option<Visibility>,
+
+    #[arg(
+        long,
+        value_name = "LEVEL",
+        value_parser = crate::vault::BUMP_LEVELS,
+        help = "Version part to bump when the content changed [default: patch]"
+    )]
+    pub bump: Option<String>,
+
+    #[arg(
+        long,
+        help = "Save even if another script already has identical content"
+    )]
+    pub force: bool,
+
     #[arg(long, help = "Skip interactive prompts")]
     pub yes: bool,
 }
 
+#[derive(Args, Debug)]
+pub struct ValidateArgs {
+    #[arg(value_name = "FILE")]
+    pub file: String,
+
+    #[arg(
+        long,
+        value_name = "NAME",
+        help = "Name the script would be saved under"
+    )]
+    pub name: Option<String>,
+
+    #[arg(
+        long,
+        help = "Exit nonzero if the safety check finds dangerous patterns"
+    )]
+    pub strict: bool,
+}
+
 #[derive(Args, Debug)]
 pub struct UpdateArgs {
     #[arg(value_name = "FILE")]
@@ -105,7 +246,7 @@ pub struct FindArgs {
     pub tag: Option<String>,
 
     #[arg(long, value_name = "LANG")]
-    pub language: Option<String>,
+    pub language: Option<ScriptLanguage>,
 
     #[arg(long)]
     pub team: bool,
@@ -115,6 +256,58 @@ pub struct FindArgs {
 
     #[arg(long)]
     pub recent: bool,
+
+    #[arg(long, help = "Include archived scripts in the results")]
+    pub include_archived: bool,
+
+    #[arg(long, help = "Only show pinned scripts")]
+    pub pinned: bool,
+
+    #[arg(long, help = "Only show scripts that have never been run")]
+    pub not_run: bool,
+
+    #[arg(
+        long,
+        value_name = "AGE",
+        value_parser = crate::utils::parse_age,
+        help = "Only show scripts not run within AGE (e.g. 30d, 2w, 6mo); includes never-run scripts"
+    )]
+    pub stale: Option<chrono::Duration>,
+
+    #[command(flatten)]
+    pub success: SuccessRateArgs,
+
+    #[arg(long, help = "Print only the number of matching scripts")]
+    pub count: bool,
+
+    #[arg(
+        long,
+        conflicts_with = "count",
+        help = "Print every match as tab-separated name, version, language, use count and last run, for scripts"
+    )]
+    pub porcelain: bool,
+
+    #[arg(
+        long,
+        conflicts_with_all = ["count", "porcelain"],
+        help = "Print every matching script as one JSON object per line"
+    )]
+    pub json_lines: bool,
+
+    #[arg(
+        long,
+        value_name = "TAG",
+        conflicts_with_all = ["count", "porcelain", "json_lines"],
+        help = "Add TAG to every matching script"
+    )]
+    pub tag_all: Option<String>,
+
+    #[arg(
+        long,
+        requires = "tag_all",
+        help = "Skip the confirmation for --tag-all"
+    )]
+    pub yes: bool,
 }
 
 #[derive(Args, Debug)]
@@ -136,11 +329,80 @@ pub struct ListArgs {
 
     #[arg(long, default_value = "0")]
     pub offset: usize,
+
+    #[arg(long, help = "Include archived scripts in the results")]
+    pub include_archived: bool,
+
+    #[command(flatten)]
+    pub success: SuccessRateArgs,
+
+    #[arg(long, help = "Print only the number of matching scripts")]
+    pub count: bool,
+
+    #[arg(
+        long,
+        conflicts_with = "count",
+        help = "Group scripts under the git repo or directory they were saved from"
+    )]
+    pub tree: bool,
+}
+
+#[derive(Args, Debug, Default)]
+pub struct SuccessRateArgs {
+    #[arg(
+        long,
+        value_name = "PCT",
+        value_parser = parse_percent,
+        help = "Only scripts whose success rate is at least PCT (0-100)"
+    )]
+    pub min_success_rate: Option<f64>,
+
+    #[arg(
+        long,
+        value_name = "PCT",
+        value_parser = parse_percent,
+        help = "Only scripts whose success rate is at most PCT (0-100)"
+    )]
+    pub max_success_rate: Option<f64>,
+
+    #[arg(long, help = "Only scripts that have been run at least once")]
+    pub has_runs: bool,
+}
+
+impl SuccessRateArgs {
+    pub fn is_active(&self) -> bool {
+        self.min_success_rate.is_some() || self.max_success_rate.is_some() || self.has_runs
+    }
+}
+
+fn parse_percent(s: &str) -> Result<f64, String> {
+    let pct: f64 = s
+        .trim_end_matches('%')
+        .parse()
+        .map_err(|_| format!("'{}' is not a number", s))?;
+    if !(0.0..=100.0).contains(&pct) {
+        return Err(format!("{} is not between 0 and 100", pct));
+    }
+    Ok(pct)
+}
+
+fn parse_timeout(s: &str) -> Result<std::time::Duration, String> {
+    match crate::utils::parse_duration(s)? {
+        d if d.is_zero() => Err("the timeout must be greater than zero".to_string()),
+        d => Ok(d),
+    }
 }
 
 #[derive(Args, Debug)]
 pub struct InfoArgs {
     pub name: String,
+
+    #[arg(
+        long,
+        value_name = "N",
+        help = "Also list the script's last N runs, newest first"
+    )]
+    pub history: Option<usize>,
 }
 
 #[derive(Args, Debug)]
@@ -151,13 +413,163 @@ pub struct RunArgs {
     #[arg(
         value_name = "ARGS",
         trailing_var_arg = true,
-        allow_hyphen_values = true
+        allow_hyphen_values = true,
+        help = "Arguments for the script; put them after -- if they start with a dash"
     )]
     pub args: Vec<String>,
 
+    #[arg(
+        long,
+        value_name = "FILE",
+        help = "Read more arguments from FILE, separated by whitespace or newlines, and pass them after ARGS"
+    )]
+    pub args_file: Option<String>,
+
+    #[arg(
+        long,
+        value_name = "VERSION",
+        help = "Run a stored version (e.g. v1.0.0) instead of the current one"
+    )]
+    pub version: Option<String>,
+
+    #[arg(
+        long,
+        value_name = "LINE",
+        help = "Run a bash or shell script starting at this line (1-based), e.g. to resume after a failure"
+    )]
+    pub continue_from: Option<usize>,
+
     #[arg(long)]
     pub dry_run: bool,
 
+    #[arg(
+        long,
+        help = "Refuse to run unless the current user may run the script under its visibility"
+    )]
+    pub check_permissions: bool,
+
+    #[arg(
+        long,
+        conflicts_with_all = ["dry_run", "ssh"],
+        help = "Record the run in history without executing the script"
+    )]
+    pub record_only: bool,
+
+    #[arg(
+        long,
+        conflicts_with = "record_only",
+        help = "Run without writing history or updating the script's run stats"
+    )]
+    pub no_record: bool,
+
+    #[arg(
+        long,
+        conflicts_with_all = ["dry_run", "record_only", "no_record", "ssh"],
+        help = "Print the script that would run, with a #! line, instead of running it"
+    )]
+    pub print_only: bool,
+
+    #[arg(
+        long,
+        value_name = "FILE",
+        conflicts_with_all = ["dry_run", "record_only", "print_only", "ssh"],
+        help = "Write the run's full execution record as JSON to FILE, even if the script fails"
+    )]
+    pub capture_json: Option<String>,
+
+    #[arg(
+        long,
+        conflicts_with_all = ["dry_run", "record_only", "print_only", "ssh"],
+        help = "Run again after each failure until the script succeeds or --max-attempts is reached"
+    )]
+    pub until_success: bool,
+
+    #[arg(
+        long,
+        conflicts_with_all = ["dry_run", "record_only", "print_only", "ssh"],
+        help = "Run the script and report its memory and CPU use; the run is recorded as usual"
+    )]
+    pub measure_only: bool,
+
+    #[arg(
+        long,
+        conflicts_with_all = ["dry_run", "record_only", "print_only", "ssh"],
+        help = "Print when each phase of the run ended and save the breakdown in the history"
+    )]
+    pub capture_timing: bool,
+
+    #[arg(
+        long,
+        value_name = "FORMAT",
+        value_parser = ["text", "json"],
+        default_value = "text",
+        conflicts_with_all = ["dry_run", "record_only", "print_only", "ssh"],
+        help = "How to report the run: text streams the script's output; json prints one JSON summary when it ends"
+    )]
+    pub output_format: String,
+
+    #[arg(
+        long,
+        value_name = "DURATION",
+        value_parser = parse_timeout,
+        conflicts_with_all = ["record_only", "print_only", "ssh"],
+        help = "Stop the script if it is still running after DURATION (e.g. 30, 90s, 5m, 2h)"
+    )]
+    pub timeout: Option<std::time::Duration>,
+
+    #[arg(
+        long,
+        value_name = "SIGNAL",
+        value_parser = ["TERM", "KILL"],
+        requires = "timeout",
+        help = "Signal sent when --timeout fires: TERM (then KILL after --kill-grace) or KILL [default: TERM]"
+    )]
+    pub kill_signal: Option<String>,
+
+    #[arg(
+        long,
+        value_name = "DURATION",
+        value_parser = crate::utils::parse_duration,
+        requires = "timeout",
+        help = "With --kill-signal TERM, how long to wait for the script to exit before sending KILL [default: 5s]"
+    )]
+    pub kill_grace: Option<std::time::Duration>,
+
+    #[arg(
+        long,
+        value_name = "N",
+        default_value = "5",
+        value_parser = clap::value_parser!(u32).range(1..),
+        requires = "until_success",
+        help = "With --until-success, the most runs to try"
+    )]
+    pub max_attempts: u32,
+
+    #[arg(
+        long,
+        value_name = "DURATION",
+        default_value = "5s",
+        value_parser = crate::utils::parse_duration,
+        requires = "until_success",
+        help = "With --until-success, how long to wait between attempts"
+    )]
+    pub retry_delay: std::time::Duration,
+
+    #[arg(
+        long,
+        value_name = "KEY=VALUE",
+        value_parser = crate::templates::parse_var,
+        help = "Set an environment variable for this run, overriding the script's saved value; repeat for each"
+    )]
+    pub env: Vec<(String, String)>,
+
+    #[arg(
+        long,
+        requires = "dry_run",
+        help = "With --dry-run, explain the safety check: matched patterns, hash, size, and line count"
+    )]
+    pub explain: bool,
+
     #[arg(
         long,
         help = "Run in a private temp directory with a minimal environment. \
@@ -165,18 +577,77 @@ pub struct RunArgs {
     )]
     pub sandbox: bool,
 
+    #[arg(
+        long,
+        value_name = "BOOL",
+        num_args = 0..=1,
+        default_missing_value = "true",
+        help = "Pass the usual environment (HOME, USER, LANG, ...) to the script; \
+            with false only PATH and allowed variables are passed [default: env_inherit in config]"
+    )]
+    pub env_inherit: Option<bool>,
+
+    #[arg(
+        long = "env-allow",
+        value_name = "NAME",
+        help = "With --env-inherit=false, also pass this variable; repeat for each"
+    )]
+    pub env_allow: Vec<String>,
+
+    #[arg(
+        long = "add-path",
+        value_name = "DIR",
+        help = "Put DIR in front of the script's PATH, ahead of prepend_path in config; repeat for each"
+    )]
+    pub add_path: Vec<String>,
+
     #[arg(long)]
     pub confirm: bool,
 
-    #[arg(long, short)]
-    pub verbose: bool,
+    #[arg(
+        long,
+        short,
+        action = clap::ArgAction::Count,
+        help = "Show script details while running; repeat (-vv, -vvv) for debug and trace logs"
+    )]
+    pub verbose: u8,
+
+    #[arg(
+        long,
+        value_name = "LEVEL",
+        value_parser = crate::logging::LOG_LEVELS,
+        help = "Set the log level (overrides -v and SCRIPTVAULT_LOG)"
+    )]
+    pub log_level: Option<String>,
 
     #[arg(long)]
     pub ci: bool,
 
+    #[arg(long, help = "Show a desktop notification when the script finishes")]
+    pub notify: bool,
+
+    #[arg(
+        long,
+        help = "Print the environment variables recorded with this run before executing"
+    )]
+    pub show_env: bool,
+
+    #[arg(
+        long,
+        help = "Prompt for every declared parameter not given as an argument, not just required ones"
+    )]
+    pub input_args: bool,
+
     #[arg(long)]
     pub update: bool,
 
+    #[arg(
+        long,
+        value_name = "CMD",
+        help = "Run with this interpreter instead of the one derived from the script language"
+    )]
+    pub interpreter: Option<String>,
+
     #[arg(
         long,
         value_name = "USER@HOST",
@@ -212,6 +683,27 @@ pub struct DeleteArgs {
 
     #[arg(long)]
     pub yes: bool,
+
+    #[arg(long, help = "Show what would be deleted without deleting anything")]
+    pub dry_run: bool,
+}
+
+#[derive(Args, Debug)]
+pub struct ArchiveArgs {
+    pub name: String,
+
+    #[arg(long, help = "Show what would change without changing anything")]
+    pub dry_run: bool,
+}
+
+#[derive(Args, Debug)]
+pub struct PinArgs {
+    pub name: String,
+}
+
+#[derive(Args, Debug)]
+pub struct TrustArgs {
+    pub name: String,
 }
 
 #[derive(Args, Debug)]
@@ -228,6 +720,9 @@ pub struct EditArgs {
 pub struct RenameArgs {
     pub old_name: String,
     pub new_name: String,
+
+    #[arg(long, help = "Show what would change without changing anything")]
+    pub dry_run: bool,
 }
 
 #[derive(Args, Debug)]
@@ -236,6 +731,22 @@ pub struct CopyArgs {
     pub dest: String,
 }
 
+#[derive(Args, Debug)]
+pub struct MergeArgs {
+    pub first: String,
+    pub second: String,
+
+    #[arg(
+        long,
+        value_name = "NAME",
+        help = "Name for the merged script (prompted for if omitted)"
+    )]
+    pub name: Option<String>,
+
+    #[arg(long, help = "Skip the preview and confirmation; requires --name")]
+    pub yes: bool,
+}
+
 #[derive(Args, Debug)]
 pub struct HistoryArgs {
     #[arg(value_name = "SCRIPT")]
@@ -249,11 +760,93 @@ pub struct HistoryArgs {
 
     #[arg(long)]
     pub team: bool,
+
+    #[arg(long, help = "Print only the number of matching runs")]
+    pub count: bool,
+
+    #[arg(
+        long,
+        conflicts_with = "count",
+        help = "Summarize the matching runs per script instead of listing them"
+    )]
+    pub stats: bool,
+
+    #[arg(
+        long,
+        conflicts_with_all = ["count", "stats"],
+        help = "Keep printing new runs as they are recorded until interrupted"
+    )]
+    pub follow: bool,
+
+    #[arg(
+        long,
+        value_name = "GROUP",
+        value_parser = ["user", "script", "day"],
+        conflicts_with_all = ["count", "stats", "follow"],
+        help = "Total the matching runs per user, script or calendar day"
+    )]
+    pub group_by: Option<String>,
+}
+
+#[derive(Args, Debug)]
+pub struct AuditArgs {
+    #[arg(
+        value_name = "SCRIPT",
+        help = "Only events for this script, including from before a rename"
+    )]
+    pub script: Option<String>,
+
+    #[arg(long, value_name = "USER", help = "Only events by this user")]
+    pub user: Option<String>,
+
+    #[arg(
+        long,
+        value_name = "ACTION",
+        value_parser = crate::audit::AUDIT_ACTIONS,
+        help = "Only events of this kind"
+    )]
+    pub action: Option<String>,
+
+    #[arg(
+        long,
+        value_name = "AGE",
+        value_parser = crate::utils::parse_age,
+        help = "Only events within AGE (e.g. 12h, 7d, 2w)"
+    )]
+    pub since: Option<chrono::Duration>,
+
+    #[arg(
+        long,
+        value_name = "N",
+        help = "Show at most N events, newest first [default: 50]"
+    )]
+    pub limit: Option<usize>,
 }
 
 #[derive(Args, Debug)]
 pub struct StatsArgs {
+    #[arg(help = "Script to show statistics for; omit for a vault-wide summary")]
+    pub name: Option<String>,
+
+    #[arg(
+        long,
+        conflicts_with = "name",
+        help = "Summarize execution across the whole vault"
+    )]
+    pub all: bool,
+}
+
+#[derive(Args, Debug)]
+pub struct TrendArgs {
     pub name: String,
+
+    #[arg(
+        long,
+        value_name = "BUCKET",
+        default_value = "daily",
+        help = "Group runs by day or week (daily, weekly)"
+    )]
+    pub bucket: String,
 }
 
 #[derive(Args, Debug)]
@@ -264,8 +857,20 @@ pub struct VersionArgs {
 #[derive(Args, Debug)]
 pub struct DiffArgs {
     pub name: String,
-    pub version1: String,
-    pub version2: String,
+
+    #[arg(required_unless_present = "file")]
+    pub version1: Option<String>,
+
+    #[arg(required_unless_present = "file")]
+    pub version2: Option<String>,
+
+    #[arg(
+        long,
+        value_name = "PATH",
+        conflicts_with_all = ["version1", "version2"],
+        help = "Compare the stored script with this file instead of two saved versions"
+    )]
+    pub file: Option<String>,
 }
 
 #[derive(Args, Debug)]
@@ -277,10 +882,13 @@ pub struct CheckoutArgs {
 pub struct ShareArgs {
     pub name: String,
 
-    #[arg(long)]
+    #[arg(long, conflicts_with = "public")]
     pub team: bool,
 
-    #[arg(long)]
+    #[arg(
+        long,
+        help = "Publish as a public gist and mark the script public (default: secret gist)"
+    )]
     pub public: bool,
 }
 
@@ -304,6 +912,62 @@ pub struct ExportArgs {
 
     #[arg(long, short)]
     pub output: Option<String>,
+
+    #[arg(long, value_name = "NAME", help = "Export only this script")]
+    pub single: Option<String>,
+
+    #[arg(
+        long,
+        value_name = "PATH",
+        conflicts_with_all = ["format", "output"],
+        help = "Write each script to its own file in a zip archive, with a manifest.json"
+    )]
+    pub zip: Option<String>,
+
+    #[arg(
+        long,
+        help = "Encrypt the export with a passphrase (needs --output or --zip)"
+    )]
+    pub encrypt: bool,
+}
+
+#[derive(Args, Debug)]
+pub struct ImportArgs {
+    #[arg(value_name = "FILE")]
+    pub file: String,
+
+    #[arg(long, help = "Replace scripts that already exist under the same name")]
+    pub overwrite: bool,
+
+    #[arg(
+        long,
+        help = "Import the valid scripts and skip malformed ones instead of aborting"
+    )]
+    pub skip_invalid: bool,
+
+    #[arg(long, help = "Decrypt a bundle written by sv export --encrypt")]
+    pub decrypt: bool,
+}
+
+#[derive(Args, Debug)]
+pub struct ImportDirArgs {
+    #[arg(value_name = "DIR")]
+    pub path: String,
+
+    #[arg(long, short, help = "Also import from subdirectories")]
+    pub recursive: bool,
+
+    #[arg(
+        long = "tag",
+        value_name = "TAG",
+        help = "Tag every imported script; repeat for several"
+    )]
+    pub tags: Vec<String>,
+    #[arg(
+        long,
+        help = "Also import files excluded by .gitignore and .ignore files"
+    )]
+    pub no_ignore: bool,
 }
 
 #[derive(Args, Debug)]
@@ -352,6 +1016,34 @@ pub enum StorageAction {
     Setup,
     Test,
     Info,
+    #[command(about = "Copy every script to another backend and optionally switch to it")]
+    Migrate(StorageMigrateArgs),
+}
+
+#[derive(Args, Debug)]
+pub struct StorageMigrateArgs {
+    #[arg(
+        long,
+        value_name = "PATH|URL",
+        help = "Target vault directory, or the http(s):// URL of an HTTP/WebDAV server"
+    )]
+    pub to: String,
+
+    #[arg(long, value_name = "TOKEN", help = "Bearer token for an HTTP target")]
+    pub token: Option<String>,
+
+    #[arg(
+        long,
+        help = "Switch to the target after a successful copy without asking"
+    )]
+    pub switch: bool,
+
+    #[arg(
+        long,
+        conflicts_with = "switch",
+        help = "Keep using the current backend without asking"
+    )]
+    pub no_switch: bool,
 }
 
 #[derive(Args, Debug)]
@@ -371,3 +1063,52 @@ pub struct AdaptArgs {
     #[arg(long, help = "Show what would change without applying anything")]
     pub dry_run: bool,
 }
+
+#[derive(Args, Debug)]
+pub struct OpenConfigArgs {
+    #[arg(
+        long,
+        help = "Open the config in $EDITOR; the edit is only saved if it is valid"
+    )]
+    pub edit: bool,
+}
+
+#[derive(Args, Debug)]
+pub struct DoctorArgs {
+    #[arg(
+        long,
+        help = "Also check every stored script: interpreter available and safety check passes"
+    )]
+    pub check_scripts: bool,
+
+    #[arg(
+        long,
+        help = "Repair duplicate script names and ids: keep the most recently updated script and rename and archive the rest"
+    )]
+    pub fix: bool,
+}
+
+#[derive(Args, Debug)]
+pub struct TemplateCommand {
+    #[command(subcommand)]
+    pub action: TemplateAction,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum TemplateAction {
+    List,
+    Add(TemplateAddArgs),
+}
+
+#[derive(Args, Debug)]
+pub struct TemplateAddArgs {
+    #[arg(value_name = "FILE")]
+    pub file: String,
+
+    #[arg(
+        long,
+        value_name = "NAME",
+        help = "Template name (defaults to the file name without extension)"
+    )]
+    pub name: Option<String>,
+}
@@ -0,0 +1,48 @@
+use std::io::IsTerminal;
+
+pub const COLOR_CHOICES: [&str; 3] = ["auto", "always", "never"];
+
+/// Decides whether to colorize output. An explicit `always` or `never` wins;
+/// `auto` honours `NO_COLOR` and turns color off when stdout isn't a terminal.
+pub fn should_colorize(choice: &str, no_color_env: bool, stdout_is_tty: bool) -> bool {
+    match choice {
+        "always" => true,
+        "never" => false,
+        _ => !no_color_env && stdout_is_tty,
+    }
+}
+
+pub fn init(choice: &str) {
+    let no_color_env = std::env::var_os("NO_COLOR").is_some_and(|v| !v.is_empty());
+    let enabled = should_colorize(choice, no_color_env, std::io::stdout().is_terminal());
+    colored::control::set_override(enabled);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use colored::*;
+
+    #[test]
+    fn test_never_disables_ansi_escapes() {
+        colored::control::set_override(should_colorize("never", false, true));
+        let output = format!("{} {}", "✓".green().bold(), "deploy".yellow());
+        colored::control::unset_override();
+
+        assert!(!output.contains('\x1b'));
+        assert_eq!(output, "✓ deploy");
+    }
+
+    #[test]
+    fn test_auto_respects_no_color_and_tty() {
+        assert!(should_colorize("auto", false, true));
+        assert!(!should_colorize("auto", true, true));
+        assert!(!should_colorize("auto", false, false));
+    }
+
+    #[test]
+    fn test_explicit_choice_overrides_environment() {
+        assert!(should_colorize("always", true, false));
+        assert!(!should_colorize("never", false, true));
+    }
+}
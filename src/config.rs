@@ -1,23 +1,24 @@
 use crate::constants::*;
+use crate::script::Visibility;
 use crate::storage::StorageConfig;
-use anyhow::{Context, Result};
+use anyhow::{Context, Result, anyhow};
 use serde::{Deserialize, Serialize};
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
 
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+/// Set by `--read-only`; applies on top of `Config::read_only` without being
+/// saved to the config file.
+static READ_ONLY_FLAG: AtomicBool = AtomicBool::new(false);
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Default)]
 pub enum AuthMode {
+    #[default]
     Local,
     ApiKey,
     OAuth,
 }
 
-impl Default for AuthMode {
-    fn default() -> Self {
-        Self::Local
-    }
-}
-
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Config {
     pub api_endpoint: String,
@@ -32,6 +33,57 @@ pub struct Config {
     pub storage: StorageConfig,
     #[serde(default)]
     pub auth_mode: AuthMode,
+    #[serde(default)]
+    pub webhook_url: Option<String>,
+    #[serde(default = "default_max_script_size")]
+    pub max_script_size_bytes: u64,
+    #[serde(default = "default_max_output")]
+    pub max_output_bytes: usize,
+    #[serde(default = "default_captured_env_vars")]
+    pub captured_env_vars: Vec<String>,
+    #[serde(default)]
+    pub github_token: Option<String>,
+    #[serde(default = "default_env_inherit")]
+    pub env_inherit: bool,
+    #[serde(default)]
+    pub env_allowlist: Vec<String>,
+    #[serde(default = "default_verify_integrity")]
+    pub verify_integrity: bool,
+    #[serde(default)]
+    pub read_only: bool,
+    #[serde(default)]
+    pub default_tags: Vec<String>,
+    /// Users allowed to run `Team` scripts under `sv run --check-permissions`.
+    #[serde(default)]
+    pub team_members: Vec<String>,
+    /// Directories put in front of PATH for `sv run`, e.g.
+    /// `node_modules/.bin`. Relative entries resolve against the current
+    /// directory, and ones that don't exist there are skipped.
+    #[serde(default)]
+    pub prepend_path: Vec<String>,
+}
+
+fn default_max_script_size() -> u64 {
+    DEFAULT_MAX_SCRIPT_SIZE_BYTES
+}
+
+fn default_max_output() -> usize {
+    DEFAULT_MAX_OUTPUT_BYTES
+}
+
+fn default_env_inherit() -> bool {
+    true
+}
+
+fn default_verify_integrity() -> bool {
+    true
+}
+
+fn default_captured_env_vars() -> Vec<String> {
+    DEFAULT_CAPTURED_ENV_VARS
+        .iter()
+        .map(|v| v.to_string())
+        .collect()
 }
 
 impl Default for Config {
@@ -39,7 +91,7 @@ impl Default for Config {
         let vault_path = Self::default_vault_path().unwrap_or_default();
         Self {
             api_endpoint: api_endpoint(),
-            storage: StorageConfig {
+            storage: StorageConfig::Local {
                 path: vault_path.clone(),
             },
             vault_path,
@@ -51,8 +103,53 @@ impl Default for Config {
             confirm_before_run: true,
             default_visibility: DEFAULT_VISIBILITY.to_string(),
             auth_mode: AuthMode::Local,
+            webhook_url: None,
+            max_script_size_bytes: DEFAULT_MAX_SCRIPT_SIZE_BYTES,
+            max_output_bytes: DEFAULT_MAX_OUTPUT_BYTES,
+            captured_env_vars: default_captured_env_vars(),
+            github_token: None,
+            env_inherit: true,
+            env_allowlist: Vec::new(),
+            verify_integrity: true,
+            read_only: false,
+            default_tags: Vec::new(),
+            team_members: Vec::new(),
+            prepend_path: Vec::new(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ConfigFormat {
+    Json,
+    Toml,
+    Yaml,
+}
+
+impl ConfigFormat {
+    pub fn from_path(path: &Path) -> Self {
+        match path.extension().and_then(|e| e.to_str()) {
+            Some("toml") => Self::Toml,
+            Some("yaml" | "yml") => Self::Yaml,
+            _ => Self::Json,
         }
     }
+
+    pub fn parse(self, contents: &str) -> Result<Config> {
+        Ok(match self {
+            Self::Json => serde_json::from_str(contents)?,
+            Self::Toml => toml::from_str(contents)?,
+            Self::Yaml => serde_yaml::from_str(contents)?,
+        })
+    }
+
+    pub fn render(self, config: &Config) -> Result<String> {
+        Ok(match self {
+            Self::Json => serde_json::to_string_pretty(config)?,
+            Self::Toml => toml::to_string_pretty(config)?,
+            Self::Yaml => serde_yaml::to_string(config)?,
+        })
+    }
 }
 
 impl Config {
@@ -61,8 +158,9 @@ impl Config {
 
         if path.exists() {
             let contents = fs::read_to_string(&path).context("Failed to read config file")?;
-            let config: Config =
-                serde_json::from_str(&contents).context("Failed to parse config file")?;
+            let config = ConfigFormat::from_path(&path)
+                .parse(&contents)
+                .context("Failed to parse config file")?;
             Ok(config)
         } else {
             let config = Self::default();
@@ -75,13 +173,42 @@ impl Config {
         let path = Self::config_path()?;
         let dir = path.parent().unwrap();
         fs::create_dir_all(dir)?;
-        let contents = serde_json::to_string_pretty(self)?;
+        let contents = ConfigFormat::from_path(&path).render(self)?;
         fs::write(&path, contents)?;
         Ok(())
     }
 
+    /// Rejects content larger than `max_script_size_bytes`. A limit of 0
+    /// disables the check.
+    pub fn check_script_size(&self, size_bytes: u64) -> Result<()> {
+        let limit = self.max_script_size_bytes;
+        if limit > 0 && size_bytes > limit {
+            return Err(anyhow!(
+                "Script is {} bytes, which exceeds the {} byte limit. \
+                 Raise max_script_size_bytes in the config to allow larger scripts.",
+                size_bytes,
+                limit
+            ));
+        }
+        Ok(())
+    }
+
     pub fn config_path() -> Result<PathBuf> {
-        Ok(Self::base_dir()?.join(CONFIG_FILE))
+        Ok(Self::config_path_in(&Self::base_dir()?))
+    }
+
+    /// `config.json` is used unless it is missing and a TOML or YAML config
+    /// exists instead, in which case that file is read and written back.
+    fn config_path_in(dir: &Path) -> PathBuf {
+        let json = dir.join(CONFIG_FILE);
+        if json.exists() {
+            return json;
+        }
+        CONFIG_FILE_ALTERNATIVES
+            .iter()
+            .map(|name| dir.join(name))
+            .find(|path| path.exists())
+            .unwrap_or(json)
     }
 
     pub fn base_dir() -> Result<PathBuf> {
@@ -114,6 +241,10 @@ impl Config {
         Ok(Self::data_dir()?.join(HISTORY_FILE))
     }
 
+    pub fn events_path() -> Result<PathBuf> {
+        Ok(Self::data_dir()?.join(EVENTS_FILE))
+    }
+
     fn default_vault_path() -> Result<PathBuf> {
         Self::vault_dir()
     }
@@ -158,11 +289,45 @@ impl Config {
     }
 
     pub fn get_storage_backend(&self) -> Result<Box<dyn crate::storage::StorageBackend>> {
-        crate::storage::create_storage_backend(&self.storage)
+        let backend = crate::storage::create_storage_backend(&self.storage)?;
+        if self.is_read_only() {
+            return Ok(Box::new(crate::storage::read_only::ReadOnlyStorage::new(
+                backend,
+            )));
+        }
+        Ok(backend)
+    }
+
+    /// Makes the vault read-only for the rest of this process.
+    pub fn force_read_only() {
+        READ_ONLY_FLAG.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_read_only(&self) -> bool {
+        self.read_only || READ_ONLY_FLAG.load(Ordering::Relaxed)
+    }
+
+    /// Fails before a command changes anything when the vault is read-only.
+    /// The storage backend refuses writes too; checking up front avoids
+    /// prompts and side files for a change that cannot be saved.
+    pub fn ensure_writable(&self) -> Result<()> {
+        if self.is_read_only() {
+            return Err(crate::storage::read_only::read_only_error());
+        }
+        Ok(())
+    }
+
+    /// Visibility for newly saved scripts, from `default_visibility`.
+    pub fn default_visibility(&self) -> Result<Visibility> {
+        self.default_visibility
+            .parse()
+            .map_err(|e| anyhow!("Invalid default_visibility in config: {}", e))
     }
 
     pub fn set_storage(&mut self, storage: StorageConfig) -> Result<()> {
-        self.vault_path = storage.path.clone();
+        if let Some(path) = storage.local_path() {
+            self.vault_path = path.to_path_buf();
+        }
         self.storage = storage;
         self.save()?;
         Ok(())
@@ -184,6 +349,49 @@ mod tests {
         assert_eq!(config.auth_mode, AuthMode::Local);
     }
 
+    #[test]
+    fn test_default_visibility_parsing() {
+        let mut config = Config::default();
+        assert_eq!(config.default_visibility().unwrap(), Visibility::Private);
+        config.default_visibility = "Team".to_string();
+        assert_eq!(config.default_visibility().unwrap(), Visibility::Team);
+        config.default_visibility = "everyone".to_string();
+        assert_eq!(
+            config.default_visibility().unwrap_err().to_string(),
+            "Invalid default_visibility in config: 'everyone' is not a visibility; expected private, team or public"
+        );
+    }
+
+    #[test]
+    fn test_script_size_limit() {
+        let mut config = Config::default();
+        assert_eq!(config.max_script_size_bytes, DEFAULT_MAX_SCRIPT_SIZE_BYTES);
+        assert!(
+            config
+                .check_script_size(DEFAULT_MAX_SCRIPT_SIZE_BYTES)
+                .is_ok()
+        );
+        assert!(
+            config
+                .check_script_size(DEFAULT_MAX_SCRIPT_SIZE_BYTES + 1)
+                .is_err()
+        );
+
+        config.max_script_size_bytes = 0;
+        assert!(config.check_script_size(u64::MAX).is_ok());
+    }
+
+    #[test]
+    fn test_max_script_size_defaults_when_missing_from_file() {
+        let mut value = serde_json::to_value(Config::default()).unwrap();
+        value
+            .as_object_mut()
+            .unwrap()
+            .remove("max_script_size_bytes");
+        let config: Config = serde_json::from_value(value).unwrap();
+        assert_eq!(config.max_script_size_bytes, DEFAULT_MAX_SCRIPT_SIZE_BYTES);
+    }
+
     #[test]
     fn test_is_authenticated_false_by_default() {
         assert!(!Config::default().is_authenticated());
@@ -259,4 +467,112 @@ mod tests {
         assert!(config.user_id.is_none());
         assert_eq!(config.username, Some("localuser".to_string()));
     }
+
+    const JSON_CONFIG: &str = r#"{
+        "api_endpoint": "https://example.test/v1",
+        "vault_path": "/srv/vault",
+        "auth_token": null,
+        "user_id": null,
+        "username": "ricky",
+        "team_id": null,
+        "auto_sync": false,
+        "confirm_before_run": false,
+        "default_visibility": "private",
+        "storage": { "path": "/srv/vault" },
+        "max_output_bytes": 1024
+    }"#;
+
+    const TOML_CONFIG: &str = r#"
+api_endpoint = "https://example.test/v1"
+vault_path = "/srv/vault"
+username = "ricky"
+auto_sync = false
+confirm_before_run = false
+default_visibility = "private"
+max_output_bytes = 1024
+
+[storage]
+path = "/srv/vault"
+"#;
+
+    const YAML_CONFIG: &str = r#"
+api_endpoint: https://example.test/v1
+vault_path: /srv/vault
+username: ricky
+auto_sync: false
+confirm_before_run: false
+default_visibility: private
+max_output_bytes: 1024
+storage:
+  path: /srv/vault
+"#;
+
+    fn assert_example_config(config: &Config) {
+        assert_eq!(config.api_endpoint, "https://example.test/v1");
+        assert_eq!(config.vault_path, PathBuf::from("/srv/vault"));
+        assert_eq!(config.storage.local_path(), Some(Path::new("/srv/vault")));
+        assert_eq!(config.username.as_deref(), Some("ricky"));
+        assert!(!config.confirm_before_run);
+        assert_eq!(config.max_output_bytes, 1024);
+        assert_eq!(config.max_script_size_bytes, DEFAULT_MAX_SCRIPT_SIZE_BYTES);
+        assert_eq!(config.auth_mode, AuthMode::Local);
+    }
+
+    #[test]
+    fn test_equivalent_config_in_each_format() {
+        for (format, contents) in [
+            (ConfigFormat::Json, JSON_CONFIG),
+            (ConfigFormat::Toml, TOML_CONFIG),
+            (ConfigFormat::Yaml, YAML_CONFIG),
+        ] {
+            let config = format.parse(contents).unwrap();
+            assert_example_config(&config);
+
+            let round_trip = format.parse(&format.render(&config).unwrap()).unwrap();
+            assert_example_config(&round_trip);
+        }
+    }
+
+    #[test]
+    fn test_config_format_from_extension() {
+        assert_eq!(
+            ConfigFormat::from_path(Path::new("config.json")),
+            ConfigFormat::Json
+        );
+        assert_eq!(
+            ConfigFormat::from_path(Path::new("config.toml")),
+            ConfigFormat::Toml
+        );
+        assert_eq!(
+            ConfigFormat::from_path(Path::new("config.yml")),
+            ConfigFormat::Yaml
+        );
+    }
+
+    #[test]
+    fn test_config_path_prefers_json_then_alternatives() {
+        let dir = tempfile::TempDir::new().unwrap();
+        assert_eq!(
+            Config::config_path_in(dir.path()),
+            dir.path().join("config.json")
+        );
+
+        fs::write(dir.path().join("config.yaml"), YAML_CONFIG).unwrap();
+        assert_eq!(
+            Config::config_path_in(dir.path()),
+            dir.path().join("config.yaml")
+        );
+
+        fs::write(dir.path().join("config.toml"), TOML_CONFIG).unwrap();
+        assert_eq!(
+            Config::config_path_in(dir.path()),
+            dir.path().join("config.toml")
+        );
+
+        fs::write(dir.path().join("config.json"), JSON_CONFIG).unwrap();
+        assert_eq!(
+            Config::config_path_in(dir.path()),
+            dir.path().join("config.json")
+        );
+    }
 }
@@ -4,12 +4,28 @@ pub const DEFAULT_VERSION: &str = "v1.0.0";
 pub const DEFAULT_VISIBILITY: &str = "private";
 pub const SCRIPTVAULT_DIR: &str = ".scriptvault";
 pub const CONFIG_FILE: &str = "config.json";
-#[allow(dead_code)]
+/// Hand-edited alternatives to `config.json`, checked in this order.
+pub const CONFIG_FILE_ALTERNATIVES: &[&str] = &["config.toml", "config.yaml", "config.yml"];
 pub const SCRIPTS_FILE: &str = "scripts.json";
 pub const HISTORY_FILE: &str = "history.jsonl";
+pub const EVENTS_FILE: &str = "events.jsonl";
 pub const VAULT_DIR: &str = "vault";
 pub const DEFAULT_HISTORY_LIMIT: usize = 20;
 pub const MAX_HISTORY_ENTRIES: usize = 1000;
+pub const DEFAULT_MAX_SCRIPT_SIZE_BYTES: u64 = 1024 * 1024;
+pub const DEFAULT_MAX_OUTPUT_BYTES: usize = 64 * 1024;
+pub const DEFAULT_CAPTURED_ENV_VARS: &[&str] = &["SHELL", "USER"];
+/// Exit code stored for runs logged with `sv run --record-only`. Real exit
+/// codes are never negative, so it cannot collide with an actual run.
+pub const NOT_EXECUTED_EXIT_CODE: i32 = -1;
+/// Exit code stored for a run whose interpreter could not be started because
+/// it isn't installed. Recorded as a failed run.
+pub const INTERPRETER_NOT_FOUND_EXIT_CODE: i32 = -2;
+/// Exit code stored for a run stopped by `sv run --timeout`, the same one GNU
+/// `timeout` uses.
+pub const TIMED_OUT_EXIT_CODE: i32 = 124;
+/// Seconds a script gets to exit after SIGTERM before it is killed.
+pub const DEFAULT_KILL_GRACE_SECS: u64 = 5;
 
 pub const DANGEROUS_PATTERNS: &[&str] = &[
     "rm -rf /",
@@ -25,12 +41,16 @@ pub const DANGEROUS_PATTERNS: &[&str] = &[
     ":(){:|:&};:",
 ];
 
-#[allow(dead_code)]
 pub const SUPPORTED_EXTENSIONS: &[&str] =
     &["sh", "bash", "py", "js", "rb", "pl", "ps1", "bat", "cmd"];
 
 pub const ENV_SCRIPTVAULT_HOME: &str = "SCRIPTVAULT_HOME";
 pub const ENV_SCRIPTVAULT_CI: &str = "SCRIPTVAULT_CI";
+pub const ENV_SCRIPTVAULT_LOG: &str = "SCRIPTVAULT_LOG";
+pub const ENV_SCRIPTVAULT_TMPDIR: &str = "SCRIPTVAULT_TMPDIR";
+pub const ENV_SCRIPTVAULT_PASSPHRASE: &str = "SCRIPTVAULT_PASSPHRASE";
+/// Standard shell variable; overrides the detected terminal width.
+pub const ENV_COLUMNS: &str = "COLUMNS";
 
 pub const BASH_INTERPRETER: &str = "bash";
 pub const SHELL_INTERPRETER: &str = "sh";
@@ -39,15 +59,10 @@ pub const RUBY_INTERPRETER: &str = "ruby";
 pub const PERL_INTERPRETER: &str = "perl";
 pub const POWERSHELL_INTERPRETER: &str = "powershell";
 
-#[allow(dead_code)]
 pub const BASH_SHEBANG: &str = "#!/usr/bin/env bash";
-#[allow(dead_code)]
 pub const SHELL_SHEBANG: &str = "#!/bin/sh";
-#[allow(dead_code)]
 pub const PYTHON_SHEBANG: &str = "#!/usr/bin/env python3";
-#[allow(dead_code)]
 pub const RUBY_SHEBANG: &str = "#!/usr/bin/env ruby";
-#[allow(dead_code)]
 pub const PERL_SHEBANG: &str = "#!/usr/bin/env perl";
 
 pub fn default_author() -> String {
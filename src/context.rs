@@ -1,3 +1,4 @@
+use crate::config::Config;
 use crate::script::ScriptContext;
 use anyhow::Result;
 use colored::*;
@@ -6,29 +7,56 @@ use std::collections::HashMap;
 use std::env;
 
 pub fn detect_context() -> Result<ScriptContext> {
+    let config = Config::load()?;
+    detect_context_with(&config.captured_env_vars)
+}
+
+pub fn detect_context_with(captured_env_vars: &[String]) -> Result<ScriptContext> {
     let directory = env::current_dir()
         .ok()
         .map(|p| p.to_string_lossy().to_string());
 
     let (git_repo, git_branch) = detect_git_context();
 
-    let mut environment = HashMap::new();
-
-    if let Ok(shell) = env::var("SHELL") {
-        environment.insert("SHELL".to_string(), shell);
-    }
-    if let Ok(user) = env::var("USER") {
-        environment.insert("USER".to_string(), user);
-    }
+    let environment = capture_environment(captured_env_vars, |name| env::var(name).ok());
 
     Ok(ScriptContext {
         directory,
         git_repo,
         git_branch,
         environment,
+        os: Some(env::consts::OS.to_string()),
+        arch: Some(env::consts::ARCH.to_string()),
+        hostname: detect_hostname(),
     })
 }
 
+/// Collects the named variables that are set; unset ones are left out.
+pub fn capture_environment(
+    names: &[String],
+    lookup: impl Fn(&str) -> Option<String>,
+) -> HashMap<String, String> {
+    names
+        .iter()
+        .filter_map(|name| lookup(name).map(|value| (name.clone(), value)))
+        .collect()
+}
+
+pub fn format_environment(environment: &HashMap<String, String>) -> String {
+    let mut keys: Vec<&String> = environment.keys().collect();
+    keys.sort();
+    keys.into_iter()
+        .map(|key| format!("  {}={}\n", key, environment[key]))
+        .collect()
+}
+
+fn detect_hostname() -> Option<String> {
+    hostname::get()
+        .ok()
+        .map(|h| h.to_string_lossy().to_string())
+        .filter(|h| !h.is_empty())
+}
+
 fn detect_git_context() -> (Option<String>, Option<String>) {
     let current_dir = match env::current_dir() {
         Ok(dir) => dir,
@@ -86,6 +114,13 @@ pub fn show_context() -> Result<()> {
         );
     }
 
+    if let (Some(os), Some(arch)) = (&ctx.os, &ctx.arch) {
+        println!("  {}: {}/{}", "Platform".bold(), os, arch);
+    }
+    if let Some(hostname) = ctx.hostname {
+        println!("  {}: {}", "Host".bold(), hostname);
+    }
+
     if !ctx.environment.is_empty() {
         println!();
         println!("  {}:", "Environment".bold());
@@ -106,10 +141,10 @@ pub fn contexts_match(ctx1: &ScriptContext, ctx2: &ScriptContext) -> bool {
         return true;
     }
 
-    if let (Some(dir1), Some(dir2)) = (&ctx1.directory, &ctx2.directory) {
-        if dir1.starts_with(dir2.as_str()) || dir2.starts_with(dir1.as_str()) {
-            return true;
-        }
+    if let (Some(dir1), Some(dir2)) = (&ctx1.directory, &ctx2.directory)
+        && (dir1.starts_with(dir2.as_str()) || dir2.starts_with(dir1.as_str()))
+    {
+        return true;
     }
 
     false
@@ -0,0 +1,163 @@
+use crate::script::{ScriptParam, Visibility};
+use crate::templates::parse_var;
+
+/// Settings a script declares about itself in `# sv:<key> <value>` comments
+/// at the top of the file. `sv save` uses them as defaults; flags still win.
+#[derive(Debug, Default, PartialEq)]
+pub struct Directives {
+    pub tags: Vec<String>,
+    pub description: Option<String>,
+    pub visibility: Option<Visibility>,
+    pub params: Vec<ScriptParam>,
+    pub env: Vec<(String, String)>,
+    pub success_codes: Vec<i32>,
+    /// One message per directive that could not be used.
+    pub problems: Vec<String>,
+}
+
+/// Line comment markers for the supported languages. `::` and `REM` are
+/// batch, `--` is Lua and SQL.
+const COMMENT_PREFIXES: &[&str] = &["#", "//", "--", "::", "REM ", "rem "];
+
+fn comment_body(line: &str) -> Option<&str> {
+    let line = line.trim_start();
+    COMMENT_PREFIXES
+        .iter()
+        .find_map(|prefix| line.strip_prefix(prefix))
+}
+
+/// Reads directives from the leading comment block: the shebang, blank lines
+/// and comments up to the first line of code. Directives further down are
+/// ignored so an `sv:` inside a heredoc or string is never picked up.
+pub fn parse(content: &str) -> Directives {
+    let mut directives = Directives::default();
+    for line in content.lines() {
+        if line.trim().is_empty() {
+            continue;
+        }
+        let Some(body) = comment_body(line) else {
+            break;
+        };
+        let Some(directive) = body.trim_start().strip_prefix("sv:") else {
+            continue;
+        };
+        let (key, value) = match directive.split_once(char::is_whitespace) {
+            Some((key, value)) => (key, value.trim()),
+            None => (directive, ""),
+        };
+        directives.apply(key, value);
+    }
+    directives
+}
+
+impl Directives {
+    fn apply(&mut self, key: &str, value: &str) {
+        if value.is_empty() {
+            self.problems.push(format!("sv:{} needs a value", key));
+            return;
+        }
+        match key {
+            "tags" => {
+                for tag in value.split(|c: char| c == ',' || c.is_whitespace()) {
+                    if !tag.is_empty() && !self.tags.iter().any(|t| t == tag) {
+                        self.tags.push(tag.to_string());
+                    }
+                }
+            }
+            "description" => self.description = Some(value.to_string()),
+            "visibility" => match value.parse() {
+                Ok(visibility) => self.visibility = Some(visibility),
+                Err(e) => self.problems.push(format!("sv:visibility: {}", e)),
+            },
+            "param" => match ScriptParam::parse(value) {
+                Ok(param) => self.params.push(param),
+                Err(e) => self.problems.push(format!("sv:param: {}", e)),
+            },
+            "env" => match parse_var(value) {
+                Ok(pair) => self.env.push(pair),
+                Err(e) => self.problems.push(format!("sv:env: {}", e)),
+            },
+            "success-codes" => {
+                let codes: Result<Vec<i32>, _> = value
+                    .split(',')
+                    .map(|code| code.trim().parse::<i32>())
+                    .collect();
+                match codes {
+                    Ok(codes) => self.success_codes = codes,
+                    Err(_) => self.problems.push(format!(
+                        "sv:success-codes: expected comma-separated exit codes, got '{}'",
+                        value
+                    )),
+                }
+            }
+            _ => self.problems.push(format!("unknown directive sv:{}", key)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_header_directives() {
+        let content = "#!/usr/bin/env bash\n\
+                       # sv:tags deploy,prod\n\
+                       # sv:tags ops deploy\n\
+                       #sv:description Deploy the API to production\n\
+                       # sv:visibility team\n\
+                       # sv:param env:Target environment=staging\n\
+                       # sv:env REGION=eu-west-1\n\
+                       # sv:success-codes 0, 3\n\
+                       \n\
+                       # Plain comment\n\
+                       set -e\n\
+                       # sv:tags ignored\n";
+        let directives = parse(content);
+        assert_eq!(directives.tags, ["deploy", "prod", "ops"]);
+        assert_eq!(
+            directives.description.as_deref(),
+            Some("Deploy the API to production")
+        );
+        assert_eq!(directives.visibility, Some(Visibility::Team));
+        assert_eq!(directives.params.len(), 1);
+        assert_eq!(directives.params[0].name, "env");
+        assert_eq!(directives.params[0].default.as_deref(), Some("staging"));
+        assert_eq!(
+            directives.env,
+            [("REGION".to_string(), "eu-west-1".to_string())]
+        );
+        assert_eq!(directives.success_codes, [0, 3]);
+        assert!(directives.problems.is_empty(), "{:?}", directives.problems);
+    }
+
+    #[test]
+    fn test_parse_other_comment_styles() {
+        let directives = parse("// sv:tags js\n-- sv:description Lua tool\nREM sv:tags bat\n");
+        assert_eq!(directives.tags, ["js", "bat"]);
+        assert_eq!(directives.description.as_deref(), Some("Lua tool"));
+    }
+
+    #[test]
+    fn test_parse_reports_bad_directives() {
+        let directives = parse(
+            "# sv:timeout 30\n# sv:visibility everyone\n# sv:success-codes 0,x\n# sv:description\n",
+        );
+        assert_eq!(
+            directives.problems,
+            [
+                "unknown directive sv:timeout",
+                "sv:visibility: 'everyone' is not a visibility; expected private, team or public",
+                "sv:success-codes: expected comma-separated exit codes, got '0,x'",
+                "sv:description needs a value",
+            ]
+        );
+        assert_eq!(directives.visibility, None);
+        assert!(directives.success_codes.is_empty());
+    }
+
+    #[test]
+    fn test_no_directives() {
+        assert_eq!(parse("echo hi\n# sv:tags late\n"), Directives::default());
+    }
+}
@@ -0,0 +1,190 @@
+use crate::constants::ENV_SCRIPTVAULT_PASSPHRASE;
+use aes_gcm::aead::{Aead, AeadCore, KeyInit, OsRng, Payload, rand_core::RngCore};
+use aes_gcm::{Aes256Gcm, Key, Nonce};
+use anyhow::{Result, anyhow};
+use argon2::{Algorithm, Argon2, Params, Version};
+use dialoguer::Password;
+use std::io::IsTerminal;
+
+/// First bytes of every encrypted bundle, followed by a format version.
+const MAGIC: &[u8] = b"SVBUNDLE";
+const FORMAT_VERSION: u8 = 1;
+const SALT_LEN: usize = 16;
+const NONCE_LEN: usize = 12;
+/// Magic, version, the three Argon2 parameters, salt and nonce.
+const HEADER_LEN: usize = MAGIC.len() + 1 + 12 + SALT_LEN + NONCE_LEN;
+
+/// Argon2id cost settings. They are stored in the header, so bundles stay
+/// readable if the defaults change.
+#[derive(Debug, Clone, Copy)]
+struct KdfParams {
+    m_cost: u32,
+    t_cost: u32,
+    p_cost: u32,
+}
+
+impl Default for KdfParams {
+    fn default() -> Self {
+        Self {
+            m_cost: Params::DEFAULT_M_COST,
+            t_cost: Params::DEFAULT_T_COST,
+            p_cost: Params::DEFAULT_P_COST,
+        }
+    }
+}
+
+fn derive_key(passphrase: &str, salt: &[u8], params: KdfParams) -> Result<Key<Aes256Gcm>> {
+    let params = Params::new(params.m_cost, params.t_cost, params.p_cost, Some(32))
+        .map_err(|e| anyhow!("Invalid key derivation parameters: {}", e))?;
+    let mut key = Key::<Aes256Gcm>::default();
+    Argon2::new(Algorithm::Argon2id, Version::V0x13, params)
+        .hash_password_into(passphrase.as_bytes(), salt, &mut key)
+        .map_err(|e| anyhow!("Key derivation failed: {}", e))?;
+    Ok(key)
+}
+
+/// True when `bytes` starts with the encrypted bundle header.
+pub fn is_encrypted(bytes: &[u8]) -> bool {
+    bytes.starts_with(MAGIC)
+}
+
+/// Encrypts an export with AES-256-GCM under a key derived from
+/// `passphrase`. The header is authenticated along with the data.
+pub fn encrypt(plaintext: &[u8], passphrase: &str) -> Result<Vec<u8>> {
+    encrypt_with(plaintext, passphrase, KdfParams::default())
+}
+
+fn encrypt_with(plaintext: &[u8], passphrase: &str, params: KdfParams) -> Result<Vec<u8>> {
+    let mut salt = [0u8; SALT_LEN];
+    OsRng.fill_bytes(&mut salt);
+    let nonce = Aes256Gcm::generate_nonce(&mut OsRng);
+
+    let mut bundle = Vec::with_capacity(HEADER_LEN + plaintext.len() + 16);
+    bundle.extend_from_slice(MAGIC);
+    bundle.push(FORMAT_VERSION);
+    for value in [params.m_cost, params.t_cost, params.p_cost] {
+        bundle.extend_from_slice(&value.to_le_bytes());
+    }
+    bundle.extend_from_slice(&salt);
+    bundle.extend_from_slice(&nonce);
+
+    let cipher = Aes256Gcm::new(&derive_key(passphrase, &salt, params)?);
+    let ciphertext = cipher
+        .encrypt(
+            &nonce,
+            Payload {
+                msg: plaintext,
+                aad: &bundle,
+            },
+        )
+        .map_err(|_| anyhow!("Encryption failed"))?;
+    bundle.extend_from_slice(&ciphertext);
+    Ok(bundle)
+}
+
+/// Reverses `encrypt`. A wrong passphrase and a damaged bundle both fail
+/// authentication and can't be told apart.
+pub fn decrypt(bundle: &[u8], passphrase: &str) -> Result<Vec<u8>> {
+    if !is_encrypted(bundle) {
+        return Err(anyhow!("Not an encrypted ScriptVault bundle"));
+    }
+    if bundle.len() < HEADER_LEN {
+        return Err(anyhow!("Encrypted bundle is truncated"));
+    }
+    let version = bundle[MAGIC.len()];
+    if version != FORMAT_VERSION {
+        return Err(anyhow!(
+            "Unsupported encrypted bundle version {}; upgrade sv to read it",
+            version
+        ));
+    }
+
+    let (header, ciphertext) = bundle.split_at(HEADER_LEN);
+    let u32_at = |offset: usize| {
+        let start = MAGIC.len() + 1 + offset * 4;
+        u32::from_le_bytes(header[start..start + 4].try_into().unwrap())
+    };
+    let params = KdfParams {
+        m_cost: u32_at(0),
+        t_cost: u32_at(1),
+        p_cost: u32_at(2),
+    };
+    let salt_start = MAGIC.len() + 1 + 12;
+    let salt = &header[salt_start..salt_start + SALT_LEN];
+    let nonce = Nonce::from_slice(&header[salt_start + SALT_LEN..]);
+
+    let cipher = Aes256Gcm::new(&derive_key(passphrase, salt, params)?);
+    cipher
+        .decrypt(
+            nonce,
+            Payload {
+                msg: ciphertext,
+                aad: header,
+            },
+        )
+        .map_err(|_| anyhow!("Wrong passphrase, or the bundle is damaged"))
+}
+
+/// The passphrase from `SCRIPTVAULT_PASSPHRASE`, or else prompted for.
+/// `confirm` asks twice, for encryption, where a typo would lock the export.
+pub fn passphrase(confirm: bool) -> Result<String> {
+    if let Ok(value) = std::env::var(ENV_SCRIPTVAULT_PASSPHRASE)
+        && !value.is_empty()
+    {
+        return Ok(value);
+    }
+    if !std::io::stdin().is_terminal() {
+        return Err(anyhow!(
+            "No passphrase given. Set {} or run in a terminal to be prompted.",
+            ENV_SCRIPTVAULT_PASSPHRASE
+        ));
+    }
+    let mut prompt = Password::new().with_prompt("Passphrase");
+    if confirm {
+        prompt = prompt.with_confirmation("Confirm passphrase", "Passphrases don't match");
+    }
+    let value = prompt.interact()?;
+    if value.is_empty() {
+        return Err(anyhow!("Passphrase cannot be empty"));
+    }
+    Ok(value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Cheap settings so the tests don't spend seconds in the KDF.
+    const FAST: KdfParams = KdfParams {
+        m_cost: 64,
+        t_cost: 1,
+        p_cost: 1,
+    };
+
+    #[test]
+    fn test_round_trip() {
+        let bundle = encrypt_with(b"{\"scripts\": []}", "hunter2", FAST).unwrap();
+        assert!(is_encrypted(&bundle));
+        assert!(!bundle.windows(8).any(|w| w == b"scripts"));
+        assert_eq!(decrypt(&bundle, "hunter2").unwrap(), b"{\"scripts\": []}");
+    }
+
+    #[test]
+    fn test_wrong_passphrase_is_rejected() {
+        let bundle = encrypt_with(b"secret", "hunter2", FAST).unwrap();
+        let err = decrypt(&bundle, "hunter3").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Wrong passphrase, or the bundle is damaged"
+        );
+    }
+
+    #[test]
+    fn test_tampered_header_is_rejected() {
+        let mut bundle = encrypt_with(b"secret", "hunter2", FAST).unwrap();
+        bundle[HEADER_LEN - 1] ^= 1;
+        assert!(decrypt(&bundle, "hunter2").is_err());
+        assert!(decrypt(b"SVBUNDLE", "hunter2").is_err());
+        assert!(decrypt(b"{}", "hunter2").is_err());
+    }
+}
@@ -1,18 +1,25 @@
-use crate::cli::{HistoryArgs, RunArgs};
+use crate::cli::{HistoryArgs, RunArgs, TrendArgs};
 use crate::config::Config;
 use crate::constants::*;
 use crate::context;
-use crate::script::{ExecutionRecord, Script, ScriptLanguage};
+use crate::notify;
+use crate::script::{
+    ExecutionRecord, PhaseTimings, ResourceUsage, Script, ScriptLanguage, ScriptParam, Visibility,
+};
+use crate::utils::{color_success_rate, human_size, humanize_duration};
 use crate::vault::{load_scripts_local, update_script_metadata};
+use crate::webhook;
 use anyhow::{Result, anyhow};
+use chrono::{DateTime, Datelike, Duration, NaiveDate, Utc};
 use colored::*;
-use dialoguer::Confirm;
-use std::collections::HashMap;
+use dialoguer::{Confirm, Input};
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::fs::{self, OpenOptions};
 use std::io::{BufRead, BufReader, Write};
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::process::{Command, Stdio};
-use std::time::Instant;
+use std::sync::{Arc, OnceLock};
+use std::time::{Instant, SystemTime};
 
 const SAFE_ENV_VARS: &[&str] = &[
     "PATH", "TERM", "LANG", "LC_ALL", "LC_CTYPE", "HOME", "USER", "LOGNAME", "SHELL", "TZ",
@@ -29,20 +36,188 @@ fn build_safe_env() -> HashMap<String, String> {
     env
 }
 
-fn check_interpreter_available(language: &ScriptLanguage) -> Result<()> {
-    let (interpreter, _) = get_interpreter_command(language);
-    which::which(interpreter).map_err(|_| {
-        anyhow!(
-            "Required interpreter '{}' not found in PATH. Install it before running this script.",
-            interpreter
+/// Only PATH plus the explicitly allowed names, for runs with `env_inherit`
+/// turned off. Variables unset in the parent are skipped.
+fn build_minimal_env(
+    allow: &[String],
+    lookup: impl Fn(&str) -> Option<String>,
+) -> HashMap<String, String> {
+    std::iter::once("PATH")
+        .chain(allow.iter().map(String::as_str))
+        .filter_map(|key| lookup(key).map(|val| (key.to_string(), val)))
+        .collect()
+}
+
+/// Environment for the script process. `allow` is `Some` when `env_inherit`
+/// is off; under `--sandbox` the sandbox's HOME and TMPDIR are still set.
+/// The script's own `env` is applied last, so it wins over both.
+fn script_env(
+    script: &Script,
+    sandbox_dir: Option<&Path>,
+    allow: Option<&[String]>,
+) -> HashMap<String, String> {
+    let lookup = |key: &str| std::env::var(key).ok();
+    let mut env = match (sandbox_dir, allow) {
+        (None, None) => build_safe_env(),
+        (None, Some(allow)) => build_minimal_env(allow, lookup),
+        (Some(dir), None) => build_isolated_env(dir),
+        (Some(dir), Some(allow)) => {
+            let mut env = build_minimal_env(allow, lookup);
+            let dir = dir.to_string_lossy().into_owned();
+            env.insert("HOME".into(), dir.clone());
+            env.insert("TMPDIR".into(), dir);
+            env.insert("ISOLATED".into(), "1".into());
+            env
+        }
+    };
+    env.extend(script.env.clone());
+    env
+}
+
+/// Directories to put in front of the script's PATH: `--add-path` ones
+/// first, then `prepend_path` from config, as absolute paths. A missing
+/// `--add-path` directory is an error; config entries are meant to be
+/// project-local, so ones missing from this project are skipped.
+fn path_dirs(add_path: &[String], configured: &[String]) -> Result<Vec<PathBuf>> {
+    let mut dirs: Vec<PathBuf> = Vec::new();
+    for dir in add_path {
+        let resolved = fs::canonicalize(dir)
+            .ok()
+            .filter(|p| p.is_dir())
+            .ok_or_else(|| anyhow!("--add-path {}: no such directory", dir))?;
+        dirs.push(resolved);
+    }
+    for dir in configured {
+        match fs::canonicalize(dir) {
+            Ok(resolved) if resolved.is_dir() => dirs.push(resolved),
+            _ => tracing::debug!(dir, "skipping prepend_path entry that is not a directory"),
+        }
+    }
+    let mut seen = HashSet::new();
+    dirs.retain(|d| seen.insert(d.clone()));
+    Ok(dirs)
+}
+
+/// `dirs` followed by the entries of `path`, joined with the platform's
+/// PATH separator.
+fn prepend_path(dirs: &[PathBuf], path: Option<&str>) -> Result<String> {
+    let existing = path.map(std::env::split_paths).into_iter().flatten();
+    let joined = std::env::join_paths(dirs.iter().cloned().chain(existing))
+        .map_err(|e| anyhow!("Cannot add directory to PATH: {}", e))?;
+    Ok(joined.to_string_lossy().into_owned())
+}
+
+/// How much of a local run reaches the terminal.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Echo {
+    /// The script's output is streamed as it runs.
+    Normal,
+    /// As `Normal`, plus the script content, interpreter and phase timings.
+    Verbose,
+    /// Nothing is streamed; the output is only captured, for
+    /// `--output-format json`.
+    Quiet,
+}
+
+struct Interpreter {
+    program: String,
+    args: Vec<String>,
+}
+
+impl Interpreter {
+    /// The program and its fixed arguments, as shown by `sv run --verbose`.
+    fn command_line(&self) -> String {
+        std::iter::once(&self.program)
+            .chain(&self.args)
+            .map(String::as_str)
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+}
+
+fn resolve_interpreter(language: &ScriptLanguage, override_cmd: Option<&str>) -> Interpreter {
+    if let Some(cmd) = override_cmd {
+        let mut parts = cmd.split_whitespace();
+        if let Some(program) = parts.next() {
+            return Interpreter {
+                program: program.to_string(),
+                args: parts.map(|s| s.to_string()).collect(),
+            };
+        }
+    }
+
+    let (program, args) = get_interpreter_command(language);
+    Interpreter {
+        program: program.to_string(),
+        args: args.into_iter().map(|s| s.to_string()).collect(),
+    }
+}
+
+/// The interpreter for a run isn't installed. When it only shows up at spawn
+/// time, `run_script` records the failed attempt before reporting it.
+#[derive(Debug)]
+struct InterpreterNotFound {
+    program: String,
+}
+
+impl std::fmt::Display for InterpreterNotFound {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Required interpreter '{}' not found in PATH. Install it before running this script, \
+             or run 'sv doctor' to see which interpreters are missing.",
+            self.program
         )
+    }
+}
+
+impl std::error::Error for InterpreterNotFound {}
+
+fn check_interpreter_available(interpreter: &Interpreter) -> Result<()> {
+    which::which(&interpreter.program).map_err(|_| InterpreterNotFound {
+        program: interpreter.program.clone(),
     })?;
     Ok(())
 }
 
-pub fn run_script(args: RunArgs) -> Result<()> {
+/// The `--check-permissions` rule: public scripts run for anyone, team
+/// scripts for users listed in `team_members`, and private scripts only for
+/// their author.
+fn check_run_permission(script: &Script, user: &str, team_members: &[String]) -> Result<()> {
+    let allowed = match script.visibility {
+        Visibility::Public => true,
+        Visibility::Team => team_members.iter().any(|m| m == user),
+        Visibility::Private => script.author == user,
+    };
+    if allowed {
+        return Ok(());
+    }
+    let reason = match script.visibility {
+        Visibility::Team => "is a team script and they are not in team_members".to_string(),
+        _ => format!("is private to {}", script.author),
+    };
+    Err(anyhow!(
+        "Permission denied: {} may not run {}, which {}.",
+        user,
+        script.name,
+        reason
+    ))
+}
+
+pub fn run_script(args: RunArgs) -> Result<i32> {
+    let _span = tracing::info_span!("run", script = %args.script).entered();
+
     let config = Config::load()?;
     let ci_mode = args.ci || std::env::var(ENV_SCRIPTVAULT_CI).is_ok();
+    let verbose = args.verbose > 0;
+    let json_output = args.output_format == "json";
+    let echo = if json_output {
+        Echo::Quiet
+    } else if verbose {
+        Echo::Verbose
+    } else {
+        Echo::Normal
+    };
 
     if args.update {
         if !config.is_authenticated() {
@@ -53,149 +228,666 @@ pub fn run_script(args: RunArgs) -> Result<()> {
         pull_script_update(&args.script, &config)?;
     }
 
-    let scripts = load_scripts_local()?;
-    let mut script = scripts
-        .iter()
-        .find(|s| s.name == args.script)
-        .ok_or_else(|| anyhow!("Script not found: {}", args.script))?
-        .clone();
+    let mut script = {
+        let _span = tracing::debug_span!("load").entered();
+        let scripts = load_scripts_local()?;
+        tracing::debug!(count = scripts.len(), "loaded vault");
+        scripts
+            .into_iter()
+            .find(|s| s.name == args.script)
+            .ok_or_else(|| anyhow!("Script not found: {}", args.script))?
+    };
+    tracing::debug!(
+        id = %script.id,
+        version = %script.version,
+        language = %script.language,
+        "resolved script"
+    );
 
-    if let Some(ref target) = args.ssh {
-        return run_script_remote(
-            &script,
-            &args.args,
-            target,
-            args.ssh_port,
-            args.ssh_identity.as_deref(),
-            args.ssh_agent,
-            args.dry_run,
-            args.verbose,
-        );
+    if args.check_permissions {
+        let user = config.username.clone().unwrap_or_else(default_author);
+        check_run_permission(&script, &user, &config.team_members)?;
     }
 
-    check_interpreter_available(&script.language)?;
+    let run_target = match args.version {
+        Some(ref version) => stored_version(&script, version)?,
+        None => script.clone(),
+    };
 
-    if !script.is_safe() {
-        println!(
+    if run_target.language == ScriptLanguage::Unknown && args.interpreter.is_none() {
+        return Err(anyhow!(
+            "Cannot tell which interpreter runs {}: its language is unknown. \
+             Pass --interpreter <CMD>, or re-save it with a known extension or a #! line.",
+            script.name
+        ));
+    }
+
+    if config.verify_integrity && !run_target.verify_integrity() {
+        return Err(anyhow!(
+            "Integrity check failed for {}: its content does not match the stored hash. \
+             It may be corrupted or tampered with; re-save or re-pull it before running.",
+            script.name
+        ));
+    }
+
+    let mut run_target = match args.continue_from {
+        Some(line) => continue_from(&run_target, line)?,
+        None => run_target,
+    };
+    run_target.env.extend(args.env.iter().cloned());
+
+    if args.print_only {
+        print!(
             "{}",
-            "Warning: This script contains potentially dangerous commands."
+            with_shebang(&run_target, args.interpreter.as_deref())?
+        );
+        return Ok(0);
+    }
+
+    let mut supplied = args.args.clone();
+    if let Some(ref path) = args.args_file {
+        let text = fs::read_to_string(path)
+            .map_err(|e| anyhow!("Failed to read args file {}: {}", path, e))?;
+        supplied.extend(parse_args_file(&text).map_err(|e| anyhow!("{}: {}", path, e))?);
+    }
+
+    let run_args = resolve_params(
+        &run_target.params,
+        &supplied,
+        ci_mode,
+        args.input_args,
+        prompt_for_param,
+    )?;
+
+    if let Some(ssh) = SshTarget::from_args(&args) {
+        return run_script_remote(&run_target, &run_args, &ssh, args.dry_run, verbose);
+    }
+
+    let path_dirs = path_dirs(&args.add_path, &config.prepend_path)?;
+    if !path_dirs.is_empty() {
+        let path = run_target
+            .env
+            .get("PATH")
+            .cloned()
+            .or_else(|| std::env::var("PATH").ok());
+        run_target.env.insert(
+            "PATH".to_string(),
+            prepend_path(&path_dirs, path.as_deref())?,
+        );
+    }
+
+    let env_allow: Vec<String> = config
+        .env_allowlist
+        .iter()
+        .chain(&args.env_allow)
+        .cloned()
+        .collect();
+    let env_allow = (!args.env_inherit.unwrap_or(config.env_inherit)).then_some(&env_allow[..]);
+
+    let interpreter = resolve_interpreter(&run_target.language, args.interpreter.as_deref());
+    tracing::debug!(
+        program = %interpreter.program,
+        args = ?interpreter.args,
+        overridden = args.interpreter.is_some(),
+        "resolved interpreter"
+    );
+    // Record-only runs never start the interpreter, so they work without it.
+    if !args.record_only {
+        check_interpreter_available(&interpreter)?;
+    }
+
+    let safe = {
+        let _span = tracing::debug_span!("safety_check").entered();
+        let safe = run_target.is_safe();
+        tracing::debug!(safe, ci_mode, "checked for dangerous patterns");
+        safe
+    };
+
+    // With --output-format json, stdout is kept for the summary alone.
+    let notice = |text: &str| {
+        if json_output {
+            eprintln!("{}", text);
+        } else {
+            println!("{}", text);
+        }
+    };
+
+    if !safe {
+        notice(
+            &"Warning: This script contains potentially dangerous commands."
                 .red()
                 .bold()
+                .to_string(),
         );
-        if !ci_mode && !args.dry_run {
+        if run_target.safety_acknowledged {
+            notice(&format!(
+                "{} Dangerous commands were acknowledged with sv trust-dangerous",
+                "i".cyan()
+            ));
+        } else if !ci_mode && !args.dry_run && !args.record_only {
             let proceed = Confirm::new()
                 .with_prompt("Run this script?")
                 .default(false)
                 .interact()?;
             if !proceed {
-                println!("Execution cancelled.");
-                return Ok(());
+                notice("Execution cancelled.");
+                return Ok(0);
             }
         }
     }
 
-    show_script_preview(&script, &args.args)?;
+    if !json_output {
+        show_script_preview(&run_target, &run_args)?;
+    }
 
-    let needs_confirm = args.confirm || (config.confirm_before_run && !ci_mode);
-    if needs_confirm && !args.dry_run {
-        println!();
+    let needs_confirm = needs_confirmation(
+        script.trusted,
+        args.confirm,
+        config.confirm_before_run,
+        ci_mode,
+    );
+    if needs_confirm && !args.dry_run && !args.record_only {
+        notice("");
         let proceed = Confirm::new()
             .with_prompt("Run this script?")
             .default(true)
             .interact()?;
         if !proceed {
-            println!("Execution cancelled.");
-            return Ok(());
+            notice("Execution cancelled.");
+            return Ok(0);
         }
     }
 
     if args.dry_run {
+        let scratch = ScratchDir::new(if args.sandbox { "isolated" } else { "run" });
+        let env = script_env(
+            &run_target,
+            args.sandbox.then_some(scratch.path.as_path()),
+            env_allow,
+        );
+        let script_path = scratch.script_path(&run_target);
+        let working_dir = std::env::current_dir().ok();
+
+        println!();
+        println!("{}", "Dry run — execution plan:".yellow().bold());
+        print!(
+            "{}",
+            format_dry_run_plan(
+                &interpreter,
+                &script_path,
+                &run_args,
+                working_dir.as_deref(),
+                &env
+            )
+        );
+        if let Some(ref hook) = run_target.pre_run {
+            println!("  Pre-run hook:  {}", hook);
+        }
+        if let Some(ref hook) = run_target.post_run {
+            println!("  Post-run hook: {}", hook);
+        }
+        if args.explain {
+            println!();
+            print!("{}", format_safety_explanation(&run_target));
+        }
         println!();
         println!("{}", "Dry run complete. Script was not executed.".yellow());
-        return Ok(());
+        return Ok(0);
     }
 
-    println!();
-    println!("{}", "Executing...".cyan().bold());
-    println!();
+    if args.record_only {
+        config.ensure_writable()?;
+        let record = ExecutionRecord {
+            id: uuid::Uuid::new_v4().to_string(),
+            script_id: script.id.clone(),
+            script_version: run_target.version.clone(),
+            executed_by: config.username.clone().unwrap_or_else(default_author),
+            executed_at: chrono::Utc::now(),
+            exit_code: NOT_EXECUTED_EXIT_CODE,
+            duration_ms: 0,
+            output: None,
+            error: None,
+            context: context::detect_context_with(&config.captured_env_vars)?,
+            succeeded: None,
+            resource_usage: None,
+            timings: None,
+        };
+        save_execution_record(&record)?;
 
-    let start = Instant::now();
-    let result = if args.sandbox {
+        println!();
         println!(
-            "{}",
-            "Note: --sandbox uses a private temp directory and clears environment variables. \
-         It does not provide kernel-level sandboxing, syscall filtering, or filesystem isolation."
-                .yellow()
+            "{} Recorded run of {} without executing it",
+            "✓".green().bold(),
+            script.name.cyan()
         );
-        execute_script_isolated(&script, &args.args, args.verbose)?
+        return Ok(0);
+    }
+
+    let timeout = Timeout::from_args(&args);
+    let max_attempts = if args.until_success {
+        args.max_attempts
     } else {
-        execute_script_safe_env(&script, &args.args, args.verbose)?
-    };
-    let duration = start.elapsed();
-
-    let exit_code = result.exit_code;
-    let ctx = context::detect_context()?;
-
-    let execution = ExecutionRecord {
-        id: uuid::Uuid::new_v4().to_string(),
-        script_id: script.id.clone(),
-        script_version: script.version.clone(),
-        executed_by: config.username.clone().unwrap_or_else(|| default_author()),
-        executed_at: chrono::Utc::now(),
-        exit_code,
-        duration_ms: duration.as_millis() as u64,
-        output: result.output,
-        error: result.error,
-        context: ctx,
+        1
     };
+    let mut attempt = 1;
+    let (execution, succeeded, duration) = loop {
+        if max_attempts > 1 {
+            notice("");
+            notice(
+                &format!("Attempt {}/{}", attempt, max_attempts)
+                    .cyan()
+                    .bold()
+                    .to_string(),
+            );
+        }
 
-    save_execution_record(&execution)?;
+        if let Some(ref hook) = run_target.pre_run {
+            let code = run_hook(
+                "pre-run",
+                hook,
+                &run_target,
+                &interpreter,
+                args.sandbox,
+                env_allow,
+                echo,
+            )?;
+            if code != 0 {
+                return Err(anyhow!(
+                    "Pre-run hook failed with exit code {}. Script was not executed.",
+                    code
+                ));
+            }
+        }
 
-    let prev_recorded = script.metadata.success_count + script.metadata.failure_count;
-    script.metadata.use_count += 1;
+        let ctx = context::detect_context_with(&config.captured_env_vars)?;
+        if args.show_env {
+            notice("");
+            notice(&"Captured environment:".cyan().bold().to_string());
+            if ctx.environment.is_empty() {
+                notice(&format!("  {}", "(none)".dimmed()));
+            } else {
+                notice(context::format_environment(&ctx.environment).trim_end());
+            }
+        }
 
-    if exit_code == 0 {
-        script.metadata.success_count += 1;
-    } else {
-        script.metadata.failure_count += 1;
-    }
+        if !json_output {
+            println!();
+            println!("{}", "Executing...".cyan().bold());
+            println!();
+        }
 
-    let new_recorded = script.metadata.success_count + script.metadata.failure_count;
+        let exec_span = tracing::info_span!("execute", sandbox = args.sandbox).entered();
+        let start = Instant::now();
+        let result = if args.sandbox {
+            if !json_output {
+                println!(
+                "{}",
+                "Note: --sandbox uses a private temp directory and clears environment variables. \
+             It does not provide kernel-level sandboxing, syscall filtering, or filesystem isolation."
+                    .yellow()
+                );
+            }
+            execute_script_isolated(
+                &run_target,
+                &interpreter,
+                &run_args,
+                env_allow,
+                echo,
+                timeout.as_ref(),
+            )
+        } else {
+            execute_script_safe_env(
+                &run_target,
+                &interpreter,
+                &run_args,
+                env_allow,
+                echo,
+                timeout.as_ref(),
+            )
+        };
+        // A missing interpreter is recorded as a failed run, then reported.
+        let mut spawn_failure = None;
+        let result = match result {
+            Err(e) if e.is::<InterpreterNotFound>() => {
+                let result = ExecutionResult {
+                    exit_code: INTERPRETER_NOT_FOUND_EXIT_CODE,
+                    output: None,
+                    error: Some(e.to_string()),
+                    resource_usage: None,
+                    timings: None,
+                };
+                spawn_failure = Some(e);
+                result
+            }
+            other => other?,
+        };
+        let duration = start.elapsed();
+        tracing::info!(
+            exit_code = result.exit_code,
+            duration_ms = duration.as_millis() as u64,
+            "script finished"
+        );
+        drop(exec_span);
 
-    script.metadata.avg_runtime_ms = Some(match script.metadata.avg_runtime_ms {
-        Some(avg) => (avg * prev_recorded + duration.as_millis() as u64) / new_recorded,
-        None => duration.as_millis() as u64,
-    });
+        let exit_code = result.exit_code;
+        let succeeded = script.is_success_code(exit_code);
 
-    script.metadata.last_run = Some(execution.executed_at);
-    script.metadata.last_run_by = Some(execution.executed_by.clone());
+        let execution = ExecutionRecord {
+            id: uuid::Uuid::new_v4().to_string(),
+            script_id: script.id.clone(),
+            script_version: run_target.version.clone(),
+            executed_by: config.username.clone().unwrap_or_else(default_author),
+            executed_at: chrono::Utc::now(),
+            exit_code,
+            duration_ms: duration.as_millis() as u64,
+            output: result
+                .output
+                .map(|o| truncate_output(o, config.max_output_bytes)),
+            error: result
+                .error
+                .map(|e| truncate_output(e, config.max_output_bytes)),
+            context: ctx,
+            succeeded: Some(succeeded),
+            resource_usage: result.resource_usage,
+            timings: result.timings.filter(|_| args.capture_timing),
+        };
 
-    update_script_metadata(&script)?;
+        if let Some(ref path) = args.capture_json
+            && let Err(e) = write_capture_json(path, &execution)
+        {
+            eprintln!("{} {}", "Warning:".yellow().bold(), e);
+        }
 
-    println!();
-    if exit_code == 0 {
-        println!("Completed in {:.2}s", duration.as_secs_f64());
-    } else {
-        println!(
-            "Failed with exit code {} in {:.2}s",
+        if args.measure_only || verbose {
+            notice("");
+            match result.resource_usage {
+                Some(ref usage) => notice(&format!("  {}", format_resource_usage(usage).dimmed())),
+                None if args.measure_only => notice(&format!(
+                    "  {}",
+                    "Resource usage is not available on this platform.".dimmed()
+                )),
+                None => {}
+            }
+        }
+        if let Some(ref timings) = execution.timings {
+            if !(args.measure_only || verbose) {
+                notice("");
+            }
+            notice(&format!("  {}", format_timings(timings).dimmed()));
+        }
+
+        if args.no_record {
+            tracing::debug!("--no-record: skipping history and metadata update");
+        } else if config.is_read_only() {
+            if !json_output {
+                println!();
+                println!("{}", "Read-only vault: this run was not recorded.".dimmed());
+            }
+        } else {
+            save_execution_record(&execution)?;
+
+            let prev_recorded = script.metadata.success_count + script.metadata.failure_count;
+            script.metadata.use_count += 1;
+
+            if succeeded {
+                script.metadata.success_count += 1;
+            } else {
+                script.metadata.failure_count += 1;
+            }
+
+            let new_recorded = script.metadata.success_count + script.metadata.failure_count;
+
+            script.metadata.avg_runtime_ms = Some(match script.metadata.avg_runtime_ms {
+                Some(avg) => (avg * prev_recorded + duration.as_millis() as u64) / new_recorded,
+                None => duration.as_millis() as u64,
+            });
+
+            script.metadata.last_run = Some(execution.executed_at);
+            script.metadata.last_run_by = Some(execution.executed_by.clone());
+
+            update_script_metadata(&script)?;
+        }
+
+        if !args.no_record
+            && let Some(ref url) = config.webhook_url
+            && let Err(e) = webhook::notify(url, &execution, &script.name)
+        {
+            eprintln!("{} {}", "Warning:".yellow().bold(), e);
+        }
+
+        if let Some(e) = spawn_failure {
+            return Err(e);
+        }
+        if succeeded || attempt >= max_attempts {
+            break (execution, succeeded, duration);
+        }
+        notice("");
+        notice(&format!(
+            "{} Attempt {}/{} failed with exit code {}. Retrying in {}s...",
+            "i".cyan(),
+            attempt,
+            max_attempts,
             exit_code,
-            duration.as_secs_f64()
+            args.retry_delay.as_secs()
+        ));
+        std::thread::sleep(args.retry_delay);
+        attempt += 1;
+    };
+
+    let exit_code = execution.exit_code;
+    if succeeded && let Some(ref hook) = run_target.post_run {
+        if !json_output {
+            println!();
+        }
+        let code = run_hook(
+            "post-run",
+            hook,
+            &run_target,
+            &interpreter,
+            args.sandbox,
+            env_allow,
+            echo,
+        )?;
+        if code != 0 {
+            eprintln!(
+                "{} Post-run hook failed with exit code {}",
+                "Warning:".yellow().bold(),
+                code
+            );
+        }
+    }
+
+    if args.notify {
+        let notification = notify::build_notification(&script.name, exit_code, succeeded, duration);
+        if let Err(e) = notify::send(&notification) {
+            eprintln!("{} {}", "Warning:".yellow().bold(), e);
+        }
+    }
+
+    if json_output {
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&RunSummary::new(&script.name, &execution))?
         );
+        return Ok(exit_code);
     }
 
-    Ok(())
+    println!();
+    println!("{}", completion_message(exit_code, succeeded, duration));
+
+    Ok(exit_code)
+}
+
+/// The last line of `sv run`. `succeeded` comes from the script's
+/// `success_exit_codes`, so exit 0 is a failure when 0 isn't in the list.
+fn completion_message(exit_code: i32, succeeded: bool, duration: std::time::Duration) -> String {
+    let secs = duration.as_secs_f64();
+    match (succeeded, exit_code) {
+        (true, 0) => format!("Completed in {:.2}s", secs),
+        (true, code) => format!(
+            "Completed with exit code {} (counted as success) in {:.2}s",
+            code, secs
+        ),
+        (false, 0) => format!(
+            "Failed: exit code 0 is not one of the script's success codes ({:.2}s)",
+            secs
+        ),
+        (false, code) => format!("Failed with exit code {} in {:.2}s", code, secs),
+    }
+}
+
+/// What `sv run --output-format json` prints once the script has finished.
+/// Output and error are as recorded in history, so they are cut to
+/// `max_output_bytes` and null when the script printed nothing.
+#[derive(Debug, serde::Serialize)]
+struct RunSummary<'a> {
+    script: &'a str,
+    script_version: &'a str,
+    exit_code: i32,
+    succeeded: bool,
+    duration_ms: u64,
+    output: Option<&'a str>,
+    error: Option<&'a str>,
+}
+
+impl<'a> RunSummary<'a> {
+    fn new(name: &'a str, execution: &'a ExecutionRecord) -> Self {
+        Self {
+            script: name,
+            script_version: &execution.script_version,
+            exit_code: execution.exit_code,
+            succeeded: execution.was_successful(),
+            duration_ms: execution.duration_ms,
+            output: execution.output.as_deref(),
+            error: execution.error.as_deref(),
+        }
+    }
+}
+
+/// Drops the lines before `line` (1-based). A `#!` line is kept so the rest
+/// still runs under the same shell. Only bash and shell scripts can be cut
+/// this way; other languages rarely survive losing their opening lines.
+fn continue_from(script: &Script, line: usize) -> Result<Script> {
+    if !matches!(
+        script.language,
+        ScriptLanguage::Bash | ScriptLanguage::Shell
+    ) {
+        return Err(anyhow!(
+            "--continue-from only works for bash and shell scripts, not {}",
+            script.language
+        ));
+    }
+
+    let lines: Vec<&str> = script.content.lines().collect();
+    if line == 0 || line > lines.len() {
+        return Err(anyhow!(
+            "Line {} is out of range: {} has {} lines",
+            line,
+            script.name,
+            lines.len()
+        ));
+    }
+
+    let mut content = String::new();
+    if line > 1 && lines[0].starts_with("#!") {
+        content.push_str(lines[0]);
+        content.push('\n');
+    }
+    content.push_str(&lines[line - 1..].join("\n"));
+    if script.content.ends_with('\n') {
+        content.push('\n');
+    }
+
+    Ok(Script {
+        content,
+        ..script.clone()
+    })
+}
+
+/// The script's content, starting with a `#!` line so it can be saved and
+/// executed directly. An existing `#!` line is kept as is; otherwise one is
+/// made from `interpreter` or the language default.
+fn with_shebang(script: &Script, interpreter: Option<&str>) -> Result<String> {
+    if script.content.starts_with("#!") {
+        return Ok(script.content.clone());
+    }
+    let shebang = match interpreter {
+        Some(cmd) if cmd.starts_with('/') => format!("#!{}", cmd),
+        Some(cmd) => format!("#!/usr/bin/env {}", cmd),
+        None => script
+            .language
+            .get_shebang()
+            .map(str::to_string)
+            .ok_or_else(|| {
+                anyhow!(
+                    "{} scripts have no standard #! line; pass --interpreter <CMD> to choose one",
+                    script.language
+                )
+            })?,
+    };
+    Ok(format!("{}\n{}", shebang, script.content))
+}
+
+/// Builds the script as it was at `version`, keeping the current id and
+/// name so the run is recorded against the same script.
+fn stored_version(script: &Script, version: &str) -> Result<Script> {
+    if version == script.version {
+        return Ok(script.clone());
+    }
+    let store = crate::versions::VersionStore::new(&Config::vault_dir()?);
+    let snapshot = store
+        .load_version(&script.id, version)
+        .map_err(|_| anyhow!("Version {} not found for script: {}", version, script.name))?;
+    Ok(Script {
+        id: script.id.clone(),
+        name: script.name.clone(),
+        trusted: script.trusted,
+        ..snapshot
+    })
+}
+
+/// Writes one run's record for `sv run --capture-json`.
+fn write_capture_json(path: &str, record: &ExecutionRecord) -> Result<()> {
+    let json = serde_json::to_string_pretty(record)?;
+    fs::write(path, json)
+        .map_err(|e| anyhow!("Failed to write execution record to {}: {}", path, e))
+}
+
+/// Where and how `sv run --ssh` connects, and what runs the script there.
+struct SshTarget<'a> {
+    host: &'a str,
+    port: u16,
+    identity: Option<&'a str>,
+    forward_agent: bool,
+    interpreter: Option<&'a str>,
+}
+
+impl<'a> SshTarget<'a> {
+    fn from_args(args: &'a RunArgs) -> Option<Self> {
+        Some(Self {
+            host: args.ssh.as_deref()?,
+            port: args.ssh_port,
+            identity: args.ssh_identity.as_deref(),
+            forward_agent: args.ssh_agent,
+            interpreter: args.interpreter.as_deref(),
+        })
+    }
 }
 
 fn run_script_remote(
     script: &Script,
     run_args: &[String],
-    target: &str,
-    port: u16,
-    identity: Option<&str>,
-    forward_agent: bool,
+    ssh: &SshTarget,
     dry_run: bool,
     verbose: bool,
-) -> Result<()> {
+) -> Result<i32> {
+    let SshTarget {
+        host: target,
+        port,
+        identity,
+        forward_agent,
+        interpreter,
+    } = *ssh;
     which::which("ssh")
         .map_err(|_| anyhow!("'ssh' not found in PATH. Install OpenSSH to use --ssh."))?;
 
@@ -218,12 +910,15 @@ fn run_script_remote(
         if forward_agent {
             println!("  Agent forward: yes");
         }
+        if let Some(interpreter) = interpreter {
+            println!("  Interpreter:   {}", interpreter);
+        }
         if !run_args.is_empty() {
             println!("  Arguments:     {}", run_args.join(" ").cyan());
         }
         println!();
         println!("{}", "Dry run complete. Script was not executed.".yellow());
-        return Ok(());
+        return Ok(0);
     }
 
     let mut base_ssh_args: Vec<String> = vec![
@@ -288,12 +983,7 @@ fn run_script_remote(
     let mut exec_cmd = std::process::Command::new("ssh");
     exec_cmd.args(&base_ssh_args);
 
-    let script_call = if run_args.is_empty() {
-        remote_path.clone()
-    } else {
-        format!("{} {}", remote_path, run_args.join(" "))
-    };
-
+    let script_call = remote_script_call(&remote_path, interpreter, run_args);
     let cleanup = format!(
         "{}; _exit=$?; rm -f {}; exit $_exit",
         script_call, remote_path
@@ -319,10 +1009,21 @@ fn run_script_remote(
         println!("Remote execution failed with exit code {}.", exit_code);
     }
 
-    Ok(())
+    Ok(exit_code)
+}
+
+/// The remote command line for `sv run --ssh`. Without `--interpreter` the
+/// copied file runs directly, so its `#!` line picks the interpreter.
+fn remote_script_call(remote_path: &str, interpreter: Option<&str>, run_args: &[String]) -> String {
+    interpreter
+        .into_iter()
+        .chain(std::iter::once(remote_path))
+        .chain(run_args.iter().map(String::as_str))
+        .collect::<Vec<_>>()
+        .join(" ")
 }
+
 fn pull_script_update(script_name: &str, config: &Config) -> Result<()> {
-    use crate::storage::StorageBackend;
     use crate::sync::remote::{HttpRemoteBackend, RemoteBackend};
 
     let token = config
@@ -408,8 +1109,8 @@ fn show_script_preview(script: &Script, run_args: &[String]) -> Result<()> {
 
     if script.metadata.use_count > 0 {
         println!(
-            "│  Success rate: {:.1}% ({}/{})",
-            script.success_rate(),
+            "│  Success rate: {} ({}/{})",
+            color_success_rate(script.success_rate()),
             script.metadata.success_count,
             script.metadata.use_count
         );
@@ -423,94 +1124,585 @@ struct ExecutionResult {
     exit_code: i32,
     output: Option<String>,
     error: Option<String>,
+    resource_usage: Option<ResourceUsage>,
+    timings: Option<PhaseTimings>,
 }
 
-fn write_temp_script(script: &Script) -> Result<std::path::PathBuf> {
-    let temp_dir = std::env::temp_dir().join("scriptvault");
-    fs::create_dir_all(&temp_dir)?;
+/// How a reaped child exited, with its own resource usage where the
+/// platform reports it.
+type Reaped = (std::process::ExitStatus, Option<ResourceUsage>);
+
+/// Reaps the child with `wait4`, which returns the usage of that child alone
+/// (and the descendants it waited for). `RUSAGE_CHILDREN` can't be used: its
+/// max RSS is a high-water mark over every child `sv` has ever reaped, hooks
+/// and earlier attempts included. Without `block` this only polls, and
+/// `None` means the child is still running.
+#[cfg(unix)]
+fn reap_child(child: &std::process::Child, block: bool) -> Result<Option<Reaped>> {
+    use std::os::unix::process::ExitStatusExt;
+
+    let pid = child.id() as libc::pid_t;
+    let options = if block { 0 } else { libc::WNOHANG };
+    let mut status = 0;
+    // SAFETY: rusage is plain old data, so all zeroes is a valid value.
+    let mut usage: libc::rusage = unsafe { std::mem::zeroed() };
+    loop {
+        // SAFETY: both pointers are to live locals, and the pid is our own
+        // child that nothing else waits for.
+        match unsafe { libc::wait4(pid, &mut status, options, &mut usage) } {
+            0 => return Ok(None),
+            -1 => {
+                let err = std::io::Error::last_os_error();
+                if err.kind() != std::io::ErrorKind::Interrupted {
+                    return Err(err.into());
+                }
+            }
+            _ => {
+                // The field types differ by platform (suseconds_t is i32 on
+                // macOS), so the casts are only redundant on Linux.
+                #[allow(clippy::unnecessary_cast)]
+                let to_ms = |tv: libc::timeval| {
+                    (tv.tv_sec as i64 * 1000 + tv.tv_usec as i64 / 1000).max(0) as u64
+                };
+                let max_rss = usage.ru_maxrss.max(0) as u64;
+                let usage = ResourceUsage {
+                    // Linux reports kilobytes, macOS bytes.
+                    max_rss_kb: if cfg!(target_os = "macos") {
+                        max_rss / 1024
+                    } else {
+                        max_rss
+                    },
+                    user_cpu_ms: to_ms(usage.ru_utime),
+                    sys_cpu_ms: to_ms(usage.ru_stime),
+                };
+                return Ok(Some((
+                    std::process::ExitStatus::from_raw(status),
+                    Some(usage),
+                )));
+            }
+        }
+    }
+}
 
-    let temp_filename = format!("{}.{}", uuid::Uuid::new_v4(), script.language.extension());
-    let script_path = temp_dir.join(temp_filename);
+#[cfg(not(unix))]
+fn reap_child(child: &mut std::process::Child, block: bool) -> Result<Option<Reaped>> {
+    let status = if block {
+        Some(child.wait()?)
+    } else {
+        child.try_wait()?
+    };
+    Ok(status.map(|status| (status, None)))
+}
 
-    fs::write(&script_path, &script.content)?;
+/// One line summarizing a run's resource usage, for `--measure-only` and
+/// `--verbose`.
+fn format_resource_usage(usage: &ResourceUsage) -> String {
+    format!(
+        "Resources: max RSS {}, user CPU {:.2}s, sys CPU {:.2}s",
+        human_size(usage.max_rss_kb * 1024),
+        usage.user_cpu_ms as f64 / 1000.0,
+        usage.sys_cpu_ms as f64 / 1000.0
+    )
+}
 
-    #[cfg(unix)]
-    {
-        use std::os::unix::fs::PermissionsExt;
-        let mut perms = fs::metadata(&script_path)?.permissions();
-        perms.set_mode(0o755);
-        fs::set_permissions(&script_path, perms)?;
-    }
+/// Where run directories are created: `SCRIPTVAULT_TMPDIR` when set, for
+/// systems whose temp dir is mounted `noexec`, otherwise the system temp dir.
+fn scratch_root() -> PathBuf {
+    std::env::var_os(ENV_SCRIPTVAULT_TMPDIR)
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+        .unwrap_or_else(std::env::temp_dir)
+}
 
-    Ok(script_path)
+/// A per-execution directory under `scratch_root`, unique to one run so
+/// concurrent runs of the same script never share a file. It is removed on
+/// drop, which covers early returns and panics as well as normal completion.
+struct ScratchDir {
+    path: PathBuf,
 }
 
-fn spawn_and_collect(
-    interpreter: &str,
-    interpreter_args: &[&str],
-    script_path: &std::path::Path,
-    args: &[String],
-    env: Option<&HashMap<String, String>>,
-    verbose: bool,
-) -> Result<ExecutionResult> {
-    if verbose {
-        println!("  Interpreter: {}", interpreter);
-        println!("  Script path: {}", script_path.display());
-        if !args.is_empty() {
-            println!("  Arguments:   {}", args.join(" "));
+impl ScratchDir {
+    fn new(kind: &str) -> Self {
+        Self {
+            path: scratch_root()
+                .join("scriptvault")
+                .join(kind)
+                .join(uuid::Uuid::new_v4().to_string()),
         }
-        println!();
     }
 
-    let mut cmd = Command::new(interpreter);
-    cmd.args(interpreter_args)
-        .arg(script_path)
-        .args(args)
-        .stdout(Stdio::piped())
-        .stderr(Stdio::piped());
+    fn script_path(&self, script: &Script) -> PathBuf {
+        self.path
+            .join(format!("script.{}", script.language.extension()))
+    }
+}
 
-    if let Some(vars) = env {
-        cmd.env_clear();
-        for (k, v) in vars {
-            cmd.env(k, v);
+impl Drop for ScratchDir {
+    fn drop(&mut self) {
+        match fs::remove_dir_all(&self.path) {
+            Ok(()) => {}
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+            Err(e) => {
+                tracing::warn!(path = %self.path.display(), "failed to remove temporary directory: {}", e)
+            }
         }
     }
+}
 
-    let mut child = cmd.spawn()?;
-
-    let stdout_pipe = child.stdout.take().expect("stdout was piped");
-    let stderr_pipe = child.stderr.take().expect("stderr was piped");
-
-    let stdout_handle = std::thread::spawn(move || {
-        let mut reader = BufReader::new(stdout_pipe);
-        let mut captured = String::new();
-        let mut line = String::new();
-        while reader.read_line(&mut line).unwrap_or(0) > 0 {
-            print!("{}", line);
-            captured.push_str(&line);
-            line.clear();
-        }
-        captured
-    });
+/// Writes the script for execution. A script saved without a `#!` line gets
+/// its language's default one in the temp file, so it behaves the same when
+/// the file is executed directly; the stored content is not changed.
+fn write_temp_script(script: &Script, script_path: &Path) -> Result<()> {
+    if let Some(dir) = script_path.parent() {
+        fs::create_dir_all(dir)?;
+    }
 
-    let stderr_handle = std::thread::spawn(move || {
-        let mut reader = BufReader::new(stderr_pipe);
-        let mut captured = String::new();
-        let mut line = String::new();
-        while reader.read_line(&mut line).unwrap_or(0) > 0 {
-            eprint!("{}", line);
-            captured.push_str(&line);
-            line.clear();
+    match script.language.get_shebang() {
+        Some(shebang) if !script.content.starts_with("#!") => {
+            fs::write(script_path, format!("{}\n{}", shebang, script.content))?
         }
-        captured
-    });
+        _ => fs::write(script_path, &script.content)?,
+    }
 
-    let status = child.wait()?;
-    let stdout_str = stdout_handle.join().unwrap_or_default();
-    let stderr_str = stderr_handle.join().unwrap_or_default();
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let mut perms = fs::metadata(script_path)?.permissions();
+        perms.set_mode(0o755);
+        fs::set_permissions(script_path, perms)?;
+    }
+
+    Ok(())
+}
+
+fn build_isolated_env(sandbox_dir: &Path) -> HashMap<String, String> {
+    let mut env: HashMap<String, String> = HashMap::new();
+    env.insert("HOME".into(), sandbox_dir.to_string_lossy().into_owned());
+    env.insert("TMPDIR".into(), sandbox_dir.to_string_lossy().into_owned());
+    env.insert("PATH".into(), std::env::var("PATH").unwrap_or_default());
+    env.insert("ISOLATED".into(), "1".into());
+
+    if let Ok(term) = std::env::var("TERM") {
+        env.insert("TERM".into(), term);
+    }
+    if let Ok(lang) = std::env::var("LANG") {
+        env.insert("LANG".into(), lang);
+    }
+
+    env
+}
+
+fn format_dry_run_plan(
+    interpreter: &Interpreter,
+    script_path: &Path,
+    args: &[String],
+    working_dir: Option<&Path>,
+    env: &HashMap<String, String>,
+) -> String {
+    let mut argv: Vec<String> = vec![interpreter.program.clone()];
+    argv.extend(interpreter.args.iter().cloned());
+    argv.push(script_path.display().to_string());
+    argv.extend(args.iter().cloned());
+
+    let mut env_keys: Vec<&str> = env.keys().map(|k| k.as_str()).collect();
+    env_keys.sort_unstable();
+
+    let mut out = String::new();
+    out.push_str(&format!("  Interpreter:   {}\n", interpreter.program));
+    out.push_str(&format!("  Command:       {}\n", argv.join(" ")));
+    out.push_str(&format!(
+        "  Working dir:   {}\n",
+        working_dir
+            .map(|d| d.display().to_string())
+            .unwrap_or_else(|| "unknown".to_string())
+    ));
+    out.push_str(&format!("  Temp script:   {}\n", script_path.display()));
+    out.push_str(&format!("  Env keys:      {}\n", env_keys.join(", ")));
+    out
+}
+
+/// `--confirm` always prompts. Otherwise the global `confirm_before_run`
+/// setting applies, except in CI mode and for scripts marked trusted.
+fn needs_confirmation(
+    trusted: bool,
+    confirm_flag: bool,
+    confirm_setting: bool,
+    ci_mode: bool,
+) -> bool {
+    confirm_flag || (confirm_setting && !ci_mode && !trusted)
+}
+
+fn format_safety_explanation(script: &Script) -> String {
+    let mut out = String::new();
+    out.push_str(&format!("{}\n", "Safety analysis:".bold()));
+    out.push_str(&format!("  Hash:          {}\n", script.metadata.hash));
+    out.push_str(&format!(
+        "  Size:          {} bytes\n",
+        script.metadata.size_bytes
+    ));
+    out.push_str(&format!(
+        "  Lines:         {}\n",
+        script.metadata.line_count
+    ));
+
+    let matched = script.dangerous_patterns();
+    if matched.is_empty() {
+        out.push_str(&format!(
+            "  Matched:       none ({} patterns checked)\n",
+            DANGEROUS_PATTERNS.len()
+        ));
+        out.push_str("  Not flagged: no known destructive patterns were found.\n");
+        return out;
+    }
+
+    out.push_str("  Matched:\n");
+    for pattern in &matched {
+        let lines: Vec<String> = script
+            .content
+            .lines()
+            .enumerate()
+            .filter(|(_, line)| line.contains(pattern))
+            .map(|(i, _)| (i + 1).to_string())
+            .collect();
+        out.push_str(&format!(
+            "    {:<16} line {}\n",
+            pattern.red(),
+            lines.join(", ")
+        ));
+    }
+    out.push_str(
+        "  Flagged as dangerous: sv run will warn and ask for confirmation (except with --ci).\n",
+    );
+    out
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum KillSignal {
+    Term,
+    Kill,
+}
+
+/// How `sv run --timeout` stops a script that runs too long.
+#[derive(Debug, Clone, Copy)]
+struct Timeout {
+    after: std::time::Duration,
+    signal: KillSignal,
+    grace: std::time::Duration,
+}
+
+impl Timeout {
+    fn from_args(args: &RunArgs) -> Option<Self> {
+        let after = args.timeout?;
+        if cfg!(not(unix)) && (args.kill_signal.is_some() || args.kill_grace.is_some()) {
+            eprintln!(
+                "{} --kill-signal and --kill-grace are not supported on this platform; the script is stopped immediately on timeout.",
+                "Warning:".yellow().bold()
+            );
+        }
+        Some(Self {
+            after,
+            signal: match args.kill_signal.as_deref() {
+                Some("KILL") => KillSignal::Kill,
+                _ => KillSignal::Term,
+            },
+            grace: args
+                .kill_grace
+                .unwrap_or(std::time::Duration::from_secs(DEFAULT_KILL_GRACE_SECS)),
+        })
+    }
+}
+
+/// Polls the child until it exits or `deadline` passes. `None` means it is
+/// still running.
+fn wait_until(child: &mut std::process::Child, deadline: Instant) -> Result<Option<Reaped>> {
+    const POLL: std::time::Duration = std::time::Duration::from_millis(20);
+    loop {
+        if let Some(reaped) = reap_child(child, false)? {
+            return Ok(Some(reaped));
+        }
+        let now = Instant::now();
+        if now >= deadline {
+            return Ok(None);
+        }
+        std::thread::sleep(POLL.min(deadline - now));
+    }
+}
+
+/// Waits for the child, stopping it once the timeout passes. The flag is set
+/// when the timeout fired.
+fn wait_for_child(
+    child: &mut std::process::Child,
+    timeout: Option<&Timeout>,
+) -> Result<(Reaped, bool)> {
+    let reap = |child: &mut std::process::Child| {
+        reap_child(child, true)?.ok_or_else(|| anyhow!("Script exited without a status"))
+    };
+    let Some(timeout) = timeout else {
+        return Ok((reap(child)?, false));
+    };
+    if let Some(reaped) = wait_until(child, Instant::now() + timeout.after)? {
+        return Ok((reaped, false));
+    }
+
+    eprintln!(
+        "{} Script timed out after {}s",
+        "Warning:".yellow().bold(),
+        timeout.after.as_secs()
+    );
+    // A script that exited during the grace period was reaped already.
+    let reaped = match stop_child(child, timeout)? {
+        Some(reaped) => reaped,
+        None => reap(child)?,
+    };
+    Ok((reaped, true))
+}
+
+/// Sends SIGTERM to the script's process group and gives it the grace period
+/// to clean up, then SIGKILLs whatever is left. With `KillSignal::Kill` the
+/// group is killed straight away.
+/// Returns the script's status if it exited during the grace period.
+#[cfg(unix)]
+fn stop_child(child: &mut std::process::Child, timeout: &Timeout) -> Result<Option<Reaped>> {
+    use nix::sys::signal::{Signal, killpg};
+    use nix::unistd::Pid;
+
+    let group = Pid::from_raw(child.id() as i32);
+    let mut reaped = None;
+    if timeout.signal == KillSignal::Term {
+        tracing::debug!(grace = ?timeout.grace, "sending SIGTERM to script process group");
+        let _ = killpg(group, Signal::SIGTERM);
+        reaped = wait_until(child, Instant::now() + timeout.grace)?;
+        if reaped.is_none() {
+            eprintln!(
+                "{} Script still running {}s after SIGTERM; sending SIGKILL",
+                "Warning:".yellow().bold(),
+                timeout.grace.as_secs_f64()
+            );
+        }
+    }
+    // Also reaps background processes the script left behind.
+    let _ = killpg(group, Signal::SIGKILL);
+    Ok(reaped)
+}
+
+#[cfg(not(unix))]
+fn stop_child(child: &mut std::process::Child, _timeout: &Timeout) -> Result<Option<Reaped>> {
+    child.kill()?;
+    Ok(None)
+}
+
+fn build_command(
+    interpreter: &Interpreter,
+    script_path: &Path,
+    args: &[String],
+    env: Option<&HashMap<String, String>>,
+) -> Command {
+    let mut cmd = Command::new(&interpreter.program);
+    cmd.args(&interpreter.args)
+        .arg(script_path)
+        .args(args)
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped());
+
+    if let Some(vars) = env {
+        cmd.env_clear();
+        for (k, v) in vars {
+            cmd.env(k, v);
+        }
+    }
+
+    cmd
+}
+
+/// errno for "exec format error"; the same value on Linux and macOS.
+const ENOEXEC: i32 = 8;
+
+/// A missing interpreter becomes `InterpreterNotFound`. Permission and
+/// exec-format failures when starting a script usually mean the temp dir is
+/// mounted `noexec`, so those point at `SCRIPTVAULT_TMPDIR`. Other spawn
+/// errors pass through unchanged.
+fn spawn_error(
+    err: std::io::Error,
+    interpreter: &Interpreter,
+    script_path: &Path,
+) -> anyhow::Error {
+    if err.kind() == std::io::ErrorKind::NotFound {
+        return InterpreterNotFound {
+            program: interpreter.program.clone(),
+        }
+        .into();
+    }
+    let likely_noexec =
+        err.kind() == std::io::ErrorKind::PermissionDenied || err.raw_os_error() == Some(ENOEXEC);
+    if !likely_noexec {
+        return err.into();
+    }
+    anyhow!(
+        "Failed to start {} for {}: {}. The temp directory may be mounted noexec; \
+         set {} to a directory that allows execution and run again.",
+        interpreter.program,
+        script_path.display(),
+        err,
+        ENV_SCRIPTVAULT_TMPDIR
+    )
+}
+
+/// Formats one phase of the `sv run --verbose` timing breakdown.
+pub(crate) fn format_phase(elapsed: std::time::Duration) -> String {
+    let ms = elapsed.as_secs_f64() * 1000.0;
+    if ms < 1000.0 {
+        format!("{:.1}ms", ms)
+    } else {
+        format!("{:.2}s", ms / 1000.0)
+    }
+}
+
+/// `sv run --capture-timing` output: when each phase ended, counted from
+/// the start of the run.
+fn format_timings(timings: &PhaseTimings) -> String {
+    let phase = |us: u64| format_phase(std::time::Duration::from_micros(us));
+    let first_output = match timings.first_output_us {
+        Some(us) => phase(us),
+        None => "none".to_string(),
+    };
+    format!(
+        "Timing: script written {}, started {}, first output {}, finished {}",
+        phase(timings.write_us),
+        phase(timings.spawn_us),
+        first_output,
+        phase(timings.total_us)
+    )
+}
+
+/// When a run started and how long writing its temp script took, the base
+/// for the timing breakdown.
+#[derive(Debug, Clone, Copy)]
+struct RunClock {
+    start: Instant,
+    write: std::time::Duration,
+}
+
+impl RunClock {
+    fn write_script(script: &Script, path: &Path) -> Result<Self> {
+        let start = Instant::now();
+        write_temp_script(script, path)?;
+        Ok(Self {
+            start,
+            write: start.elapsed(),
+        })
+    }
+
+    fn micros_at(&self, at: Instant) -> u64 {
+        at.duration_since(self.start).as_micros() as u64
+    }
+}
+
+fn spawn_and_collect(
+    interpreter: &Interpreter,
+    script_path: &Path,
+    args: &[String],
+    env: Option<&HashMap<String, String>>,
+    echo: Echo,
+    clock: RunClock,
+    timeout: Option<&Timeout>,
+) -> Result<ExecutionResult> {
+    let verbose = echo == Echo::Verbose;
+    if verbose {
+        println!("  Interpreter: {}", interpreter.command_line());
+        println!("  Script path: {}", script_path.display());
+        if let Ok(cwd) = std::env::current_dir() {
+            println!("  Working dir: {}", cwd.display());
+        }
+        if !args.is_empty() {
+            println!("  Arguments:   {}", args.join(" "));
+        }
+        println!();
+    }
+
+    tracing::debug!(
+        program = %interpreter.program,
+        script_path = %script_path.display(),
+        env_vars = env.map(|e| e.len()),
+        "spawning interpreter"
+    );
+    let spawn_start = Instant::now();
+    let mut command = build_command(interpreter, script_path, args, env);
+    // Its own process group lets a timeout signal everything the script
+    // started, so background children can't keep the output pipes open.
+    #[cfg(unix)]
+    if timeout.is_some() {
+        std::os::unix::process::CommandExt::process_group(&mut command, 0);
+    }
+    let mut child = command
+        .spawn()
+        .map_err(|e| spawn_error(e, interpreter, script_path))?;
+    let spawn_time = spawn_start.elapsed();
+    let wait_start = Instant::now();
+
+    let stdout_pipe = child.stdout.take().expect("stdout was piped");
+    let stderr_pipe = child.stderr.take().expect("stderr was piped");
+
+    let first_output = Arc::new(OnceLock::new());
+    let stdout_handle = {
+        let first_output = Arc::clone(&first_output);
+        std::thread::spawn(move || {
+            relay_output(
+                BufReader::new(stdout_pipe),
+                relay_sink(echo, std::io::stdout()),
+                &first_output,
+            )
+        })
+    };
+    let stderr_handle = {
+        let first_output = Arc::clone(&first_output);
+        std::thread::spawn(move || {
+            relay_output(
+                BufReader::new(stderr_pipe),
+                relay_sink(echo, std::io::stderr()),
+                &first_output,
+            )
+        })
+    };
+
+    let ((status, resource_usage), timed_out) = wait_for_child(&mut child, timeout)?;
+    let (stdout_str, stdout_lossy) = stdout_handle.join().unwrap_or_default();
+    let (stderr_str, stderr_lossy) = stderr_handle.join().unwrap_or_default();
+    let wait_time = wait_start.elapsed();
+    let timings = PhaseTimings {
+        write_us: clock.write.as_micros() as u64,
+        spawn_us: clock.micros_at(wait_start),
+        first_output_us: first_output.get().map(|&at| clock.micros_at(at)),
+        total_us: clock.micros_at(Instant::now()),
+    };
+    if stdout_lossy || stderr_lossy {
+        eprintln!(
+            "{} The script printed invalid UTF-8; it was passed through unchanged, but the recorded output has those bytes replaced with U+FFFD.",
+            "Warning:".yellow().bold()
+        );
+    }
+
+    if verbose {
+        println!();
+        println!(
+            "  {}",
+            format!(
+                "Timing: write {}, spawn {}, wait {}",
+                format_phase(clock.write),
+                format_phase(spawn_time),
+                format_phase(wait_time)
+            )
+            .dimmed()
+        );
+    }
 
     Ok(ExecutionResult {
-        exit_code: status.code().unwrap_or(1),
+        resource_usage,
+        timings: Some(timings),
+        exit_code: if timed_out {
+            TIMED_OUT_EXIT_CODE
+        } else {
+            status.code().unwrap_or(1)
+        },
         output: if stdout_str.is_empty() {
             None
         } else {
@@ -524,16 +1716,60 @@ fn spawn_and_collect(
     })
 }
 
+/// Where a child's output stream is copied while it runs: the terminal, or
+/// nowhere when the output is only captured.
+fn relay_sink(echo: Echo, stream: impl Write + Send + 'static) -> Box<dyn Write + Send> {
+    match echo {
+        Echo::Quiet => Box::new(std::io::sink()),
+        Echo::Normal | Echo::Verbose => Box::new(stream),
+    }
+}
+
+/// Copies a child's output stream to `sink` line by line as it arrives and
+/// returns the captured text. Bytes are passed through untouched; the capture
+/// is decoded lossily, and the flag is set when anything had to be replaced.
+/// `first_output` is set when the first byte arrives on this or a sibling
+/// stream.
+fn relay_output(
+    mut reader: impl BufRead,
+    mut sink: impl Write,
+    first_output: &OnceLock<Instant>,
+) -> (String, bool) {
+    if reader.fill_buf().is_ok_and(|buf| !buf.is_empty()) {
+        first_output.get_or_init(Instant::now);
+    }
+    let mut captured = String::new();
+    let mut lossy = false;
+    let mut line = Vec::new();
+    while reader.read_until(b'\n', &mut line).unwrap_or(0) > 0 {
+        let _ = sink.write_all(&line);
+        let _ = sink.flush();
+        match String::from_utf8_lossy(&line) {
+            std::borrow::Cow::Borrowed(text) => captured.push_str(text),
+            std::borrow::Cow::Owned(text) => {
+                lossy = true;
+                captured.push_str(&text);
+            }
+        }
+        line.clear();
+    }
+    (captured, lossy)
+}
+
 fn execute_script_safe_env(
     script: &Script,
+    interpreter: &Interpreter,
     args: &[String],
-    verbose: bool,
+    env_allow: Option<&[String]>,
+    echo: Echo,
+    timeout: Option<&Timeout>,
 ) -> Result<ExecutionResult> {
-    let script_path = write_temp_script(script)?;
-    let (interpreter, interpreter_args) = get_interpreter_command(&script.language);
-    let safe_env = build_safe_env();
+    let scratch = ScratchDir::new("run");
+    let script_path = scratch.script_path(script);
+    let clock = RunClock::write_script(script, &script_path)?;
+    let safe_env = script_env(script, None, env_allow);
 
-    if verbose {
+    if echo == Echo::Verbose {
         println!();
         println!("  {}:", "Content".dimmed());
         for line in script.content.lines() {
@@ -542,62 +1778,33 @@ fn execute_script_safe_env(
         println!();
     }
 
-    let result = spawn_and_collect(
+    spawn_and_collect(
         interpreter,
-        &interpreter_args,
         &script_path,
         args,
         Some(&safe_env),
-        verbose,
-    );
-
-    if let Err(e) = fs::remove_file(&script_path) {
-        eprintln!("Warning: failed to remove temporary file: {}", e);
-    }
-
-    result
+        echo,
+        clock,
+        timeout,
+    )
 }
 
 fn execute_script_isolated(
     script: &Script,
+    interpreter: &Interpreter,
     args: &[String],
-    verbose: bool,
+    env_allow: Option<&[String]>,
+    echo: Echo,
+    timeout: Option<&Timeout>,
 ) -> Result<ExecutionResult> {
-    let sandbox_dir = std::env::temp_dir()
-        .join("scriptvault")
-        .join("isolated")
-        .join(uuid::Uuid::new_v4().to_string());
-
-    fs::create_dir_all(&sandbox_dir)?;
-
-    let script_filename = format!("script.{}", script.language.extension());
-    let script_path = sandbox_dir.join(&script_filename);
-
-    fs::write(&script_path, &script.content)?;
-
-    #[cfg(unix)]
-    {
-        use std::os::unix::fs::PermissionsExt;
-        let mut perms = fs::metadata(&script_path)?.permissions();
-        perms.set_mode(0o755);
-        fs::set_permissions(&script_path, perms)?;
-    }
-
-    let mut env: HashMap<String, String> = HashMap::new();
-    env.insert("HOME".into(), sandbox_dir.to_string_lossy().into_owned());
-    env.insert("TMPDIR".into(), sandbox_dir.to_string_lossy().into_owned());
-    env.insert("PATH".into(), std::env::var("PATH").unwrap_or_default());
-    env.insert("ISOLATED".into(), "1".into());
+    let scratch = ScratchDir::new("isolated");
+    let script_path = scratch.script_path(script);
+    let clock = RunClock::write_script(script, &script_path)?;
 
-    if let Ok(term) = std::env::var("TERM") {
-        env.insert("TERM".into(), term);
-    }
-    if let Ok(lang) = std::env::var("LANG") {
-        env.insert("LANG".into(), lang);
-    }
+    let env = script_env(script, Some(&scratch.path), env_allow);
 
-    if verbose {
-        println!("  Isolated directory: {}", sandbox_dir.display());
+    if echo == Echo::Verbose {
+        println!("  Isolated directory: {}", scratch.path.display());
         println!();
         println!("  {}:", "Content".dimmed());
         for line in script.content.lines() {
@@ -606,21 +1813,174 @@ fn execute_script_isolated(
         println!();
     }
 
-    let (interpreter, interpreter_args) = get_interpreter_command(&script.language);
-    let result = spawn_and_collect(
+    spawn_and_collect(
         interpreter,
-        &interpreter_args,
         &script_path,
         args,
         Some(&env),
-        verbose,
-    );
+        echo,
+        clock,
+        timeout,
+    )
+}
+
+/// Splits an `--args-file` into arguments. Whitespace and newlines separate
+/// arguments; single quotes keep everything literally, double quotes allow
+/// `\"` and `\\` escapes, and a backslash outside quotes escapes the next
+/// character. A `#` at the start of an argument comments out the rest of the
+/// line.
+fn parse_args_file(text: &str) -> Result<Vec<String>> {
+    let mut args = Vec::new();
+    let mut current: Option<String> = None;
+    let mut chars = text.chars();
+
+    while let Some(c) = chars.next() {
+        match c {
+            c if c.is_whitespace() => {
+                args.extend(current.take());
+            }
+            '#' if current.is_none() => {
+                for c in chars.by_ref() {
+                    if c == '\n' {
+                        break;
+                    }
+                }
+            }
+            '\'' => {
+                let arg = current.get_or_insert_with(String::new);
+                loop {
+                    match chars.next() {
+                        Some('\'') => break,
+                        Some(c) => arg.push(c),
+                        None => return Err(anyhow!("unterminated single quote")),
+                    }
+                }
+            }
+            '"' => {
+                let arg = current.get_or_insert_with(String::new);
+                loop {
+                    match chars.next() {
+                        Some('"') => break,
+                        Some('\\') => match chars.next() {
+                            Some(c @ ('"' | '\\')) => arg.push(c),
+                            Some(c) => {
+                                arg.push('\\');
+                                arg.push(c);
+                            }
+                            None => return Err(anyhow!("unterminated double quote")),
+                        },
+                        Some(c) => arg.push(c),
+                        None => return Err(anyhow!("unterminated double quote")),
+                    }
+                }
+            }
+            '\\' => match chars.next() {
+                // A backslash before a newline continues the line.
+                Some('\n') => {}
+                next => current
+                    .get_or_insert_with(String::new)
+                    .push(next.unwrap_or('\\')),
+            },
+            c => current.get_or_insert_with(String::new).push(c),
+        }
+    }
+    args.extend(current);
+    Ok(args)
+}
+
+/// Fills in declared parameters that weren't passed positionally. Required
+/// parameters are prompted for interactively; with `prompt_all`, optional ones
+/// are too. In CI mode nothing is prompted and missing required parameters are
+/// an error.
+fn resolve_params(
+    params: &[ScriptParam],
+    supplied: &[String],
+    ci_mode: bool,
+    prompt_all: bool,
+    mut ask: impl FnMut(&ScriptParam) -> Result<String>,
+) -> Result<Vec<String>> {
+    let mut resolved = supplied.to_vec();
+    let mut missing = Vec::new();
+
+    for param in params.iter().skip(supplied.len()) {
+        let should_prompt = !ci_mode && (param.required || prompt_all);
+        if should_prompt {
+            resolved.push(ask(param)?);
+        } else if let Some(ref default) = param.default {
+            resolved.push(default.clone());
+        } else if param.required {
+            missing.push(param.name.as_str());
+        } else {
+            resolved.push(String::new());
+        }
+    }
+
+    if !missing.is_empty() {
+        return Err(anyhow!(
+            "Missing required parameters: {}. Pass them as arguments after the script name.",
+            missing.join(", ")
+        ));
+    }
+
+    Ok(resolved)
+}
+
+fn prompt_for_param(param: &ScriptParam) -> Result<String> {
+    let prompt = match param.description {
+        Some(ref desc) => format!("{} ({})", param.name, desc),
+        None => param.name.clone(),
+    };
+    let mut input = Input::<String>::new()
+        .with_prompt(prompt)
+        .allow_empty(!param.required);
+    if let Some(ref default) = param.default {
+        input = input.default(default.clone());
+    }
+    Ok(input.interact_text()?)
+}
 
-    if let Err(e) = fs::remove_dir_all(&sandbox_dir) {
-        eprintln!("Warning: failed to remove isolated directory: {}", e);
+/// Hooks are run as throwaway scripts through the same interpreter and
+/// environment rules as the script they belong to.
+fn run_hook(
+    kind: &str,
+    hook: &str,
+    script: &Script,
+    interpreter: &Interpreter,
+    sandbox: bool,
+    env_allow: Option<&[String]>,
+    echo: Echo,
+) -> Result<i32> {
+    let _span = tracing::debug_span!("hook", kind).entered();
+    // Hooks never get the verbose treatment, only the script itself.
+    let echo = match echo {
+        Echo::Quiet => Echo::Quiet,
+        Echo::Normal | Echo::Verbose => Echo::Normal,
+    };
+    if echo != Echo::Quiet {
+        println!(
+            "{} {}",
+            format!("Running {} hook:", kind).dimmed(),
+            hook.dimmed()
+        );
     }
 
-    result
+    let hook_script = Script {
+        content: hook.to_string(),
+        ..script.clone()
+    };
+    let result = if sandbox {
+        execute_script_isolated(&hook_script, interpreter, &[], env_allow, echo, None)?
+    } else {
+        execute_script_safe_env(&hook_script, interpreter, &[], env_allow, echo, None)?
+    };
+
+    tracing::debug!(exit_code = result.exit_code, "hook finished");
+    Ok(result.exit_code)
+}
+
+/// The program `sv run` would launch for this language by default.
+pub(crate) fn default_interpreter(language: &ScriptLanguage) -> &'static str {
+    get_interpreter_command(language).0
 }
 
 fn get_interpreter_command(language: &ScriptLanguage) -> (&'static str, Vec<&'static str>) {
@@ -640,28 +2000,32 @@ pub fn show_history(args: HistoryArgs) -> Result<()> {
         return Err(anyhow!("Team history is not yet available."));
     }
 
-    let history_path = Config::history_path()?;
+    if args.follow {
+        return follow_history(&args);
+    }
 
-    if !history_path.exists() {
-        println!("No execution history found.");
+    let records = load_history()?;
+    if records.is_empty() {
+        if args.count {
+            println!("0");
+        } else {
+            println!("No execution history found.");
+        }
         return Ok(());
     }
 
-    let contents = fs::read_to_string(history_path)?;
-    let records: Vec<ExecutionRecord> = contents
-        .lines()
-        .filter(|l| !l.is_empty())
-        .filter_map(|line| serde_json::from_str(line).ok())
-        .collect();
-
     let scripts = load_scripts_local()?;
-    let script_map: HashMap<String, String> = scripts
+    let script_map: HashMap<&str, &str> = scripts
         .iter()
-        .map(|s| (s.id.clone(), s.name.clone()))
+        .map(|s| (s.id.as_str(), s.name.as_str()))
         .collect();
 
     if let Some(ref script_name) = args.script {
         let found = scripts.iter().any(|s| s.name == *script_name);
+        if !found && args.count {
+            println!("0");
+            return Ok(());
+        }
         if !found {
             println!(
                 "Note: '{}' is not in your vault (it may have been deleted).",
@@ -673,45 +2037,30 @@ pub fn show_history(args: HistoryArgs) -> Result<()> {
         }
     }
 
-    let filtered: Vec<&ExecutionRecord> = records
-        .iter()
-        .filter(|r| {
-            if let Some(ref script_name) = args.script {
-                let matched_id = scripts
-                    .iter()
-                    .find(|s| s.name == *script_name)
-                    .map(|s| s.id.as_str());
-                if let Some(id) = matched_id {
-                    if r.script_id != id {
-                        return false;
-                    }
-                } else {
-                    return false;
-                }
-            }
-            if args.failed && r.exit_code == 0 {
-                return false;
-            }
-            true
-        })
-        .collect();
+    let filtered = filter_history(&records, &scripts, args.script.as_deref(), args.failed);
+
+    if args.count {
+        println!("{}", filtered.len());
+        return Ok(());
+    }
 
     if filtered.is_empty() {
         println!("No execution history found.");
         return Ok(());
     }
 
-    println!("{}", "Execution History".cyan().bold());
-    println!();
-    println!(
-        "{:<20} {:<22} {:<15} {:<10} {:<10}",
-        "TIME".bold(),
-        "SCRIPT".bold(),
-        "USER".bold(),
-        "EXIT CODE".bold(),
-        "DURATION".bold()
-    );
-    println!("{}", "─".repeat(80).dimmed());
+    if args.stats {
+        print_history_rollup(&compute_rollup(&filtered), &script_map);
+        return Ok(());
+    }
+
+    if let Some(ref group) = args.group_by {
+        let group = HistoryGroup::parse(group)?;
+        print_history_groups(group, &group_history(&filtered, group, &script_map));
+        return Ok(());
+    }
+
+    print_history_header();
 
     let limit = if args.recent {
         10
@@ -720,34 +2069,569 @@ pub fn show_history(args: HistoryArgs) -> Result<()> {
     };
 
     for record in filtered.iter().rev().take(limit) {
-        let time = record.executed_at.format("%Y-%m-%d %H:%M:%S");
+        print_history_row(record, &script_map);
+    }
+
+    Ok(())
+}
+
+fn print_history_header() {
+    println!("{}", "Execution History".cyan().bold());
+    println!();
+    println!(
+        "{:<20} {:<22} {:<15} {:<10} {:<10}",
+        "TIME".bold(),
+        "SCRIPT".bold(),
+        "USER".bold(),
+        "EXIT CODE".bold(),
+        "DURATION".bold()
+    );
+    println!("{}", "─".repeat(80).dimmed());
+}
+
+fn print_history_row(record: &ExecutionRecord, script_map: &HashMap<&str, &str>) {
+    let time = record.executed_at.format("%Y-%m-%d %H:%M:%S");
+
+    let script_display = match script_map.get(record.script_id.as_str()) {
+        Some(name) => format!("{} {}", name.yellow(), record.script_version.dimmed()),
+        None => "[deleted]".dimmed().to_string(),
+    };
+
+    println!(
+        "{:<20} {:<22} {:<15} {:<10} {:<10}",
+        time.to_string().dimmed(),
+        script_display,
+        record.executed_by,
+        exit_status_label(record),
+        format_record_duration(record)
+    );
+}
+
+/// The EXIT CODE column of `sv history`, colored by outcome.
+pub(crate) fn exit_status_label(record: &ExecutionRecord) -> colored::ColoredString {
+    if !record.was_executed() {
+        "not run".dimmed()
+    } else if record.exit_code == INTERPRETER_NOT_FOUND_EXIT_CODE {
+        "no interp".red()
+    } else if record.was_successful() {
+        record.exit_code.to_string().green()
+    } else {
+        record.exit_code.to_string().red()
+    }
+}
+
+pub(crate) fn format_record_duration(record: &ExecutionRecord) -> String {
+    format!("{:.2}s", record.duration_ms as f64 / 1000.0)
+}
+
+/// Runs already in the history when `sv history --follow` starts that are
+/// shown before waiting for new ones.
+const FOLLOW_BACKLOG: usize = 10;
+const FOLLOW_POLL_INTERVAL: std::time::Duration = std::time::Duration::from_millis(500);
+
+/// Prints matching runs, oldest first, as they are appended to the history.
+/// Never returns on its own; the user stops it with Ctrl-C.
+fn follow_history(args: &HistoryArgs) -> Result<()> {
+    println!("{}", "Following new runs. Press Ctrl-C to stop.".dimmed());
+    println!();
+    print_history_header();
+
+    let mut follower = HistoryFollower::new(Config::history_path()?);
+    let mut first = true;
+    loop {
+        let records = follower.poll()?;
+        if !records.is_empty() {
+            let scripts = load_scripts_local()?;
+            let script_map: HashMap<&str, &str> = scripts
+                .iter()
+                .map(|s| (s.id.as_str(), s.name.as_str()))
+                .collect();
+            let matching = filter_history(&records, &scripts, args.script.as_deref(), args.failed);
+            let skip = if first {
+                matching.len().saturating_sub(FOLLOW_BACKLOG)
+            } else {
+                0
+            };
+            for record in matching.iter().skip(skip) {
+                print_history_row(record, &script_map);
+            }
+        }
+        first = false;
+        std::thread::sleep(FOLLOW_POLL_INTERVAL);
+    }
+}
+
+/// Tails the history file. Appends are not read from a saved offset, since
+/// rotation rewrites the file and it may also be truncated by hand. Instead
+/// the file is re-read whenever its size or mtime changes, and everything
+/// after the last record seen is returned. If that record is gone, as after
+/// a truncation, every record in the file is new.
+struct HistoryFollower {
+    path: PathBuf,
+    last_id: Option<String>,
+    last_state: Option<(u64, SystemTime)>,
+}
+
+impl HistoryFollower {
+    fn new(path: PathBuf) -> Self {
+        Self {
+            path,
+            last_id: None,
+            last_state: None,
+        }
+    }
+
+    fn file_state(&self) -> Option<(u64, SystemTime)> {
+        let meta = fs::metadata(&self.path).ok()?;
+        Some((meta.len(), meta.modified().ok()?))
+    }
+
+    /// Records added since the previous call; on the first call, all of them.
+    fn poll(&mut self) -> Result<Vec<ExecutionRecord>> {
+        let state = self.file_state();
+        if state.is_some() && state == self.last_state {
+            return Ok(Vec::new());
+        }
+        self.last_state = state;
+
+        let mut records = read_history_file(&self.path)?;
+        let start = self
+            .last_id
+            .as_ref()
+            .and_then(|id| records.iter().position(|r| &r.id == id))
+            .map_or(0, |i| i + 1);
+        let new = records.split_off(start);
+        if let Some(last) = new.last() {
+            self.last_id = Some(last.id.clone());
+        }
+        Ok(new)
+    }
+}
 
-        let script_display = match script_map.get(&record.script_id) {
-            Some(name) => name.yellow().to_string(),
-            None => "[deleted]".dimmed().to_string(),
+#[derive(Debug, PartialEq)]
+struct ScriptRollup {
+    script_id: String,
+    runs: usize,
+    failures: usize,
+    avg_duration_ms: u64,
+    last_run: DateTime<Utc>,
+}
+
+/// Per-script totals for `sv history --stats`, most recently run first.
+/// Runs logged with `--record-only` are left out.
+fn compute_rollup(records: &[&ExecutionRecord]) -> Vec<ScriptRollup> {
+    let mut by_script: HashMap<&str, (usize, usize, u64, DateTime<Utc>)> = HashMap::new();
+    for record in records.iter().filter(|r| r.was_executed()) {
+        let entry =
+            by_script
+                .entry(record.script_id.as_str())
+                .or_insert((0, 0, 0, record.executed_at));
+        entry.0 += 1;
+        if !record.was_successful() {
+            entry.1 += 1;
+        }
+        entry.2 += record.duration_ms;
+        entry.3 = entry.3.max(record.executed_at);
+    }
+
+    let mut rollup: Vec<ScriptRollup> = by_script
+        .into_iter()
+        .map(|(id, (runs, failures, total_ms, last_run))| ScriptRollup {
+            script_id: id.to_string(),
+            runs,
+            failures,
+            avg_duration_ms: total_ms / runs as u64,
+            last_run,
+        })
+        .collect();
+    rollup.sort_by(|a, b| {
+        b.last_run
+            .cmp(&a.last_run)
+            .then_with(|| a.script_id.cmp(&b.script_id))
+    });
+    rollup
+}
+
+fn print_history_rollup(rollup: &[ScriptRollup], script_map: &HashMap<&str, &str>) {
+    println!("{}", "Execution History by Script".cyan().bold());
+    println!();
+    println!(
+        "{:<22} {:<6} {:<9} {:<13} {:<20}",
+        "SCRIPT".bold(),
+        "RUNS".bold(),
+        "FAILURES".bold(),
+        "AVG DURATION".bold(),
+        "LAST RUN".bold()
+    );
+    println!("{}", "─".repeat(80).dimmed());
+
+    for row in rollup {
+        let name = match script_map.get(row.script_id.as_str()) {
+            Some(name) => name.yellow(),
+            None => "[deleted]".dimmed(),
         };
+        let failures = if row.failures == 0 {
+            row.failures.to_string().green()
+        } else {
+            row.failures.to_string().red()
+        };
+        println!(
+            "{:<22} {:<6} {:<9} {:<13} {:<20}",
+            name,
+            row.runs,
+            failures,
+            format!("{:.2}s", row.avg_duration_ms as f64 / 1000.0),
+            humanize_duration(row.last_run).dimmed()
+        );
+    }
+}
 
-        let exit_status = if record.exit_code == 0 {
-            record.exit_code.to_string().green()
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum HistoryGroup {
+    User,
+    Script,
+    Day,
+}
+
+impl HistoryGroup {
+    fn parse(value: &str) -> Result<Self> {
+        match value {
+            "user" => Ok(HistoryGroup::User),
+            "script" => Ok(HistoryGroup::Script),
+            "day" => Ok(HistoryGroup::Day),
+            _ => Err(anyhow!(
+                "Unknown group: '{}'. Supported: user, script, day",
+                value
+            )),
+        }
+    }
+
+    fn label(self) -> &'static str {
+        match self {
+            HistoryGroup::User => "User",
+            HistoryGroup::Script => "Script",
+            HistoryGroup::Day => "Day",
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+struct GroupTotals {
+    key: String,
+    runs: usize,
+    failures: usize,
+    total_duration_ms: u64,
+}
+
+/// Subtotals for `sv history --group-by`. Days are in UTC, newest first;
+/// users and scripts are sorted by name. Runs logged with `--record-only`
+/// are left out.
+fn group_history(
+    records: &[&ExecutionRecord],
+    group: HistoryGroup,
+    script_map: &HashMap<&str, &str>,
+) -> Vec<GroupTotals> {
+    let mut groups: BTreeMap<String, GroupTotals> = BTreeMap::new();
+    for record in records.iter().filter(|r| r.was_executed()) {
+        let key = match group {
+            HistoryGroup::User => record.executed_by.clone(),
+            HistoryGroup::Script => script_map
+                .get(record.script_id.as_str())
+                .map_or_else(|| "[deleted]".to_string(), |name| name.to_string()),
+            HistoryGroup::Day => record.executed_at.format("%Y-%m-%d").to_string(),
+        };
+        let totals = groups.entry(key.clone()).or_insert(GroupTotals {
+            key,
+            runs: 0,
+            failures: 0,
+            total_duration_ms: 0,
+        });
+        totals.runs += 1;
+        if !record.was_successful() {
+            totals.failures += 1;
+        }
+        totals.total_duration_ms += record.duration_ms;
+    }
+
+    let mut totals: Vec<GroupTotals> = groups.into_values().collect();
+    if group == HistoryGroup::Day {
+        totals.reverse();
+    }
+    totals
+}
+
+fn print_history_groups(group: HistoryGroup, totals: &[GroupTotals]) {
+    println!(
+        "{}",
+        format!("Execution History by {}", group.label())
+            .cyan()
+            .bold()
+    );
+    println!();
+    println!(
+        "{:<22} {:<6} {:<9} {:<15}",
+        group.label().to_uppercase().bold(),
+        "RUNS".bold(),
+        "FAILURES".bold(),
+        "TOTAL DURATION".bold()
+    );
+    println!("{}", "─".repeat(80).dimmed());
+
+    let row = |key: ColoredString, runs: usize, failures: usize, duration_ms: u64| {
+        let failures = if failures == 0 {
+            failures.to_string().green()
+        } else {
+            failures.to_string().red()
+        };
+        println!(
+            "{:<22} {:<6} {:<9} {:<15}",
+            key,
+            runs,
+            failures,
+            format!("{:.2}s", duration_ms as f64 / 1000.0)
+        );
+    };
+    for totals in totals {
+        let key = if totals.key == "[deleted]" {
+            totals.key.dimmed()
         } else {
-            record.exit_code.to_string().red()
+            totals.key.yellow()
         };
+        row(key, totals.runs, totals.failures, totals.total_duration_ms);
+    }
+
+    println!("{}", "─".repeat(80).dimmed());
+    row(
+        "Total".bold(),
+        totals.iter().map(|t| t.runs).sum(),
+        totals.iter().map(|t| t.failures).sum(),
+        totals.iter().map(|t| t.total_duration_ms).sum(),
+    );
+}
+
+fn filter_history<'a>(
+    records: &'a [ExecutionRecord],
+    scripts: &[Script],
+    script_name: Option<&str>,
+    failed_only: bool,
+) -> Vec<&'a ExecutionRecord> {
+    let mut ids_by_name: HashMap<&str, HashSet<&str>> = HashMap::new();
+    for script in scripts {
+        ids_by_name
+            .entry(script.name.as_str())
+            .or_default()
+            .insert(script.id.as_str());
+    }
+
+    let empty = HashSet::new();
+    let wanted_ids = script_name.map(|name| ids_by_name.get(name).unwrap_or(&empty));
+
+    records
+        .iter()
+        .filter(|r| {
+            if let Some(ids) = wanted_ids
+                && !ids.contains(r.script_id.as_str())
+            {
+                return false;
+            }
+            !failed_only || (r.was_executed() && !r.was_successful())
+        })
+        .collect()
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum TrendBucket {
+    Daily,
+    Weekly,
+}
+
+impl TrendBucket {
+    fn parse(value: &str) -> Result<Self> {
+        match value.to_lowercase().as_str() {
+            "daily" | "day" => Ok(TrendBucket::Daily),
+            "weekly" | "week" => Ok(TrendBucket::Weekly),
+            _ => Err(anyhow!(
+                "Unknown bucket: '{}'. Supported: daily, weekly",
+                value
+            )),
+        }
+    }
+
+    fn days(self) -> i64 {
+        match self {
+            TrendBucket::Daily => 1,
+            TrendBucket::Weekly => 7,
+        }
+    }
+
+    fn start_of(self, date: NaiveDate) -> NaiveDate {
+        match self {
+            TrendBucket::Daily => date,
+            TrendBucket::Weekly => {
+                date - Duration::days(date.weekday().num_days_from_monday() as i64)
+            }
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+struct TrendPoint {
+    start: NaiveDate,
+    runs: usize,
+    successes: usize,
+}
+
+impl TrendPoint {
+    fn success_rate(&self) -> Option<f64> {
+        if self.runs == 0 {
+            return None;
+        }
+        Some((self.successes as f64 / self.runs as f64) * 100.0)
+    }
+}
+
+/// Buckets records by day or week. Buckets with no runs between the first and
+/// last are kept so gaps show up in the sparkline.
+fn compute_trend(records: &[&ExecutionRecord], bucket: TrendBucket) -> Vec<TrendPoint> {
+    let mut counts: BTreeMap<NaiveDate, (usize, usize)> = BTreeMap::new();
+    for record in records.iter().filter(|r| r.was_executed()) {
+        let entry = counts
+            .entry(bucket.start_of(record.executed_at.date_naive()))
+            .or_default();
+        entry.0 += 1;
+        if record.was_successful() {
+            entry.1 += 1;
+        }
+    }
+
+    let (Some(&first), Some(&last)) = (counts.keys().next(), counts.keys().next_back()) else {
+        return Vec::new();
+    };
+
+    let mut points = Vec::new();
+    let mut start = first;
+    while start <= last {
+        let (runs, successes) = counts.get(&start).copied().unwrap_or_default();
+        points.push(TrendPoint {
+            start,
+            runs,
+            successes,
+        });
+        start += Duration::days(bucket.days());
+    }
+    points
+}
+
+fn sparkline(values: &[Option<f64>], max: f64) -> String {
+    const BARS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+    values
+        .iter()
+        .map(|v| match v {
+            Some(v) if max > 0.0 => {
+                let idx = ((v / max) * (BARS.len() - 1) as f64).round() as usize;
+                BARS[idx.min(BARS.len() - 1)]
+            }
+            Some(_) => BARS[0],
+            None => ' ',
+        })
+        .collect()
+}
 
-        let duration = format!("{:.2}s", record.duration_ms as f64 / 1000.0);
+pub fn show_trend(args: TrendArgs) -> Result<()> {
+    let bucket = TrendBucket::parse(&args.bucket)?;
 
+    let scripts = load_scripts_local()?;
+    if !scripts.iter().any(|s| s.name == args.name) {
+        return Err(anyhow!("Script not found: {}", args.name));
+    }
+
+    let records = load_history()?;
+    let matching = filter_history(&records, &scripts, Some(&args.name), false);
+    let points = compute_trend(&matching, bucket);
+
+    if points.is_empty() {
+        println!("No execution history for '{}' yet.", args.name);
+        println!("Run 'sv run {}' to start recording a trend.", args.name);
+        return Ok(());
+    }
+
+    let label = match bucket {
+        TrendBucket::Daily => "daily",
+        TrendBucket::Weekly => "weekly",
+    };
+    println!(
+        "{} {}",
+        format!("Trend: {}", args.name).cyan().bold(),
+        format!("({})", label).dimmed()
+    );
+    println!();
+
+    let rates: Vec<Option<f64>> = points.iter().map(|p| p.success_rate()).collect();
+    let runs: Vec<Option<f64>> = points.iter().map(|p| Some(p.runs as f64)).collect();
+    let max_runs = points.iter().map(|p| p.runs).max().unwrap_or(0) as f64;
+
+    println!("  Success rate  {}", sparkline(&rates, 100.0).green());
+    println!("  Runs          {}", sparkline(&runs, max_runs).cyan());
+    println!();
+
+    println!(
+        "  {:<12} {:<6} {}",
+        "PERIOD".bold(),
+        "RUNS".bold(),
+        "SUCCESS RATE".bold()
+    );
+    println!("  {}", "─".repeat(34).dimmed());
+    for point in &points {
+        let rate = match point.success_rate() {
+            Some(rate) => color_success_rate(rate).to_string(),
+            None => "-".dimmed().to_string(),
+        };
         println!(
-            "{:<20} {:<22} {:<15} {:<10} {:<10}",
-            time.to_string().dimmed(),
-            script_display,
-            record.executed_by,
-            exit_status,
-            duration
+            "  {:<12} {:<6} {}",
+            point.start.format("%Y-%m-%d"),
+            point.runs,
+            rate
         );
     }
 
     Ok(())
 }
 
+pub(crate) fn load_history() -> Result<Vec<ExecutionRecord>> {
+    read_history_file(&Config::history_path()?)
+}
+
+/// Records in a history file, skipping lines that don't parse, such as one
+/// still being written.
+fn read_history_file(path: &Path) -> Result<Vec<ExecutionRecord>> {
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+
+    let contents = fs::read_to_string(path)?;
+    Ok(contents
+        .lines()
+        .filter(|l| !l.is_empty())
+        .filter_map(|line| serde_json::from_str(line).ok())
+        .collect())
+}
+
+/// Caps captured output kept in history at `max_bytes`, cutting on a char
+/// boundary and noting how much was dropped. A limit of 0 keeps everything.
+fn truncate_output(mut text: String, max_bytes: usize) -> String {
+    if max_bytes == 0 || text.len() <= max_bytes {
+        return text;
+    }
+    let mut cut = max_bytes;
+    while !text.is_char_boundary(cut) {
+        cut -= 1;
+    }
+    let dropped = text.len() - cut;
+    text.truncate(cut);
+    text.push_str(&format!("... [truncated {} bytes]", dropped));
+    text
+}
+
 fn save_execution_record(record: &ExecutionRecord) -> Result<()> {
     let history_path = Config::history_path()?;
 
@@ -776,3 +2660,1063 @@ fn rotate_history(path: &Path) -> Result<()> {
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::script::ScriptContext;
+
+    fn make_record(script_id: &str, exit_code: i32) -> ExecutionRecord {
+        ExecutionRecord {
+            id: uuid::Uuid::new_v4().to_string(),
+            script_id: script_id.to_string(),
+            script_version: "v1.0.0".to_string(),
+            executed_by: "test".to_string(),
+            executed_at: chrono::Utc::now(),
+            exit_code,
+            duration_ms: 100,
+            output: None,
+            error: None,
+            context: ScriptContext {
+                directory: None,
+                git_repo: None,
+                git_branch: None,
+                environment: HashMap::new(),
+                os: None,
+                arch: None,
+                hostname: None,
+            },
+            succeeded: None,
+            resource_usage: None,
+            timings: None,
+        }
+    }
+
+    fn make_record_at(script_id: &str, exit_code: i32, executed_at: &str) -> ExecutionRecord {
+        ExecutionRecord {
+            executed_at: executed_at.parse().unwrap(),
+            ..make_record(script_id, exit_code)
+        }
+    }
+
+    fn make_script(name: &str) -> Script {
+        Script::new(
+            name.to_string(),
+            "echo test".to_string(),
+            ScriptLanguage::Bash,
+        )
+    }
+
+    #[test]
+    fn test_filter_history_by_name() {
+        let deploy = make_script("deploy");
+        let backup = make_script("backup");
+        let records = vec![
+            make_record(&deploy.id, 0),
+            make_record(&backup.id, 0),
+            make_record(&deploy.id, 1),
+        ];
+        let scripts = vec![deploy.clone(), backup];
+
+        let filtered = filter_history(&records, &scripts, Some("deploy"), false);
+        assert_eq!(filtered.len(), 2);
+        assert!(filtered.iter().all(|r| r.script_id == deploy.id));
+    }
+
+    #[test]
+    fn test_filter_history_duplicate_names_match_all_ids() {
+        let first = make_script("deploy");
+        let second = make_script("deploy");
+        let other = make_script("backup");
+        let records = vec![
+            make_record(&first.id, 0),
+            make_record(&second.id, 0),
+            make_record(&other.id, 0),
+        ];
+        let scripts = vec![first.clone(), second.clone(), other];
+
+        let filtered = filter_history(&records, &scripts, Some("deploy"), false);
+        let ids: HashSet<&str> = filtered.iter().map(|r| r.script_id.as_str()).collect();
+        assert_eq!(filtered.len(), 2);
+        assert!(ids.contains(first.id.as_str()));
+        assert!(ids.contains(second.id.as_str()));
+    }
+
+    #[test]
+    fn test_filter_history_failed_only() {
+        let deploy = make_script("deploy");
+        let records = vec![
+            make_record(&deploy.id, 0),
+            make_record(&deploy.id, 2),
+            make_record(&deploy.id, NOT_EXECUTED_EXIT_CODE),
+        ];
+        let scripts = vec![deploy];
+
+        let filtered = filter_history(&records, &scripts, None, true);
+        assert_eq!(filtered.len(), 1);
+        assert_eq!(filtered[0].exit_code, 2);
+    }
+
+    #[test]
+    fn test_group_history_by_day() {
+        let records = [
+            ExecutionRecord {
+                duration_ms: 200,
+                ..make_record_at("deploy", 0, "2026-03-01T10:00:00Z")
+            },
+            ExecutionRecord {
+                duration_ms: 300,
+                ..make_record_at("backup", 1, "2026-03-01T23:59:59Z")
+            },
+            ExecutionRecord {
+                duration_ms: 1500,
+                ..make_record_at("deploy", 2, "2026-03-03T00:00:00Z")
+            },
+            make_record_at("deploy", NOT_EXECUTED_EXIT_CODE, "2026-03-04T10:00:00Z"),
+        ];
+        let refs: Vec<&ExecutionRecord> = records.iter().collect();
+
+        let totals = group_history(&refs, HistoryGroup::Day, &HashMap::new());
+        assert_eq!(
+            totals,
+            [
+                GroupTotals {
+                    key: "2026-03-03".to_string(),
+                    runs: 1,
+                    failures: 1,
+                    total_duration_ms: 1500,
+                },
+                GroupTotals {
+                    key: "2026-03-01".to_string(),
+                    runs: 2,
+                    failures: 1,
+                    total_duration_ms: 500,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_group_history_by_script_and_user() {
+        let records = [
+            ExecutionRecord {
+                executed_by: "bob".to_string(),
+                ..make_record("deploy", 0)
+            },
+            make_record("deploy", 1),
+            make_record("gone", 0),
+        ];
+        let refs: Vec<&ExecutionRecord> = records.iter().collect();
+        let script_map = HashMap::from([("deploy", "deploy")]);
+
+        let by_script = group_history(&refs, HistoryGroup::Script, &script_map);
+        let keys: Vec<(&str, usize, usize)> = by_script
+            .iter()
+            .map(|t| (t.key.as_str(), t.runs, t.failures))
+            .collect();
+        assert_eq!(keys, [("[deleted]", 1, 0), ("deploy", 2, 1)]);
+
+        let by_user = group_history(&refs, HistoryGroup::User, &script_map);
+        let keys: Vec<(&str, usize)> = by_user.iter().map(|t| (t.key.as_str(), t.runs)).collect();
+        assert_eq!(keys, [("bob", 1), ("test", 2)]);
+    }
+
+    #[test]
+    fn test_history_rollup_per_script() {
+        let records = [
+            ExecutionRecord {
+                duration_ms: 200,
+                ..make_record_at("deploy", 0, "2026-03-01T10:00:00Z")
+            },
+            ExecutionRecord {
+                duration_ms: 400,
+                ..make_record_at("deploy", 1, "2026-03-03T10:00:00Z")
+            },
+            ExecutionRecord {
+                duration_ms: 600,
+                ..make_record_at("deploy", 2, "2026-03-02T10:00:00Z")
+            },
+            make_record_at("backup", 0, "2026-03-04T10:00:00Z"),
+            make_record_at("backup", NOT_EXECUTED_EXIT_CODE, "2026-03-05T10:00:00Z"),
+        ];
+        let refs: Vec<&ExecutionRecord> = records.iter().collect();
+
+        let rollup = compute_rollup(&refs);
+        assert_eq!(
+            rollup,
+            [
+                ScriptRollup {
+                    script_id: "backup".to_string(),
+                    runs: 1,
+                    failures: 0,
+                    avg_duration_ms: 100,
+                    last_run: "2026-03-04T10:00:00Z".parse().unwrap(),
+                },
+                ScriptRollup {
+                    script_id: "deploy".to_string(),
+                    runs: 3,
+                    failures: 2,
+                    avg_duration_ms: 400,
+                    last_run: "2026-03-03T10:00:00Z".parse().unwrap(),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_filter_history_unknown_name_matches_nothing() {
+        let deploy = make_script("deploy");
+        let records = vec![make_record(&deploy.id, 0)];
+        let scripts = vec![deploy];
+
+        assert!(filter_history(&records, &scripts, Some("missing"), false).is_empty());
+    }
+
+    #[test]
+    fn test_interpreter_defaults_to_language() {
+        let interp = resolve_interpreter(&ScriptLanguage::Python, None);
+        assert_eq!(interp.program, PYTHON_INTERPRETER);
+        assert!(interp.args.is_empty());
+    }
+
+    #[test]
+    fn test_interpreter_override_replaces_language_default() {
+        let interp = resolve_interpreter(&ScriptLanguage::Bash, Some("dash -e"));
+        assert_eq!(interp.program, "dash");
+        assert_eq!(interp.args, vec!["-e".to_string()]);
+        assert_eq!(interp.command_line(), "dash -e");
+    }
+
+    #[test]
+    fn test_spawn_error_suggests_tmpdir_for_noexec() {
+        let interp = resolve_interpreter(&ScriptLanguage::Bash, None);
+        let path = Path::new("/tmp/scriptvault/run/x/script.sh");
+
+        for err in [
+            std::io::Error::from(std::io::ErrorKind::PermissionDenied),
+            std::io::Error::from_raw_os_error(ENOEXEC),
+        ] {
+            let msg = spawn_error(err, &interp, path).to_string();
+            assert!(
+                msg.starts_with("Failed to start bash for /tmp/scriptvault/run/x/script.sh: "),
+                "{msg}"
+            );
+            assert!(msg.contains("mounted noexec"), "{msg}");
+            assert!(msg.contains("set SCRIPTVAULT_TMPDIR"), "{msg}");
+        }
+
+        let other = std::io::Error::new(std::io::ErrorKind::OutOfMemory, "out of memory");
+        assert_eq!(
+            spawn_error(other, &interp, path).to_string(),
+            "out of memory"
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_spawn_reports_resource_usage() {
+        let interp = resolve_interpreter(&ScriptLanguage::Bash, None);
+        let scratch = ScratchDir::new("test");
+        let mut script = make_script("busy");
+        script.content = "i=0; while [ $i -lt 20000 ]; do i=$((i + 1)); done\n".to_string();
+        let script_path = scratch.script_path(&script);
+        let clock = RunClock::write_script(&script, &script_path).unwrap();
+
+        let result =
+            spawn_and_collect(&interp, &script_path, &[], None, Echo::Normal, clock, None).unwrap();
+        let usage = result.resource_usage.unwrap();
+        assert!(usage.max_rss_kb > 0, "{usage:?}");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_spawn_records_monotonic_phase_timings() {
+        let interp = resolve_interpreter(&ScriptLanguage::Bash, None);
+        let scratch = ScratchDir::new("test");
+        let mut script = make_script("phases");
+        script.content = "sleep 0.05\necho ready\nsleep 0.05\n".to_string();
+        let script_path = scratch.script_path(&script);
+        let clock = RunClock::write_script(&script, &script_path).unwrap();
+
+        let result =
+            spawn_and_collect(&interp, &script_path, &[], None, Echo::Normal, clock, None).unwrap();
+        let timings = result.timings.unwrap();
+        let first_output = timings.first_output_us.unwrap();
+        assert!(timings.write_us <= timings.spawn_us, "{timings:?}");
+        assert!(timings.spawn_us <= first_output, "{timings:?}");
+        assert!(first_output <= timings.total_us, "{timings:?}");
+        assert!(first_output >= timings.spawn_us + 40_000, "{timings:?}");
+        assert!(timings.total_us >= first_output + 40_000, "{timings:?}");
+
+        script.content = "true\n".to_string();
+        let clock = RunClock::write_script(&script, &script_path).unwrap();
+        let silent =
+            spawn_and_collect(&interp, &script_path, &[], None, Echo::Normal, clock, None).unwrap();
+        assert_eq!(silent.timings.unwrap().first_output_us, None);
+    }
+
+    #[test]
+    fn test_format_timings() {
+        let timings = PhaseTimings {
+            write_us: 400,
+            spawn_us: 2_100,
+            first_output_us: Some(15_000),
+            total_us: 1_250_000,
+        };
+        assert_eq!(
+            format_timings(&timings),
+            "Timing: script written 0.4ms, started 2.1ms, first output 15.0ms, finished 1.25s"
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_resource_usage_is_the_scripts_own() {
+        let interp = resolve_interpreter(&ScriptLanguage::Bash, None);
+        let run = |content: &str| {
+            let scratch = ScratchDir::new("test");
+            let mut script = make_script("usage");
+            script.content = content.to_string();
+            let script_path = scratch.script_path(&script);
+            let clock = RunClock::write_script(&script, &script_path).unwrap();
+            spawn_and_collect(&interp, &script_path, &[], None, Echo::Normal, clock, None)
+                .unwrap()
+                .resource_usage
+                .expect("usage on unix")
+        };
+
+        // Holds a 40 MB string in the shell.
+        let big = run("x=$(head -c 40000000 /dev/zero | tr '\\0' a)\n: \"${#x}\"\n");
+        let small = run("true\n");
+        assert!(big.max_rss_kb > 40_000, "{big:?}");
+        // A high-water mark over all children would repeat the big peak.
+        assert!(
+            small.max_rss_kb < big.max_rss_kb / 2,
+            "{small:?} vs {big:?}"
+        );
+    }
+
+    #[cfg(unix)]
+    fn run_with_timeout(content: &str, signal: KillSignal, grace_ms: u64) -> ExecutionResult {
+        let interp = resolve_interpreter(&ScriptLanguage::Bash, None);
+        let scratch = ScratchDir::new("test");
+        let mut script = make_script("slow");
+        script.content = content.to_string();
+        let script_path = scratch.script_path(&script);
+        let clock = RunClock::write_script(&script, &script_path).unwrap();
+
+        let timeout = Timeout {
+            after: std::time::Duration::from_millis(200),
+            signal,
+            grace: std::time::Duration::from_millis(grace_ms),
+        };
+        spawn_and_collect(
+            &interp,
+            &script_path,
+            &[],
+            None,
+            Echo::Normal,
+            clock,
+            Some(&timeout),
+        )
+        .unwrap()
+    }
+
+    #[cfg(unix)]
+    const TRAPS_TERM: &str = "trap 'sleep 0.3; echo cleaned up; exit 0' TERM\nsleep 30 &\nwait\n";
+
+    #[cfg(unix)]
+    #[test]
+    fn test_timeout_term_gives_grace_period() {
+        let start = Instant::now();
+        let result = run_with_timeout(TRAPS_TERM, KillSignal::Term, 5000);
+        assert_eq!(result.exit_code, TIMED_OUT_EXIT_CODE);
+        assert_eq!(result.output.as_deref(), Some("cleaned up\n"));
+        assert!(start.elapsed() < std::time::Duration::from_secs(5));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_timeout_kills_after_grace_or_with_kill_signal() {
+        let start = Instant::now();
+        let short_grace = run_with_timeout(TRAPS_TERM, KillSignal::Term, 50);
+        assert_eq!(short_grace.exit_code, TIMED_OUT_EXIT_CODE);
+        assert_eq!(short_grace.output, None);
+
+        let killed = run_with_timeout(TRAPS_TERM, KillSignal::Kill, 5000);
+        assert_eq!(killed.exit_code, TIMED_OUT_EXIT_CODE);
+        assert_eq!(killed.output, None);
+        assert!(start.elapsed() < std::time::Duration::from_secs(5));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_timeout_does_not_affect_fast_scripts() {
+        let result = run_with_timeout("exit 3\n", KillSignal::Term, 5000);
+        assert_eq!(result.exit_code, 3);
+    }
+
+    #[test]
+    fn test_format_resource_usage() {
+        let usage = ResourceUsage {
+            max_rss_kb: 1536,
+            user_cpu_ms: 1250,
+            sys_cpu_ms: 40,
+        };
+        assert_eq!(
+            format_resource_usage(&usage),
+            "Resources: max RSS 1.5 MB, user CPU 1.25s, sys CPU 0.04s"
+        );
+    }
+
+    #[test]
+    fn test_missing_interpreter_spawn_error() {
+        let interp = resolve_interpreter(&ScriptLanguage::Bash, Some("sv-no-such-interpreter"));
+        let scratch = ScratchDir::new("test");
+        let script = make_script("deploy");
+        let script_path = scratch.script_path(&script);
+        let clock = RunClock::write_script(&script, &script_path).unwrap();
+
+        let err = spawn_and_collect(&interp, &script_path, &[], None, Echo::Normal, clock, None)
+            .err()
+            .unwrap();
+        assert!(err.is::<InterpreterNotFound>());
+        assert_eq!(
+            err.to_string(),
+            "Required interpreter 'sv-no-such-interpreter' not found in PATH. Install it before \
+             running this script, or run 'sv doctor' to see which interpreters are missing."
+        );
+    }
+
+    #[test]
+    fn test_parse_args_file_multiline_with_quotes() {
+        let text = "--name 'Alice Smith'\n# a comment line\n--greeting \"Hello, \\\"world\\\"\"\n\n  plain\\ space  ''  x#y \\\n  last # trailing comment\n";
+        assert_eq!(
+            parse_args_file(text).unwrap(),
+            [
+                "--name",
+                "Alice Smith",
+                "--greeting",
+                "Hello, \"world\"",
+                "plain space",
+                "",
+                "x#y",
+                "last",
+            ]
+        );
+        assert!(parse_args_file("").unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_parse_args_file_unterminated_quote() {
+        let err = parse_args_file("ok 'never closed\n").unwrap_err();
+        assert_eq!(err.to_string(), "unterminated single quote");
+        assert!(parse_args_file("\"open").is_err());
+    }
+
+    #[test]
+    fn test_format_phase() {
+        use std::time::Duration;
+        assert_eq!(format_phase(Duration::from_micros(1250)), "1.2ms");
+        assert_eq!(format_phase(Duration::from_millis(999)), "999.0ms");
+        assert_eq!(format_phase(Duration::from_millis(2500)), "2.50s");
+    }
+
+    #[test]
+    fn test_continue_from_slices_lines() {
+        let mut script = make_script("deploy");
+        script.content = "#!/bin/bash\nstep one\nstep two\nstep three\n".to_string();
+
+        let resumed = continue_from(&script, 3).unwrap();
+        assert_eq!(resumed.content, "#!/bin/bash\nstep two\nstep three\n");
+        assert_eq!(continue_from(&script, 1).unwrap().content, script.content);
+        assert_eq!(
+            continue_from(&script, 4).unwrap().content,
+            "#!/bin/bash\nstep three\n"
+        );
+
+        script.content = "one\ntwo".to_string();
+        assert_eq!(continue_from(&script, 2).unwrap().content, "two");
+    }
+
+    #[test]
+    fn test_continue_from_rejects_out_of_range_and_other_languages() {
+        let mut script = make_script("deploy");
+        script.content = "one\ntwo\n".to_string();
+
+        let err = continue_from(&script, 3).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Line 3 is out of range: deploy has 2 lines"
+        );
+        assert!(continue_from(&script, 0).is_err());
+
+        script.language = ScriptLanguage::Python;
+        assert!(
+            continue_from(&script, 1)
+                .unwrap_err()
+                .to_string()
+                .contains("only works for bash and shell")
+        );
+    }
+
+    #[test]
+    fn test_with_shebang_adds_missing_line() {
+        let script = make_script("deploy");
+        assert_eq!(
+            with_shebang(&script, None).unwrap(),
+            "#!/usr/bin/env bash\necho test"
+        );
+        assert_eq!(
+            with_shebang(&script, Some("dash")).unwrap(),
+            "#!/usr/bin/env dash\necho test"
+        );
+        assert_eq!(
+            with_shebang(&script, Some("/bin/zsh")).unwrap(),
+            "#!/bin/zsh\necho test"
+        );
+    }
+
+    #[test]
+    fn test_with_shebang_keeps_existing_line() {
+        let mut script = make_script("deploy");
+        script.content = "#!/bin/sh\necho test\n".to_string();
+        assert_eq!(with_shebang(&script, None).unwrap(), script.content);
+        assert_eq!(with_shebang(&script, Some("dash")).unwrap(), script.content);
+
+        script.content = "Write-Host hi".to_string();
+        script.language = ScriptLanguage::PowerShell;
+        assert!(with_shebang(&script, None).is_err());
+        assert_eq!(
+            with_shebang(&script, Some("pwsh")).unwrap(),
+            "#!/usr/bin/env pwsh\nWrite-Host hi"
+        );
+    }
+
+    #[test]
+    fn test_minimal_env_passes_only_path_and_allowed() {
+        let parent: HashMap<&str, &str> = [
+            ("PATH", "/usr/bin"),
+            ("HOME", "/home/user"),
+            ("AWS_SECRET_ACCESS_KEY", "hunter2"),
+            ("DEPLOY_ENV", "staging"),
+        ]
+        .into_iter()
+        .collect();
+        let lookup = |key: &str| parent.get(key).map(|v| v.to_string());
+
+        let env = build_minimal_env(&[], lookup);
+        assert_eq!(env.len(), 1);
+        assert_eq!(env["PATH"], "/usr/bin");
+
+        let allow = vec!["DEPLOY_ENV".to_string(), "UNSET".to_string()];
+        let env = build_minimal_env(&allow, lookup);
+        let mut keys: Vec<&str> = env.keys().map(String::as_str).collect();
+        keys.sort_unstable();
+        assert_eq!(keys, ["DEPLOY_ENV", "PATH"]);
+    }
+
+    #[test]
+    fn test_sandboxed_minimal_env_keeps_sandbox_home() {
+        let dir = Path::new("/tmp/scriptvault/isolated/x");
+        let env = script_env(&make_script("x"), Some(dir), Some(&[]));
+        assert_eq!(env["HOME"], "/tmp/scriptvault/isolated/x");
+        assert_eq!(env["ISOLATED"], "1");
+        assert!(!env.contains_key("TERM"));
+        assert!(!env.contains_key("LANG"));
+    }
+
+    #[test]
+    fn test_script_env_defaults_applied_last() {
+        let mut script = make_script("deploy");
+        script.env.insert("DEPLOY_ENV".into(), "staging".into());
+        script.env.insert("HOME".into(), "/srv/deploy".into());
+
+        let dir = Path::new("/tmp/scriptvault/isolated/y");
+        let env = script_env(&script, Some(dir), None);
+        assert_eq!(env["DEPLOY_ENV"], "staging");
+        assert_eq!(env["HOME"], "/srv/deploy");
+        assert_eq!(env["ISOLATED"], "1");
+
+        let env = script_env(&script, None, Some(&[]));
+        assert_eq!(env["DEPLOY_ENV"], "staging");
+    }
+
+    #[test]
+    fn test_path_dirs_validates_and_orders() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let bin = dir.path().join("bin");
+        let tools = dir.path().join("tools");
+        fs::create_dir(&bin).unwrap();
+        fs::create_dir(&tools).unwrap();
+        let s = |p: &Path| p.to_string_lossy().into_owned();
+        let missing = s(&dir.path().join("missing"));
+
+        let dirs = path_dirs(&[s(&tools)], &[s(&bin), missing.clone(), s(&tools)]).unwrap();
+        assert_eq!(
+            dirs,
+            [tools.canonicalize().unwrap(), bin.canonicalize().unwrap()]
+        );
+
+        let err = path_dirs(std::slice::from_ref(&missing), &[]).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            format!("--add-path {}: no such directory", missing)
+        );
+        let file = dir.path().join("file");
+        fs::write(&file, "").unwrap();
+        assert!(path_dirs(&[s(&file)], &[]).is_err());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_prepend_path() {
+        let dirs = [
+            PathBuf::from("/repo/bin"),
+            PathBuf::from("/repo/node_modules/.bin"),
+        ];
+        assert_eq!(
+            prepend_path(&dirs, Some("/usr/bin:/bin")).unwrap(),
+            "/repo/bin:/repo/node_modules/.bin:/usr/bin:/bin"
+        );
+        assert_eq!(prepend_path(&dirs[..1], None).unwrap(), "/repo/bin");
+    }
+
+    #[test]
+    fn test_completion_message_follows_success_codes() {
+        let took = std::time::Duration::from_millis(1500);
+        assert_eq!(completion_message(0, true, took), "Completed in 1.50s");
+        assert_eq!(
+            completion_message(1, true, took),
+            "Completed with exit code 1 (counted as success) in 1.50s"
+        );
+        // success_exit_codes = [1]: a clean exit is the unexpected outcome.
+        assert_eq!(
+            completion_message(0, false, took),
+            "Failed: exit code 0 is not one of the script's success codes (1.50s)"
+        );
+        assert_eq!(
+            completion_message(2, false, took),
+            "Failed with exit code 2 in 1.50s"
+        );
+    }
+
+    #[test]
+    fn test_remote_script_call_uses_interpreter() {
+        let args = vec!["--env".to_string(), "prod".to_string()];
+        assert_eq!(
+            remote_script_call("/tmp/sv_x.sh", None, &args),
+            "/tmp/sv_x.sh --env prod"
+        );
+        assert_eq!(
+            remote_script_call("/tmp/sv_x.txt", Some("python3 -u"), &[]),
+            "python3 -u /tmp/sv_x.txt"
+        );
+    }
+
+    #[test]
+    fn test_dry_run_plan_lists_interpreter_and_args() {
+        let interp = resolve_interpreter(&ScriptLanguage::Bash, None);
+        let mut env = HashMap::new();
+        env.insert("PATH".to_string(), "/usr/bin".to_string());
+        env.insert("HOME".to_string(), "/home/test".to_string());
+
+        let plan = format_dry_run_plan(
+            &interp,
+            Path::new("/tmp/scriptvault/abc.sh"),
+            &["--name".to_string(), "Alice".to_string()],
+            Some(Path::new("/home/test/project")),
+            &env,
+        );
+
+        assert!(plan.contains("Interpreter:   bash"));
+        assert!(plan.contains("bash /tmp/scriptvault/abc.sh --name Alice"));
+        assert!(plan.contains("Working dir:   /home/test/project"));
+        assert!(plan.contains("Temp script:   /tmp/scriptvault/abc.sh"));
+        assert!(plan.contains("Env keys:      HOME, PATH"));
+    }
+
+    #[test]
+    fn test_interpreter_override_used_in_spawned_command() {
+        let interp = resolve_interpreter(&ScriptLanguage::Shell, Some("dash"));
+        let cmd = build_command(
+            &interp,
+            Path::new("/tmp/script.sh"),
+            &["one".to_string()],
+            None,
+        );
+        assert_eq!(cmd.get_program(), "dash");
+        let args: Vec<_> = cmd.get_args().collect();
+        assert_eq!(args, vec!["/tmp/script.sh", "one"]);
+    }
+
+    #[test]
+    fn test_truncate_output_at_limit() {
+        let exact = "a".repeat(16);
+        assert_eq!(truncate_output(exact.clone(), 16), exact);
+
+        let long = "a".repeat(20);
+        assert_eq!(
+            truncate_output(long.clone(), 16),
+            format!("{}... [truncated 4 bytes]", "a".repeat(16))
+        );
+        assert_eq!(truncate_output(long.clone(), 0), long);
+    }
+
+    #[test]
+    fn test_truncate_output_respects_char_boundaries() {
+        // "é" is two bytes; a limit of 2 falls inside the second one.
+        let truncated = truncate_output("aéb".to_string(), 2);
+        assert_eq!(truncated, "a... [truncated 3 bytes]");
+    }
+
+    #[test]
+    fn test_temp_script_gets_missing_shebang() {
+        let script = make_script("bare");
+        let scratch = ScratchDir::new("run");
+        let script_path = scratch.script_path(&script);
+        write_temp_script(&script, &script_path).unwrap();
+        assert_eq!(
+            fs::read_to_string(&script_path).unwrap(),
+            "#!/usr/bin/env bash\necho test"
+        );
+        assert_eq!(script.content, "echo test");
+
+        let mut with_own = make_script("own");
+        with_own.content = "#!/bin/dash\necho test\n".to_string();
+        write_temp_script(&with_own, &script_path).unwrap();
+        assert_eq!(fs::read_to_string(&script_path).unwrap(), with_own.content);
+
+        let mut batch = make_script("win");
+        batch.language = ScriptLanguage::Batch;
+        write_temp_script(&batch, &script_path).unwrap();
+        assert_eq!(fs::read_to_string(&script_path).unwrap(), "echo test");
+    }
+
+    #[test]
+    fn test_scratch_dir_removed_on_drop() {
+        let script = make_script("cleanup");
+        let scratch = ScratchDir::new("run");
+        let script_path = scratch.script_path(&script);
+        write_temp_script(&script, &script_path).unwrap();
+        assert!(script_path.exists());
+
+        let dir = scratch.path.clone();
+        drop(scratch);
+        assert!(!dir.exists());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_concurrent_runs_do_not_collide() {
+        let script = Script::new(
+            "concurrent".to_string(),
+            "sleep 0.2\necho \"$0\"\n".to_string(),
+            ScriptLanguage::Shell,
+        );
+
+        let handles: Vec<_> = (0..2)
+            .map(|_| {
+                let script = script.clone();
+                let interp = resolve_interpreter(&script.language, None);
+                std::thread::spawn(move || {
+                    execute_script_safe_env(&script, &interp, &[], None, Echo::Normal, None)
+                        .unwrap()
+                })
+            })
+            .collect();
+        let mut paths: Vec<_> = handles
+            .into_iter()
+            .map(|h| {
+                let result = h.join().unwrap();
+                assert_eq!(result.exit_code, 0);
+                PathBuf::from(result.output.unwrap().trim())
+            })
+            .collect();
+        paths.dedup();
+
+        assert_eq!(paths.len(), 2);
+        for path in &paths {
+            assert!(!path.parent().unwrap().exists());
+        }
+    }
+
+    fn date(s: &str) -> NaiveDate {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn test_trend_daily_buckets_fill_gaps() {
+        let records = [
+            make_record_at("a", 0, "2026-03-02T09:00:00Z"),
+            make_record_at("a", 1, "2026-03-02T17:30:00Z"),
+            make_record_at("a", 0, "2026-03-04T08:00:00Z"),
+        ];
+        let refs: Vec<&ExecutionRecord> = records.iter().collect();
+
+        let points = compute_trend(&refs, TrendBucket::Daily);
+        assert_eq!(points.len(), 3);
+        assert_eq!(points[0].start, date("2026-03-02"));
+        assert_eq!((points[0].runs, points[0].successes), (2, 1));
+        assert_eq!(points[0].success_rate(), Some(50.0));
+        assert_eq!(points[1].start, date("2026-03-03"));
+        assert_eq!(points[1].runs, 0);
+        assert_eq!(points[1].success_rate(), None);
+        assert_eq!((points[2].runs, points[2].successes), (1, 1));
+    }
+
+    #[test]
+    fn test_trend_weekly_buckets_start_on_monday() {
+        let records = [
+            make_record_at("a", 0, "2026-03-03T09:00:00Z"),
+            make_record_at("a", 0, "2026-03-08T09:00:00Z"),
+            make_record_at("a", 2, "2026-03-09T09:00:00Z"),
+        ];
+        let refs: Vec<&ExecutionRecord> = records.iter().collect();
+
+        let points = compute_trend(&refs, TrendBucket::Weekly);
+        assert_eq!(points.len(), 2);
+        assert_eq!(points[0].start, date("2026-03-02"));
+        assert_eq!((points[0].runs, points[0].successes), (2, 2));
+        assert_eq!(points[1].start, date("2026-03-09"));
+        assert_eq!((points[1].runs, points[1].successes), (1, 0));
+    }
+
+    #[test]
+    fn test_trend_empty_history() {
+        assert!(compute_trend(&[], TrendBucket::Daily).is_empty());
+    }
+
+    #[test]
+    fn test_trend_bucket_parse() {
+        assert_eq!(TrendBucket::parse("Weekly").unwrap(), TrendBucket::Weekly);
+        assert_eq!(TrendBucket::parse("daily").unwrap(), TrendBucket::Daily);
+        assert!(TrendBucket::parse("monthly").is_err());
+    }
+
+    #[test]
+    fn test_sparkline_scales_and_marks_gaps() {
+        assert_eq!(sparkline(&[Some(0.0), None, Some(100.0)], 100.0), "▁ █");
+    }
+
+    fn param(name: &str, default: Option<&str>) -> ScriptParam {
+        ScriptParam {
+            name: name.to_string(),
+            description: None,
+            default: default.map(str::to_string),
+            required: default.is_none(),
+        }
+    }
+
+    fn no_prompt(p: &ScriptParam) -> Result<String> {
+        panic!("unexpected prompt for {}", p.name)
+    }
+
+    #[test]
+    fn test_params_prompt_for_missing_required() {
+        let params = [param("env", None), param("region", Some("eu-west-1"))];
+        let mut asked = Vec::new();
+        let resolved = resolve_params(&params, &[], false, false, |p| {
+            asked.push(p.name.clone());
+            Ok("prod".to_string())
+        })
+        .unwrap();
+        assert_eq!(asked, ["env"]);
+        assert_eq!(resolved, ["prod", "eu-west-1"]);
+    }
+
+    #[test]
+    fn test_params_prompt_all_includes_optional() {
+        let params = [param("env", None), param("region", Some("eu-west-1"))];
+        let resolved = resolve_params(&params, &[], false, true, |p| {
+            Ok(format!("{}-value", p.name))
+        })
+        .unwrap();
+        assert_eq!(resolved, ["env-value", "region-value"]);
+    }
+
+    #[test]
+    fn test_params_positional_args_skip_prompts() {
+        let params = [param("env", None), param("region", Some("eu-west-1"))];
+        let supplied = [
+            "staging".to_string(),
+            "us-east-1".to_string(),
+            "--force".to_string(),
+        ];
+        let resolved = resolve_params(&params, &supplied, false, true, no_prompt).unwrap();
+        assert_eq!(resolved, ["staging", "us-east-1", "--force"]);
+    }
+
+    #[test]
+    fn test_params_ci_mode_requires_params_up_front() {
+        let params = [
+            param("env", None),
+            param("tag", None),
+            param("region", Some("eu")),
+        ];
+        let err = resolve_params(&params, &[], true, true, no_prompt).unwrap_err();
+        assert!(err.to_string().contains("env, tag"));
+
+        let supplied = ["prod".to_string(), "v2".to_string()];
+        let resolved = resolve_params(&params, &supplied, true, true, no_prompt).unwrap();
+        assert_eq!(resolved, ["prod", "v2", "eu"]);
+    }
+
+    #[test]
+    fn test_safety_explanation_lists_matched_pattern() {
+        let script = Script::new(
+            "wipe".to_string(),
+            "#!/bin/sh\necho bye\nrm -rf /tmp/cache\nrm -rf /\n".to_string(),
+            ScriptLanguage::Shell,
+        );
+        let explanation = format_safety_explanation(&script);
+
+        assert!(explanation.contains("rm -rf /"));
+        assert!(explanation.contains("line 3, 4"));
+        assert!(explanation.contains("Flagged as dangerous"));
+        assert!(explanation.contains(&script.metadata.hash));
+        assert!(explanation.contains(&format!("{} bytes", script.metadata.size_bytes)));
+    }
+
+    #[test]
+    fn test_safety_explanation_for_safe_script() {
+        let script = make_script("hello");
+        let explanation = format_safety_explanation(&script);
+        assert!(explanation.contains("none"));
+        assert!(explanation.contains("Not flagged"));
+        assert!(!explanation.contains("Flagged as dangerous"));
+    }
+
+    fn script_owned_by(author: &str, visibility: Visibility) -> Script {
+        let mut script = make_script("deploy");
+        script.author = author.to_string();
+        script.visibility = visibility;
+        script
+    }
+
+    #[test]
+    fn test_public_scripts_run_for_anyone() {
+        let script = script_owned_by("alice", Visibility::Public);
+        assert!(check_run_permission(&script, "alice", &[]).is_ok());
+        assert!(check_run_permission(&script, "bob", &[]).is_ok());
+    }
+
+    #[test]
+    fn test_team_scripts_require_membership() {
+        let script = script_owned_by("alice", Visibility::Team);
+        let team = ["bob".to_string(), "carol".to_string()];
+        assert!(check_run_permission(&script, "bob", &team).is_ok());
+        assert!(check_run_permission(&script, "alice", &team).is_err());
+
+        let err = check_run_permission(&script, "mallory", &team).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Permission denied: mallory may not run deploy, which is a team script and they are not in team_members."
+        );
+        assert!(check_run_permission(&script, "bob", &[]).is_err());
+    }
+
+    #[test]
+    fn test_private_scripts_require_author() {
+        let script = script_owned_by("alice", Visibility::Private);
+        let team = ["bob".to_string()];
+        assert!(check_run_permission(&script, "alice", &[]).is_ok());
+
+        let err = check_run_permission(&script, "bob", &team).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Permission denied: bob may not run deploy, which is private to alice."
+        );
+    }
+
+    #[test]
+    fn test_trusted_script_skips_confirmation() {
+        assert!(!needs_confirmation(true, false, true, false));
+        assert!(needs_confirmation(false, false, true, false));
+    }
+
+    #[test]
+    fn test_confirm_flag_overrides_trust() {
+        assert!(needs_confirmation(true, true, false, false));
+        assert!(needs_confirmation(true, true, true, true));
+    }
+
+    #[test]
+    fn test_ci_mode_and_setting_skip_confirmation() {
+        assert!(!needs_confirmation(false, false, true, true));
+        assert!(!needs_confirmation(false, false, false, false));
+    }
+
+    #[test]
+    fn test_relay_output_flags_invalid_utf8() {
+        let mut sink = Vec::new();
+        let (captured, lossy) = relay_output(&b"ok\n"[..], &mut sink, &OnceLock::new());
+        assert_eq!(captured, "ok\n");
+        assert!(!lossy);
+
+        let input = b"caf\xe9\nnext line\n";
+        sink.clear();
+        let (captured, lossy) = relay_output(&input[..], &mut sink, &OnceLock::new());
+        assert_eq!(captured, "caf\u{FFFD}\nnext line\n");
+        assert!(lossy);
+        assert_eq!(sink, input);
+    }
+
+    fn write_history(path: &Path, records: &[&ExecutionRecord]) {
+        let lines: Vec<String> = records
+            .iter()
+            .map(|r| serde_json::to_string(r).unwrap() + "\n")
+            .collect();
+        fs::write(path, lines.concat()).unwrap();
+    }
+
+    #[test]
+    fn test_history_follower_surfaces_appended_records() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("history.jsonl");
+        let mut follower = HistoryFollower::new(path.clone());
+        assert!(follower.poll().unwrap().is_empty());
+
+        let a = make_record("a", 0);
+        write_history(&path, &[&a]);
+        let first: Vec<String> = follower.poll().unwrap().into_iter().map(|r| r.id).collect();
+        assert_eq!(first, [a.id]);
+        assert!(follower.poll().unwrap().is_empty());
+
+        let b = make_record("b", 1);
+        let mut file = OpenOptions::new().append(true).open(&path).unwrap();
+        writeln!(file, "{}", serde_json::to_string(&b).unwrap()).unwrap();
+        // A half-written line is left for the next poll.
+        write!(file, "{{\"id\":").unwrap();
+        let new = follower.poll().unwrap();
+        assert_eq!(new.len(), 1);
+        assert_eq!(new[0].id, b.id);
+        assert_eq!(new[0].exit_code, 1);
+    }
+
+    #[test]
+    fn test_history_follower_handles_rotation_and_truncation() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("history.jsonl");
+        let (a, b) = (make_record("a", 0), make_record("b", 0));
+        write_history(&path, &[&a, &b]);
+        let mut follower = HistoryFollower::new(path.clone());
+        assert_eq!(follower.poll().unwrap().len(), 2);
+
+        // Rotation drops the oldest record while a new one is appended.
+        let c = make_record("rotated-in", 2);
+        write_history(&path, &[&b, &c]);
+        let new = follower.poll().unwrap();
+        assert_eq!(new.len(), 1);
+        assert_eq!(new[0].id, c.id);
+
+        // After truncation everything in the file is new.
+        fs::write(&path, "").unwrap();
+        assert!(follower.poll().unwrap().is_empty());
+        let d = make_record("d", 0);
+        write_history(&path, &[&d]);
+        let new = follower.poll().unwrap();
+        assert_eq!(new.len(), 1);
+        assert_eq!(new[0].id, d.id);
+    }
+}
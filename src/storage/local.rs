@@ -1,18 +1,31 @@
-use super::{ListOptions, StorageBackend, StorageMetadata};
+use super::{ListOptions, StorageBackend, StorageMetadata, page_summaries};
+use crate::constants::SCRIPTS_FILE;
 use crate::script::{Script, ScriptSummary, SyncState, SyncStatus};
 use anyhow::{Context, Result, anyhow};
 use chrono::{DateTime, Utc};
+use std::collections::HashMap;
 use std::fs;
 use std::path::{Path, PathBuf};
+use std::sync::{LazyLock, Mutex, MutexGuard};
+
+type ScriptCache = HashMap<PathBuf, HashMap<String, Script>>;
+
+/// Every indexed script keyed by id, per vault directory. Shared by all
+/// `LocalStorage` instances in the process, since commands and their helpers
+/// each open the backend, and kept current by writes through any of them.
+static CACHE: LazyLock<Mutex<ScriptCache>> = LazyLock::new(Default::default);
 
 pub struct LocalStorage {
     vault_path: PathBuf,
     index_path: PathBuf,
+    #[cfg(test)]
+    disk_reads: std::sync::atomic::AtomicUsize,
 }
 
+/// Script name to id. The HTTP backend keeps the same file remotely.
 #[derive(serde::Serialize, serde::Deserialize, Default)]
-struct Index {
-    entries: std::collections::HashMap<String, String>,
+pub(super) struct Index {
+    pub(super) entries: std::collections::HashMap<String, String>,
 }
 
 impl Index {
@@ -29,34 +42,141 @@ impl Index {
         fs::write(&tmp, serde_json::to_string_pretty(self)?)?;
         fs::rename(&tmp, path).context("failed to replace index")
     }
+
+    /// Whether `id` has exactly one entry, under `name`. Updates rewrite the
+    /// index otherwise, which also drops stale names left for the script.
+    pub(super) fn lists_only(&self, name: &str, id: &str) -> bool {
+        self.entries.get(name).is_some_and(|v| v == id)
+            && self.entries.values().filter(|v| *v == id).count() == 1
+    }
+}
+
+/// Older vaults kept every script in one `scripts.json`, either as an array
+/// or as an object keyed by id.
+#[derive(serde::Deserialize)]
+#[serde(untagged)]
+enum LegacyScripts {
+    List(Vec<Script>),
+    Map(std::collections::HashMap<String, Script>),
+}
+
+impl LegacyScripts {
+    fn into_scripts(self) -> Vec<Script> {
+        match self {
+            Self::List(scripts) => scripts,
+            Self::Map(map) => map.into_values().collect(),
+        }
+    }
 }
 
 impl LocalStorage {
     pub fn new(vault_path: PathBuf) -> Result<Self> {
         fs::create_dir_all(&vault_path).context("failed to create vault directory")?;
         let index_path = vault_path.join("index.json");
-        Ok(Self {
+        let storage = Self {
             vault_path,
             index_path,
-        })
+            #[cfg(test)]
+            disk_reads: Default::default(),
+        };
+        storage.migrate_legacy_file()?;
+        Ok(storage)
+    }
+
+    /// Splits a legacy `scripts.json` into per-script files and renames it to
+    /// `scripts.json.migrated` so this only runs once. Scripts already present
+    /// by id are left alone.
+    fn migrate_legacy_file(&self) -> Result<()> {
+        let legacy_path = self.vault_path.join(SCRIPTS_FILE);
+        if !legacy_path.exists() {
+            return Ok(());
+        }
+
+        let raw = fs::read_to_string(&legacy_path).context("failed to read legacy scripts.json")?;
+        let legacy: LegacyScripts =
+            serde_json::from_str(&raw).context("failed to parse legacy scripts.json")?;
+
+        let mut idx = Index::load(&self.index_path)?;
+        let mut migrated = 0;
+        for script in legacy.into_scripts() {
+            if self.script_path(&script.id).exists() {
+                continue;
+            }
+            self.write_script(&script)?;
+            idx.entries.insert(script.name.clone(), script.id.clone());
+            migrated += 1;
+        }
+        idx.save(&self.index_path)?;
+
+        fs::rename(&legacy_path, legacy_path.with_extension("json.migrated"))
+            .context("failed to retire legacy scripts.json")?;
+        tracing::info!(migrated, "migrated legacy scripts.json to per-script files");
+        Ok(())
     }
 
     fn script_path(&self, id: &str) -> PathBuf {
         self.vault_path.join(format!("{}.json", id))
     }
 
-    fn read_script(&self, id: &str) -> Result<Script> {
+    fn read_script_from_disk(&self, id: &str) -> Result<Script> {
+        #[cfg(test)]
+        self.disk_reads
+            .fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+        tracing::trace!(id, "parsing script file");
         let path = self.script_path(id);
         let raw =
             fs::read_to_string(&path).with_context(|| format!("script file not found: {}", id))?;
         serde_json::from_str(&raw).context("failed to parse script file")
     }
 
+    fn read_script(&self, id: &str) -> Result<Script> {
+        if let Some(script) = self
+            .lock_cache()
+            .get(&self.vault_path)
+            .and_then(|c| c.get(id))
+        {
+            return Ok(script.clone());
+        }
+        self.read_script_from_disk(id)
+    }
+
+    fn lock_cache(&self) -> MutexGuard<'static, ScriptCache> {
+        CACHE.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Applies `f` to this vault's cached scripts, if they have been loaded.
+    fn update_cache(&self, f: impl FnOnce(&mut HashMap<String, Script>)) {
+        if let Some(scripts) = self.lock_cache().get_mut(&self.vault_path) {
+            f(scripts);
+        }
+    }
+
+    /// Runs `f` over every indexed script, parsing them from disk only the
+    /// first time in this process.
+    fn with_all_scripts<T>(&self, f: impl FnOnce(&HashMap<String, Script>) -> T) -> Result<T> {
+        let mut cache = self.lock_cache();
+        if !cache.contains_key(&self.vault_path) {
+            let idx = Index::load(&self.index_path)?;
+            let scripts = idx
+                .entries
+                .values()
+                .filter_map(|id| self.read_script_from_disk(id).ok())
+                .map(|s| (s.id.clone(), s))
+                .collect();
+            cache.insert(self.vault_path.clone(), scripts);
+        }
+        Ok(f(&cache[&self.vault_path]))
+    }
+
     fn write_script(&self, script: &Script) -> Result<()> {
         let path = self.script_path(&script.id);
         let tmp = path.with_extension("tmp");
         fs::write(&tmp, serde_json::to_string_pretty(script)?)?;
-        fs::rename(&tmp, &path).context("failed to atomically write script")
+        fs::rename(&tmp, &path).context("failed to atomically write script")?;
+        self.update_cache(|scripts| {
+            scripts.insert(script.id.clone(), script.clone());
+        });
+        Ok(())
     }
 
     fn index_add(&self, name: &str, id: &str) -> Result<()> {
@@ -88,11 +208,14 @@ impl LocalStorage {
 
 impl StorageBackend for LocalStorage {
     fn save_script(&self, script: &Script) -> Result<()> {
-        if let Ok(existing_id) = self.id_for_name(&script.name) {
-            if existing_id != script.id {
-                let old_path = self.script_path(&existing_id);
-                let _ = fs::remove_file(old_path);
-            }
+        if let Ok(existing_id) = self.id_for_name(&script.name)
+            && existing_id != script.id
+        {
+            let old_path = self.script_path(&existing_id);
+            let _ = fs::remove_file(old_path);
+            self.update_cache(|scripts| {
+                scripts.remove(&existing_id);
+            });
         }
         self.write_script(script)?;
         self.index_add(&script.name, &script.id)
@@ -102,10 +225,9 @@ impl StorageBackend for LocalStorage {
         if !self.script_path(&script.id).exists() {
             return Err(anyhow!("script not found: {}", script.id));
         }
-        let old = self.read_script(&script.id)?;
-        if old.name != script.name {
-            let mut idx = Index::load(&self.index_path)?;
-            idx.entries.remove(&old.name);
+        let mut idx = Index::load(&self.index_path)?;
+        if !idx.lists_only(&script.name, &script.id) {
+            idx.entries.retain(|_, v| *v != script.id);
             idx.entries.insert(script.name.clone(), script.id.clone());
             idx.save(&self.index_path)?;
         }
@@ -122,32 +244,18 @@ impl StorageBackend for LocalStorage {
     }
 
     fn list_scripts(&self) -> Result<Vec<Script>> {
-        let idx = Index::load(&self.index_path)?;
-        let mut scripts = Vec::with_capacity(idx.entries.len());
-        for id in idx.entries.values() {
-            if let Ok(s) = self.read_script(id) {
-                scripts.push(s);
-            }
-        }
-        Ok(scripts)
+        self.with_all_scripts(|scripts| scripts.values().cloned().collect())
     }
 
     fn list_summaries(&self, opts: &ListOptions) -> Result<Vec<ScriptSummary>> {
-        let idx = Index::load(&self.index_path)?;
-        let mut summaries: Vec<ScriptSummary> = idx
-            .entries
-            .values()
-            .filter_map(|id| self.read_script(id).ok())
-            .map(|s| ScriptSummary::from(&s))
-            .collect();
-
-        summaries.sort_by(|a, b| b.updated_at.cmp(&a.updated_at));
-
-        Ok(summaries
-            .into_iter()
-            .skip(opts.offset)
-            .take(opts.limit)
-            .collect())
+        let summaries: Vec<ScriptSummary> = self.with_all_scripts(|scripts| {
+            scripts
+                .values()
+                .filter(|s| opts.include_archived || !s.archived)
+                .map(ScriptSummary::from)
+                .collect()
+        })?;
+        Ok(page_summaries(summaries, opts))
     }
 
     fn delete_script(&self, id: &str) -> Result<()> {
@@ -156,6 +264,9 @@ impl StorageBackend for LocalStorage {
             return Err(anyhow!("script not found: {}", id));
         }
         fs::remove_file(&path).context("failed to delete script file")?;
+        self.update_cache(|scripts| {
+            scripts.remove(id);
+        });
         self.index_remove_by_id(id)
     }
 
@@ -251,6 +362,9 @@ mod tests {
                 git_repo: None,
                 git_branch: None,
                 environment: HashMap::new(),
+                os: None,
+                arch: None,
+                hostname: None,
             },
             metadata: ScriptMetadata {
                 hash: "testhash".to_string(),
@@ -265,6 +379,15 @@ mod tests {
             },
             visibility: Visibility::Private,
             sync_state: SyncState::default(),
+            archived: false,
+            pinned: false,
+            trusted: false,
+            safety_acknowledged: false,
+            pre_run: None,
+            post_run: None,
+            params: Vec::new(),
+            env: HashMap::new(),
+            success_exit_codes: vec![0],
         }
     }
 
@@ -370,18 +493,21 @@ mod tests {
             .list_summaries(&ListOptions {
                 limit: 4,
                 offset: 0,
+                ..Default::default()
             })
             .unwrap();
         let page2 = s
             .list_summaries(&ListOptions {
                 limit: 4,
                 offset: 4,
+                ..Default::default()
             })
             .unwrap();
         let page3 = s
             .list_summaries(&ListOptions {
                 limit: 4,
                 offset: 8,
+                ..Default::default()
             })
             .unwrap();
         assert_eq!(page1.len(), 4);
@@ -392,6 +518,154 @@ mod tests {
         assert_eq!(unique.len(), 10);
     }
 
+    #[test]
+    fn test_save_only_writes_target_file() {
+        let tmp = TempDir::new().unwrap();
+        let s = storage(&tmp);
+        let other = make_script("other");
+        s.save_script(&other).unwrap();
+        let other_path = tmp.path().join(format!("{}.json", other.id));
+        let before = fs::metadata(&other_path).unwrap().modified().unwrap();
+        let before_raw = fs::read(&other_path).unwrap();
+
+        std::thread::sleep(std::time::Duration::from_millis(20));
+        let target = make_script("target");
+        s.save_script(&target).unwrap();
+
+        assert_eq!(
+            fs::metadata(&other_path).unwrap().modified().unwrap(),
+            before
+        );
+        assert_eq!(fs::read(&other_path).unwrap(), before_raw);
+        assert!(tmp.path().join(format!("{}.json", target.id)).exists());
+    }
+
+    #[test]
+    fn test_migrates_legacy_scripts_json() {
+        let tmp = TempDir::new().unwrap();
+        let legacy = vec![
+            make_script("deploy"),
+            make_script("backup"),
+            make_script("lint"),
+        ];
+        fs::write(
+            tmp.path().join(SCRIPTS_FILE),
+            serde_json::to_string(&legacy).unwrap(),
+        )
+        .unwrap();
+
+        let s = storage(&tmp);
+        let mut names: Vec<String> = s
+            .list_scripts()
+            .unwrap()
+            .into_iter()
+            .map(|s| s.name)
+            .collect();
+        names.sort();
+        assert_eq!(names, ["backup", "deploy", "lint"]);
+        for script in &legacy {
+            assert_eq!(s.load_script(&script.id).unwrap().content, script.content);
+        }
+        assert!(!tmp.path().join(SCRIPTS_FILE).exists());
+        assert!(tmp.path().join("scripts.json.migrated").exists());
+
+        // Opening again must not re-run the migration.
+        assert_eq!(storage(&tmp).list_scripts().unwrap().len(), 3);
+    }
+
+    #[test]
+    fn test_migrates_legacy_map_format() {
+        let tmp = TempDir::new().unwrap();
+        let script = make_script("deploy");
+        let mut map = HashMap::new();
+        map.insert(script.id.clone(), script.clone());
+        fs::write(
+            tmp.path().join(SCRIPTS_FILE),
+            serde_json::to_string(&map).unwrap(),
+        )
+        .unwrap();
+
+        let s = storage(&tmp);
+        assert_eq!(s.load_script_by_name("deploy").unwrap().id, script.id);
+    }
+
+    fn disk_reads(s: &LocalStorage) -> usize {
+        s.disk_reads.load(std::sync::atomic::Ordering::Relaxed)
+    }
+
+    #[test]
+    fn test_workflow_parses_each_script_once() {
+        let tmp = TempDir::new().unwrap();
+        let writer = storage(&tmp);
+        for name in ["deploy", "backup", "lint"] {
+            writer.save_script(&make_script(name)).unwrap();
+        }
+
+        // Same sequence of reads as `sv find --here` followed by `sv info`.
+        let s = storage(&tmp);
+        s.list_summaries(&ListOptions::default()).unwrap();
+        s.list_scripts().unwrap();
+        let deploy = s.load_script_by_name("deploy").unwrap();
+        s.get_metadata().unwrap();
+        assert_eq!(disk_reads(&s), 3);
+
+        // Helpers open their own instance; writes keep the shared cache current.
+        let helper = storage(&tmp);
+        helper.update_script(&deploy).unwrap();
+        helper.list_scripts().unwrap();
+        s.list_scripts().unwrap();
+        assert_eq!(disk_reads(&s) + disk_reads(&helper), 3);
+    }
+
+    #[test]
+    fn test_cache_sees_writes_through_same_instance() {
+        let tmp = TempDir::new().unwrap();
+        let s = storage(&tmp);
+        s.save_script(&make_script("deploy")).unwrap();
+        assert_eq!(s.list_scripts().unwrap().len(), 1);
+
+        let mut renamed = s.load_script_by_name("deploy").unwrap();
+        renamed.name = "ship".to_string();
+        s.update_script(&renamed).unwrap();
+        s.save_script(&make_script("backup")).unwrap();
+
+        let mut names: Vec<String> = s
+            .list_scripts()
+            .unwrap()
+            .into_iter()
+            .map(|s| s.name)
+            .collect();
+        names.sort();
+        assert_eq!(names, ["backup", "ship"]);
+
+        s.delete_script(&renamed.id).unwrap();
+        assert_eq!(s.list_scripts().unwrap().len(), 1);
+        assert!(s.load_script(&renamed.id).is_err());
+    }
+
+    #[test]
+    fn test_list_summaries_hides_archived_by_default() {
+        let tmp = TempDir::new().unwrap();
+        let s = storage(&tmp);
+        s.save_script(&make_script("active")).unwrap();
+        let mut archived = make_script("old");
+        archived.archived = true;
+        s.save_script(&archived).unwrap();
+
+        let visible = s.list_summaries(&ListOptions::default()).unwrap();
+        assert_eq!(visible.len(), 1);
+        assert_eq!(visible[0].name, "active");
+
+        let all = s
+            .list_summaries(&ListOptions {
+                include_archived: true,
+                ..Default::default()
+            })
+            .unwrap();
+        assert_eq!(all.len(), 2);
+        assert!(all.iter().any(|s| s.name == "old" && s.archived));
+    }
+
     #[test]
     fn test_mark_synced_and_conflict() {
         let tmp = TempDir::new().unwrap();
@@ -1,25 +1,58 @@
 pub mod commands;
+pub mod http;
 pub mod local;
+pub mod read_only;
 
-use crate::script::{Script, ScriptSummary, SyncStatus};
-use anyhow::Result;
+use crate::script::{Script, ScriptSummary, SyncStatus, content_hash};
+use anyhow::{Result, anyhow};
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
-use std::path::PathBuf;
+use std::fmt;
+use std::path::{Path, PathBuf};
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct StorageConfig {
-    pub path: PathBuf,
+/// Where scripts are kept. Untagged so configs written before there was a
+/// choice of backend (`{ "path": ... }`) still load as `Local`.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(untagged)]
+pub enum StorageConfig {
+    Local {
+        path: PathBuf,
+    },
+    /// A plain HTTP(S) or WebDAV server holding the same files as a local
+    /// vault directory.
+    Http {
+        base_url: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        token: Option<String>,
+    },
 }
 
 impl Default for StorageConfig {
     fn default() -> Self {
-        Self {
+        Self::Local {
             path: PathBuf::from(".scriptvault/vault"),
         }
     }
 }
 
+impl StorageConfig {
+    pub fn local_path(&self) -> Option<&Path> {
+        match self {
+            Self::Local { path } => Some(path),
+            Self::Http { .. } => None,
+        }
+    }
+}
+
+impl fmt::Display for StorageConfig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Local { path } => write!(f, "{}", path.display()),
+            Self::Http { base_url, .. } => write!(f, "{}", base_url),
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct StorageMetadata {
     pub total_scripts: usize,
@@ -32,6 +65,7 @@ pub struct StorageMetadata {
 pub struct ListOptions {
     pub limit: usize,
     pub offset: usize,
+    pub include_archived: bool,
 }
 
 impl Default for ListOptions {
@@ -39,6 +73,7 @@ impl Default for ListOptions {
         Self {
             limit: 50,
             offset: 0,
+            include_archived: false,
         }
     }
 }
@@ -69,8 +104,65 @@ pub trait StorageBackend: Send + Sync {
 }
 
 pub fn create_storage_backend(config: &StorageConfig) -> Result<Box<dyn StorageBackend>> {
-    let backend = local::LocalStorage::new(config.path.clone())?;
-    Ok(Box::new(backend))
+    match config {
+        StorageConfig::Local { path } => Ok(Box::new(local::LocalStorage::new(path.clone())?)),
+        StorageConfig::Http { base_url, token } => {
+            Ok(Box::new(http::HttpStorage::new(base_url, token.clone())?))
+        }
+    }
+}
+
+/// Copies every script from `from` into `to`, then reads each one back to
+/// check that nothing was lost or altered. Returns the number of scripts
+/// copied. Refuses up front if `to` holds a different script under a name
+/// being copied, since saving would replace it.
+pub fn migrate(from: &dyn StorageBackend, to: &dyn StorageBackend) -> Result<usize> {
+    let scripts = from.list_scripts()?;
+
+    for existing in to.list_scripts()? {
+        if let Some(clash) = scripts
+            .iter()
+            .find(|s| s.name == existing.name && s.id != existing.id)
+        {
+            return Err(anyhow!(
+                "Target already has a different script named {}",
+                clash.name
+            ));
+        }
+    }
+
+    for script in &scripts {
+        to.save_script(script)?;
+    }
+
+    let mut problems = Vec::new();
+    for script in &scripts {
+        match to.load_script(&script.id) {
+            Ok(copy) if content_hash(&copy.content) == content_hash(&script.content) => {}
+            Ok(_) => problems.push(format!("{}: content differs", script.name)),
+            Err(_) => problems.push(format!("{}: missing", script.name)),
+        }
+    }
+    if !problems.is_empty() {
+        return Err(anyhow!(
+            "Verification failed for {} of {} scripts: {}",
+            problems.len(),
+            scripts.len(),
+            problems.join("; ")
+        ));
+    }
+
+    Ok(scripts.len())
+}
+
+/// Newest first, then the requested page.
+fn page_summaries(mut summaries: Vec<ScriptSummary>, opts: &ListOptions) -> Vec<ScriptSummary> {
+    summaries.sort_by_key(|s| std::cmp::Reverse(s.updated_at));
+    summaries
+        .into_iter()
+        .skip(opts.offset)
+        .take(opts.limit)
+        .collect()
 }
 
 #[cfg(test)]
@@ -80,17 +172,112 @@ mod tests {
     #[test]
     fn test_default_storage_config() {
         let config = StorageConfig::default();
-        assert_eq!(config.path, PathBuf::from(".scriptvault/vault"));
+        assert_eq!(config.local_path(), Some(Path::new(".scriptvault/vault")));
     }
 
     #[test]
     fn test_storage_config_serialization() {
-        let config = StorageConfig {
+        let config = StorageConfig::Local {
             path: PathBuf::from("/test/path"),
         };
         let json = serde_json::to_string(&config).unwrap();
+        assert_eq!(json, r#"{"path":"/test/path"}"#);
         let deserialized: StorageConfig = serde_json::from_str(&json).unwrap();
-        assert_eq!(deserialized.path, PathBuf::from("/test/path"));
+        assert_eq!(deserialized, config);
+    }
+
+    #[test]
+    fn test_http_storage_config_serialization() {
+        let config: StorageConfig =
+            serde_json::from_str(r#"{"base_url":"https://dav.example.test/vault"}"#).unwrap();
+        assert_eq!(
+            config,
+            StorageConfig::Http {
+                base_url: "https://dav.example.test/vault".to_string(),
+                token: None,
+            }
+        );
+        assert!(config.local_path().is_none());
+
+        let with_token = StorageConfig::Http {
+            base_url: "https://dav.example.test/vault".to_string(),
+            token: Some("secret".to_string()),
+        };
+        let json = serde_json::to_string(&with_token).unwrap();
+        assert_eq!(
+            serde_json::from_str::<StorageConfig>(&json).unwrap(),
+            with_token
+        );
+    }
+
+    fn local(tmp: &tempfile::TempDir) -> local::LocalStorage {
+        local::LocalStorage::new(tmp.path().to_path_buf()).unwrap()
+    }
+
+    fn script(name: &str, content: &str) -> Script {
+        Script::new(
+            name.to_string(),
+            content.to_string(),
+            crate::script::ScriptLanguage::Bash,
+        )
+    }
+
+    #[test]
+    fn test_migrate_copies_every_script() {
+        let (src_dir, dst_dir) = (
+            tempfile::TempDir::new().unwrap(),
+            tempfile::TempDir::new().unwrap(),
+        );
+        let (src, dst) = (local(&src_dir), local(&dst_dir));
+        let mut archived = script("old", "echo old\n");
+        archived.archived = true;
+        for s in [
+            script("deploy", "echo deploy\n"),
+            script("backup", "echo backup\n"),
+            archived,
+        ] {
+            src.save_script(&s).unwrap();
+        }
+
+        assert_eq!(migrate(&src, &dst).unwrap(), 3);
+
+        let mut expected = src.list_scripts().unwrap();
+        let mut copied = dst.list_scripts().unwrap();
+        expected.sort_by(|a, b| a.id.cmp(&b.id));
+        copied.sort_by(|a, b| a.id.cmp(&b.id));
+        assert_eq!(copied.len(), expected.len());
+        for (a, b) in expected.iter().zip(&copied) {
+            assert_eq!(a.id, b.id);
+            assert_eq!(a.name, b.name);
+            assert_eq!(a.content, b.content);
+            assert_eq!(a.metadata.hash, b.metadata.hash);
+            assert_eq!(a.archived, b.archived);
+        }
+
+        // Running it again is harmless.
+        assert_eq!(migrate(&src, &dst).unwrap(), 3);
+        assert_eq!(dst.list_scripts().unwrap().len(), 3);
+    }
+
+    #[test]
+    fn test_migrate_refuses_to_replace_different_script() {
+        let (src_dir, dst_dir) = (
+            tempfile::TempDir::new().unwrap(),
+            tempfile::TempDir::new().unwrap(),
+        );
+        let (src, dst) = (local(&src_dir), local(&dst_dir));
+        src.save_script(&script("deploy", "echo new\n")).unwrap();
+        dst.save_script(&script("deploy", "echo old\n")).unwrap();
+
+        let err = migrate(&src, &dst).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Target already has a different script named deploy"
+        );
+        assert_eq!(
+            dst.load_script_by_name("deploy").unwrap().content,
+            "echo old\n"
+        );
     }
 
     #[test]
@@ -98,5 +285,6 @@ mod tests {
         let opts = ListOptions::default();
         assert_eq!(opts.limit, 50);
         assert_eq!(opts.offset, 0);
+        assert!(!opts.include_archived);
     }
 }
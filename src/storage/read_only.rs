@@ -0,0 +1,124 @@
+use super::{ListOptions, StorageBackend, StorageMetadata};
+use crate::script::{Script, ScriptSummary, SyncStatus};
+use anyhow::{Result, anyhow};
+use chrono::{DateTime, Utc};
+
+pub fn read_only_error() -> anyhow::Error {
+    anyhow!(
+        "The vault is read-only, so nothing was changed. Set read_only to false in the config or drop --read-only."
+    )
+}
+
+/// Wraps a backend so reads pass through and every write fails with
+/// `read_only_error`.
+pub struct ReadOnlyStorage {
+    inner: Box<dyn StorageBackend>,
+}
+
+impl ReadOnlyStorage {
+    pub fn new(inner: Box<dyn StorageBackend>) -> Self {
+        Self { inner }
+    }
+}
+
+impl StorageBackend for ReadOnlyStorage {
+    fn save_script(&self, _script: &Script) -> Result<()> {
+        Err(read_only_error())
+    }
+
+    fn update_script(&self, _script: &Script) -> Result<()> {
+        Err(read_only_error())
+    }
+
+    fn load_script(&self, id: &str) -> Result<Script> {
+        self.inner.load_script(id)
+    }
+
+    fn load_script_by_name(&self, name: &str) -> Result<Script> {
+        self.inner.load_script_by_name(name)
+    }
+
+    fn list_scripts(&self) -> Result<Vec<Script>> {
+        self.inner.list_scripts()
+    }
+
+    fn list_summaries(&self, opts: &ListOptions) -> Result<Vec<ScriptSummary>> {
+        self.inner.list_summaries(opts)
+    }
+
+    fn delete_script(&self, _id: &str) -> Result<()> {
+        Err(read_only_error())
+    }
+
+    fn script_exists(&self, id: &str) -> Result<bool> {
+        self.inner.script_exists(id)
+    }
+
+    fn get_metadata(&self) -> Result<StorageMetadata> {
+        self.inner.get_metadata()
+    }
+
+    fn health_check(&self) -> Result<bool> {
+        self.inner.health_check()
+    }
+
+    fn get_sync_status(&self, script_id: &str) -> Result<SyncStatus> {
+        self.inner.get_sync_status(script_id)
+    }
+
+    fn mark_synced(
+        &self,
+        _script_id: &str,
+        _remote_version: &str,
+        _synced_at: DateTime<Utc>,
+    ) -> Result<()> {
+        Err(read_only_error())
+    }
+
+    fn mark_conflict(&self, _script_id: &str) -> Result<()> {
+        Err(read_only_error())
+    }
+
+    fn list_pending_push(&self) -> Result<Vec<Script>> {
+        self.inner.list_pending_push()
+    }
+
+    fn list_conflicts(&self) -> Result<Vec<Script>> {
+        self.inner.list_conflicts()
+    }
+
+    fn backend_type(&self) -> &str {
+        self.inner.backend_type()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::script::ScriptLanguage;
+    use crate::storage::local::LocalStorage;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_reads_pass_through_and_writes_fail() {
+        let tmp = TempDir::new().unwrap();
+        let local = LocalStorage::new(tmp.path().to_path_buf()).unwrap();
+        let script = Script::new(
+            "deploy".to_string(),
+            "echo deploy\n".to_string(),
+            ScriptLanguage::Bash,
+        );
+        local.save_script(&script).unwrap();
+
+        let storage = ReadOnlyStorage::new(Box::new(local));
+        assert_eq!(storage.load_script_by_name("deploy").unwrap().id, script.id);
+        assert_eq!(storage.list_scripts().unwrap().len(), 1);
+
+        let err = storage.delete_script(&script.id).unwrap_err();
+        assert!(err.to_string().starts_with("The vault is read-only"));
+        assert!(storage.save_script(&script).is_err());
+        assert!(storage.update_script(&script).is_err());
+        assert!(storage.mark_conflict(&script.id).is_err());
+        assert!(storage.script_exists(&script.id).unwrap());
+    }
+}
@@ -322,6 +322,9 @@ mod tests {
                 git_repo: None,
                 git_branch: None,
                 environment: HashMap::new(),
+                os: None,
+                arch: None,
+                hostname: None,
             },
             metadata: ScriptMetadata {
                 hash,
@@ -336,6 +339,15 @@ mod tests {
             },
             visibility: Visibility::Private,
             sync_state: SyncState::default(),
+            archived: false,
+            pinned: false,
+            trusted: false,
+            safety_acknowledged: false,
+            pre_run: None,
+            post_run: None,
+            params: Vec::new(),
+            env: HashMap::new(),
+            success_exit_codes: vec![0],
         }
     }
 
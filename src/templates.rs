@@ -0,0 +1,379 @@
+use crate::cli::{TemplateAction, TemplateAddArgs};
+use crate::config::Config;
+use crate::script::ScriptLanguage;
+use anyhow::{Context, Result, anyhow};
+use colored::*;
+use dialoguer::Input;
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+const BASH_TEMPLATE: &str = r#"#!/usr/bin/env bash
+# {{name}}: {{description}}
+set -euo pipefail
+
+usage() {
+    echo "Usage: {{name}} [args...]" >&2
+}
+
+main() {
+    if [[ "${1:-}" == "-h" || "${1:-}" == "--help" ]]; then
+        usage
+        exit 0
+    fi
+
+    echo "Running {{name}}"
+}
+
+main "$@"
+"#;
+
+const PYTHON_ARGPARSE_TEMPLATE: &str = r#"#!/usr/bin/env python3
+"""{{description}}"""
+import argparse
+
+
+def parse_args():
+    parser = argparse.ArgumentParser(prog="{{name}}", description="{{description}}")
+    parser.add_argument("-v", "--verbose", action="store_true", help="print more output")
+    return parser.parse_args()
+
+
+def main():
+    args = parse_args()
+    if args.verbose:
+        print("Running {{name}}")
+
+
+if __name__ == "__main__":
+    main()
+"#;
+
+/// Templates shipped with sv. A user template with the same name takes
+/// precedence.
+const BUILTIN_TEMPLATES: &[(&str, ScriptLanguage, &str)] = &[
+    ("bash", ScriptLanguage::Bash, BASH_TEMPLATE),
+    (
+        "python-argparse",
+        ScriptLanguage::Python,
+        PYTHON_ARGPARSE_TEMPLATE,
+    ),
+];
+
+#[derive(Debug, Clone)]
+pub struct Template {
+    pub name: String,
+    pub language: ScriptLanguage,
+    pub content: String,
+    pub builtin: bool,
+}
+
+pub struct TemplateStore {
+    base: PathBuf,
+}
+
+impl TemplateStore {
+    pub fn new(data_dir: &Path) -> Self {
+        Self {
+            base: data_dir.join("templates"),
+        }
+    }
+
+    fn user_templates(&self) -> Result<Vec<Template>> {
+        if !self.base.exists() {
+            return Ok(Vec::new());
+        }
+
+        let mut templates = Vec::new();
+        for entry in fs::read_dir(&self.base).context("failed to read templates directory")? {
+            let path = entry?.path();
+            let (Some(name), Some(ext)) = (
+                path.file_stem().and_then(|s| s.to_str()),
+                path.extension().and_then(|s| s.to_str()),
+            ) else {
+                continue;
+            };
+            templates.push(Template {
+                name: name.to_string(),
+                language: ScriptLanguage::from_extension(ext),
+                content: fs::read_to_string(&path)
+                    .with_context(|| format!("failed to read template {}", path.display()))?,
+                builtin: false,
+            });
+        }
+        Ok(templates)
+    }
+
+    /// All templates sorted by name, with user templates shadowing built-ins.
+    pub fn list(&self) -> Result<Vec<Template>> {
+        let mut templates = self.user_templates()?;
+        for (name, language, content) in BUILTIN_TEMPLATES {
+            if !templates.iter().any(|t| t.name == *name) {
+                templates.push(Template {
+                    name: name.to_string(),
+                    language: language.clone(),
+                    content: content.to_string(),
+                    builtin: true,
+                });
+            }
+        }
+        templates.sort_by(|a, b| a.name.cmp(&b.name));
+        Ok(templates)
+    }
+
+    pub fn load(&self, name: &str) -> Result<Template> {
+        self.list()?
+            .into_iter()
+            .find(|t| t.name == name)
+            .ok_or_else(|| anyhow!("Template not found: {}", name))
+    }
+
+    pub fn add(&self, name: &str, language: &ScriptLanguage, content: &str) -> Result<PathBuf> {
+        if self.user_templates()?.iter().any(|t| t.name == name) {
+            return Err(anyhow!("Template already exists: {}", name));
+        }
+        fs::create_dir_all(&self.base).context("failed to create templates directory")?;
+        let path = self.base.join(format!("{}.{}", name, language.extension()));
+        fs::write(&path, content).context("failed to write template")?;
+        Ok(path)
+    }
+}
+
+/// Returns the text between `{{` and `}}` when it is a valid placeholder name.
+/// Anything else, such as `{{ }}` or `{{a b}}`, is left as literal text.
+fn placeholder_at(rest: &str) -> Option<(&str, usize)> {
+    let inner = rest.strip_prefix("{{")?;
+    let end = inner.find("}}")?;
+    let name = inner[..end].trim();
+    let valid = !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-');
+    valid.then_some((name, end + 4))
+}
+
+/// Placeholder names in order of first appearance, without duplicates.
+pub fn placeholders(content: &str) -> Vec<String> {
+    let mut names: Vec<String> = Vec::new();
+    let mut rest = content;
+    while let Some(start) = rest.find("{{") {
+        rest = &rest[start..];
+        match placeholder_at(rest) {
+            Some((name, len)) => {
+                if !names.iter().any(|n| n == name) {
+                    names.push(name.to_string());
+                }
+                rest = &rest[len..];
+            }
+            None => rest = &rest[2..],
+        }
+    }
+    names
+}
+
+/// Substitutes every `{{placeholder}}`. Fails on the first placeholder that
+/// has no value rather than leaving it in the script.
+pub fn render(content: &str, values: &HashMap<String, String>) -> Result<String> {
+    let mut out = String::with_capacity(content.len());
+    let mut rest = content;
+    while let Some(start) = rest.find("{{") {
+        out.push_str(&rest[..start]);
+        rest = &rest[start..];
+        match placeholder_at(rest) {
+            Some((name, len)) => {
+                let value = values
+                    .get(name)
+                    .ok_or_else(|| anyhow!("Missing value for template placeholder: {}", name))?;
+                out.push_str(value);
+                rest = &rest[len..];
+            }
+            None => {
+                out.push_str("{{");
+                rest = &rest[2..];
+            }
+        }
+    }
+    out.push_str(rest);
+    Ok(out)
+}
+
+/// Parses a `KEY=VALUE` argument, as taken by `--var`, `--script-env` and
+/// `sv run --env`.
+pub fn parse_var(spec: &str) -> Result<(String, String), String> {
+    match spec.split_once('=') {
+        Some((key, value)) if !key.trim().is_empty() => {
+            Ok((key.trim().to_string(), value.to_string()))
+        }
+        _ => Err(format!("expected KEY=VALUE, got '{}'", spec)),
+    }
+}
+
+/// Fills in the template's placeholders from `values`, prompting for the rest
+/// when `interactive` is set. Without prompts a missing value is an error.
+pub fn instantiate(
+    template: &Template,
+    mut values: HashMap<String, String>,
+    interactive: bool,
+) -> Result<String> {
+    for name in placeholders(&template.content) {
+        if values.contains_key(&name) {
+            continue;
+        }
+        if !interactive {
+            return Err(anyhow!(
+                "Missing value for template placeholder: {} (pass --var {}=VALUE)",
+                name,
+                name
+            ));
+        }
+        let value: String = Input::new()
+            .with_prompt(&name)
+            .allow_empty(true)
+            .interact_text()?;
+        values.insert(name, value);
+    }
+    render(&template.content, &values)
+}
+
+pub fn handle_template_command(action: TemplateAction) -> Result<()> {
+    let store = TemplateStore::new(&Config::data_dir()?);
+    match action {
+        TemplateAction::List => list_templates(&store),
+        TemplateAction::Add(args) => add_template(&store, args),
+    }
+}
+
+fn list_templates(store: &TemplateStore) -> Result<()> {
+    println!("{}", "Templates".cyan().bold());
+    println!();
+    for template in store.list()? {
+        let source = if template.builtin { "built-in" } else { "user" };
+        let vars = placeholders(&template.content).join(", ");
+        println!(
+            "  {:<18} {:<8} {:<9} {}",
+            template.name.yellow(),
+            template.language.to_string().green(),
+            source.dimmed(),
+            vars
+        );
+    }
+    Ok(())
+}
+
+fn add_template(store: &TemplateStore, args: TemplateAddArgs) -> Result<()> {
+    let path = Path::new(&args.file);
+    if !path.exists() {
+        return Err(anyhow!("Template file not found: {}", args.file));
+    }
+    let content = crate::utils::read_script_text(path)?;
+    let name = match args.name {
+        Some(name) => name,
+        None => path
+            .file_stem()
+            .and_then(|s| s.to_str())
+            .ok_or_else(|| anyhow!("Invalid template filename"))?
+            .to_string(),
+    };
+    let language =
+        ScriptLanguage::from_extension(path.extension().and_then(|s| s.to_str()).unwrap_or("sh"));
+
+    store.add(&name, &language, &content)?;
+
+    println!("{} Added template: {}", "✓".green().bold(), name.yellow());
+    let vars = placeholders(&content);
+    if !vars.is_empty() {
+        println!("  Placeholders: {}", vars.join(", ").cyan());
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn values(pairs: &[(&str, &str)]) -> HashMap<String, String> {
+        pairs
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn test_placeholders_in_order_without_duplicates() {
+        let content = "{{name}} {{ description }} {{name}} {{}} {{not valid}} ${{x";
+        assert_eq!(placeholders(content), ["name", "description"]);
+    }
+
+    #[test]
+    fn test_render_substitutes_all() {
+        let rendered = render(
+            "echo {{greeting}}, {{ who }}! {{greeting}}",
+            &values(&[("greeting", "hi"), ("who", "world")]),
+        )
+        .unwrap();
+        assert_eq!(rendered, "echo hi, world! hi");
+    }
+
+    #[test]
+    fn test_render_leaves_non_placeholders() {
+        let content = "echo ${{x}} {{ }} {{a b}} {{unterminated";
+        assert_eq!(
+            render(content, &values(&[("x", "1")])).unwrap(),
+            "echo $1 {{ }} {{a b}} {{unterminated"
+        );
+    }
+
+    #[test]
+    fn test_render_missing_placeholder_fails() {
+        let err = render("{{a}} {{b}}", &values(&[("a", "1")])).unwrap_err();
+        assert_eq!(err.to_string(), "Missing value for template placeholder: b");
+    }
+
+    #[test]
+    fn test_instantiate_without_prompts_requires_values() {
+        let store = TemplateStore::new(TempDir::new().unwrap().path());
+        let template = store.load("bash").unwrap();
+
+        let err = instantiate(&template, values(&[("name", "tool")]), false).unwrap_err();
+        assert!(err.to_string().contains("--var description=VALUE"));
+
+        let script = instantiate(
+            &template,
+            values(&[("name", "tool"), ("description", "Does things")]),
+            false,
+        )
+        .unwrap();
+        assert!(script.contains("# tool: Does things"));
+        assert!(!script.contains("{{"));
+    }
+
+    #[test]
+    fn test_user_templates_shadow_builtins() {
+        let dir = TempDir::new().unwrap();
+        let store = TemplateStore::new(dir.path());
+        assert!(store.load("bash").unwrap().builtin);
+
+        store
+            .add("bash", &ScriptLanguage::Bash, "echo {{name}}\n")
+            .unwrap();
+        let template = store.load("bash").unwrap();
+        assert!(!template.builtin);
+        assert_eq!(template.content, "echo {{name}}\n");
+        assert!(store.add("bash", &ScriptLanguage::Bash, "").is_err());
+
+        let names: Vec<String> = store.list().unwrap().into_iter().map(|t| t.name).collect();
+        assert_eq!(names, ["bash", "python-argparse"]);
+    }
+
+    #[test]
+    fn test_parse_var() {
+        assert_eq!(
+            parse_var("port=8080").unwrap(),
+            ("port".to_string(), "8080".to_string())
+        );
+        assert_eq!(parse_var("url=a=b").unwrap().1, "a=b");
+        assert!(parse_var("novalue").is_err());
+        assert!(parse_var("=x").is_err());
+    }
+}
@@ -1,5 +1,148 @@
-use anyhow::Result;
+use crate::cli::{DoctorArgs, OpenConfigArgs};
+use crate::config::{Config, ConfigFormat};
+use crate::constants::ENV_COLUMNS;
+use crate::script::Script;
+use crate::storage::StorageBackend;
+use anyhow::{Context, Result, anyhow};
+use chrono::{DateTime, Utc};
 use colored::*;
+use std::collections::{BTreeMap, BTreeSet, HashSet};
+use std::fs;
+use std::path::Path;
+
+/// Time units shared by `humanize_duration` and `parse_age`: suffix, name,
+/// and length in seconds. A month is 30 days.
+const TIME_UNITS: &[(&str, &str, i64)] = &[
+    ("s", "second", 1),
+    ("m", "minute", 60),
+    ("h", "hour", 3600),
+    ("d", "day", 86_400),
+    ("w", "week", 7 * 86_400),
+    ("mo", "month", 30 * 86_400),
+];
+
+/// Reads a script file as text. Binary or otherwise non-UTF-8 files get a
+/// clear error instead of the io error's "stream did not contain valid UTF-8".
+pub fn read_script_text(path: &Path) -> Result<String> {
+    let bytes = fs::read(path).with_context(|| format!("Failed to read {}", path.display()))?;
+    String::from_utf8(bytes).map_err(|_| {
+        anyhow!(
+            "{}: file is not valid UTF-8; binary scripts are unsupported",
+            path.display()
+        )
+    })
+}
+
+/// Relative description of a past time, e.g. "3 hours ago".
+pub fn humanize_duration(dt: DateTime<Utc>) -> String {
+    humanize_since(dt, Utc::now())
+}
+
+/// Buckets are cut on whole units, so 59 minutes is still "59 minutes ago".
+/// Times in the future read as "just now".
+fn humanize_since(dt: DateTime<Utc>, now: DateTime<Utc>) -> String {
+    let secs = (now - dt).num_seconds();
+    if secs < 10 {
+        return "just now".to_string();
+    }
+    let (_, unit, unit_secs) = TIME_UNITS
+        .iter()
+        .rev()
+        .find(|(_, _, unit_secs)| secs >= *unit_secs)
+        .expect("seconds always match");
+    let count = secs / unit_secs;
+    let plural = if count == 1 { "" } else { "s" };
+    format!("{} {}{} ago", count, unit, plural)
+}
+
+/// Width of the terminal in columns: `COLUMNS` if set, else the size of the
+/// terminal stdout is attached to. `None` when output is piped.
+pub fn terminal_width() -> Option<usize> {
+    if let Some(columns) = std::env::var(ENV_COLUMNS)
+        .ok()
+        .and_then(|v| v.trim().parse::<usize>().ok())
+        .filter(|&c| c > 0)
+    {
+        return Some(columns);
+    }
+    terminal_size::terminal_size().map(|(terminal_size::Width(w), _)| w as usize)
+}
+
+/// Shortens `text` to at most `width` characters, ending in "…" when
+/// anything was cut.
+pub fn truncate_ellipsis(text: &str, width: usize) -> String {
+    if text.chars().count() <= width {
+        return text.to_string();
+    }
+    if width == 0 {
+        return String::new();
+    }
+    let mut cut: String = text.chars().take(width - 1).collect();
+    cut.push('…');
+    cut
+}
+
+/// A success percentage colored green above 90%, yellow above 70% and red
+/// otherwise.
+pub fn color_success_rate(rate: f64) -> ColoredString {
+    let text = format!("{:.1}%", rate);
+    if rate > 90.0 {
+        text.green()
+    } else if rate > 70.0 {
+        text.yellow()
+    } else {
+        text.red()
+    }
+}
+
+/// Byte count in binary units, e.g. "512 B" or "1.5 KB".
+pub fn human_size(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["KB", "MB", "GB", "TB"];
+    if bytes < 1024 {
+        return format!("{} B", bytes);
+    }
+    let mut size = bytes as f64 / 1024.0;
+    let mut unit = 0;
+    while size >= 1024.0 && unit < UNITS.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+    format!("{:.1} {}", size, UNITS[unit])
+}
+
+/// Parses a duration like `30s`, `5m`, `2h`, `7d`, `2w`, or `6mo`. A plain
+/// number is seconds.
+pub fn parse_duration(s: &str) -> Result<std::time::Duration, String> {
+    let split = s.find(|c: char| !c.is_ascii_digit()).unwrap_or(s.len());
+    let (digits, suffix) = s.split_at(split);
+    if digits.is_empty() {
+        return Err(format!("'{}' does not start with a number", s));
+    }
+    let too_large = || format!("'{}' is too large", s);
+    let count: u64 = digits.parse().map_err(|_| too_large())?;
+    let unit_secs = match suffix {
+        "" => 1,
+        _ => {
+            TIME_UNITS
+                .iter()
+                .find(|(unit, _, _)| *unit == suffix)
+                .ok_or_else(|| format!("unknown unit in '{}' (use s, m, h, d, w, or mo)", s))?
+                .2
+        }
+    };
+    let secs = count.checked_mul(unit_secs as u64).ok_or_else(too_large)?;
+    Ok(std::time::Duration::from_secs(secs))
+}
+
+/// Parses an age like `90m`, `12h`, `30d`, `2w`, or `6mo`. Unlike
+/// `parse_duration` the unit is required: an age of `30` is more likely
+/// meant as days than seconds.
+pub fn parse_age(s: &str) -> Result<chrono::Duration, String> {
+    if s.bytes().all(|b| b.is_ascii_digit()) {
+        return Err(format!("'{}' needs a unit (s, m, h, d, w, or mo)", s));
+    }
+    chrono::Duration::from_std(parse_duration(s)?).map_err(|_| format!("'{}' is too large", s))
+}
 
 fn health_url(api_endpoint: &str) -> String {
     if let Some(base) = api_endpoint.strip_suffix("/v1") {
@@ -9,7 +152,7 @@ fn health_url(api_endpoint: &str) -> String {
     }
 }
 
-pub fn run_doctor() -> Result<()> {
+pub fn run_doctor(args: DoctorArgs) -> Result<()> {
     println!("{}", "ScriptVault Health Check".cyan().bold());
     println!();
 
@@ -106,11 +249,248 @@ pub fn run_doctor() -> Result<()> {
         }
     }
 
+    if args.check_scripts {
+        println!();
+        println!("  {}:", "Scripts".bold());
+        check_scripts_doctor(&config)?;
+    }
+
+    println!();
+    println!("  {}:", "Duplicates".bold());
+    check_duplicates_doctor(&config, args.fix)?;
+
     println!();
     println!("{}", "Health check complete.".green().bold());
     Ok(())
 }
 
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CheckLevel {
+    Ok,
+    Warn,
+    Fail,
+}
+
+#[derive(Debug)]
+pub struct ScriptCheck {
+    pub level: CheckLevel,
+    pub detail: Option<String>,
+}
+
+/// Checks what `sv run` would trip over: an unknown language or a missing
+/// interpreter is a failure, dangerous patterns are a warning.
+pub fn check_script(script: &Script, has_program: impl Fn(&str) -> bool) -> ScriptCheck {
+    if script.language == crate::script::ScriptLanguage::Unknown {
+        return ScriptCheck {
+            level: CheckLevel::Fail,
+            detail: Some("unknown language; use --interpreter or re-save".to_string()),
+        };
+    }
+    let interpreter = crate::execution::default_interpreter(&script.language);
+    if !has_program(interpreter) {
+        return ScriptCheck {
+            level: CheckLevel::Fail,
+            detail: Some(format!("interpreter '{}' not found", interpreter)),
+        };
+    }
+
+    let dangerous = script.dangerous_patterns();
+    if !dangerous.is_empty() {
+        return ScriptCheck {
+            level: CheckLevel::Warn,
+            detail: Some(format!("dangerous patterns: {}", dangerous.join(", "))),
+        };
+    }
+
+    ScriptCheck {
+        level: CheckLevel::Ok,
+        detail: None,
+    }
+}
+
+fn check_scripts_doctor(config: &Config) -> Result<()> {
+    let storage = config.get_storage_backend()?;
+    let mut scripts = storage.list_scripts()?;
+    scripts.sort_by(|a, b| a.name.cmp(&b.name));
+
+    if scripts.is_empty() {
+        println!("    {}", "no scripts in vault".dimmed());
+        return Ok(());
+    }
+
+    let mut counts = [0usize; 3];
+    for script in &scripts {
+        let check = check_script(script, |program| which::which(program).is_ok());
+        let label = match check.level {
+            CheckLevel::Ok => "OK".green(),
+            CheckLevel::Warn => "WARN".yellow(),
+            CheckLevel::Fail => "FAIL".red(),
+        };
+        counts[check.level as usize] += 1;
+        match check.detail {
+            Some(detail) => println!("    {:<4} {} ({})", label, script.name, detail),
+            None => println!("    {:<4} {}", label, script.name),
+        }
+    }
+
+    println!();
+    println!(
+        "    {} ok, {} warn, {} fail",
+        counts[0], counts[1], counts[2]
+    );
+    Ok(())
+}
+
+/// Scripts that share a name or an id. These can appear when script files are
+/// copied into the vault by hand, since saving always replaces by name.
+#[derive(Debug, Default)]
+pub struct Duplicates {
+    /// Groups of distinct scripts with the same name, most recently updated
+    /// first.
+    pub names: Vec<Vec<Script>>,
+    /// Ids listed more than once, i.e. one script under several names.
+    pub ids: Vec<String>,
+}
+
+impl Duplicates {
+    pub fn is_empty(&self) -> bool {
+        self.names.is_empty() && self.ids.is_empty()
+    }
+}
+
+pub fn find_duplicates(scripts: &[Script]) -> Duplicates {
+    let mut seen_ids: HashSet<&str> = HashSet::new();
+    let mut ids: BTreeSet<String> = BTreeSet::new();
+    let mut by_name: BTreeMap<&str, Vec<Script>> = BTreeMap::new();
+    for script in scripts {
+        if !seen_ids.insert(&script.id) {
+            ids.insert(script.id.clone());
+            continue;
+        }
+        by_name
+            .entry(&script.name)
+            .or_default()
+            .push(script.clone());
+    }
+
+    let names = by_name
+        .into_values()
+        .filter(|group| group.len() > 1)
+        .map(|mut group| {
+            group.sort_by_key(|s| std::cmp::Reverse(s.updated_at));
+            group
+        })
+        .collect();
+    Duplicates {
+        names,
+        ids: ids.into_iter().collect(),
+    }
+}
+
+/// Keeps the first script of each name group and renames every other one to
+/// `<name>-<short id>` and archives it, so nothing is deleted. A script listed
+/// under several names is updated in place, which leaves it a single index
+/// entry. Returns the renames as `(old name, new name)`.
+pub fn repair_duplicates(
+    storage: &dyn StorageBackend,
+    duplicates: &Duplicates,
+) -> Result<Vec<(String, String)>> {
+    for id in &duplicates.ids {
+        storage.update_script(&storage.load_script(id)?)?;
+    }
+
+    let mut taken: HashSet<String> = storage
+        .list_scripts()?
+        .into_iter()
+        .map(|s| s.name)
+        .collect();
+    let mut renamed = Vec::new();
+    for group in &duplicates.names {
+        for extra in &group[1..] {
+            let short_id: String = extra.id.chars().take(8).collect();
+            let base = format!("{}-{}", extra.name, short_id);
+            let mut new_name = base.clone();
+            let mut n = 2;
+            while taken.contains(&new_name) {
+                new_name = format!("{}-{}", base, n);
+                n += 1;
+            }
+            let mut script = extra.clone();
+            script.name = new_name.clone();
+            script.archived = true;
+            storage.update_script(&script)?;
+            taken.insert(new_name.clone());
+            renamed.push((extra.name.clone(), new_name));
+        }
+        // The name may have been indexed under one of the extras.
+        storage.update_script(&group[0])?;
+    }
+    Ok(renamed)
+}
+
+fn check_duplicates_doctor(config: &Config, fix: bool) -> Result<()> {
+    let storage = config.get_storage_backend()?;
+    let scripts = match storage.list_scripts() {
+        Ok(scripts) => scripts,
+        Err(e) => {
+            println!("    {} could not list scripts ({})", "FAIL".red(), e);
+            return Ok(());
+        }
+    };
+    let duplicates = find_duplicates(&scripts);
+    if duplicates.is_empty() {
+        println!("    {}", "none".green());
+        return Ok(());
+    }
+
+    for group in &duplicates.names {
+        let ids: Vec<String> = group
+            .iter()
+            .map(|s| s.id.chars().take(8).collect())
+            .collect();
+        println!(
+            "    {} {}: {} scripts share this name ({})",
+            "WARN".yellow(),
+            group[0].name,
+            group.len(),
+            ids.join(", ")
+        );
+    }
+    for id in &duplicates.ids {
+        println!(
+            "    {} id {} is listed under more than one name",
+            "WARN".yellow(),
+            id
+        );
+    }
+
+    if !fix {
+        println!(
+            "    Run {} to keep the most recently updated script and archive the rest.",
+            "sv doctor --fix".cyan()
+        );
+        return Ok(());
+    }
+
+    config.ensure_writable()?;
+    for (old, new) in repair_duplicates(storage.as_ref(), &duplicates)? {
+        println!(
+            "    {} Renamed a duplicate {} to {} and archived it",
+            "✓".green().bold(),
+            old.yellow(),
+            new.yellow()
+        );
+    }
+    if !duplicates.ids.is_empty() {
+        println!(
+            "    {} Re-saved {} script(s) listed under several names",
+            "✓".green().bold(),
+            duplicates.ids.len()
+        );
+    }
+    Ok(())
+}
+
 fn check_ssh_doctor() {
     print!("    ssh binary... ");
     if which::which("ssh").is_ok() {
@@ -186,3 +566,323 @@ pub fn check_status() -> Result<()> {
     );
     Ok(())
 }
+
+pub fn open_config(args: OpenConfigArgs) -> Result<()> {
+    let path = Config::config_path()?;
+    if !path.exists() {
+        Config::load()?;
+    }
+
+    if !args.edit {
+        println!("{}", path.display());
+        return Ok(());
+    }
+
+    let editor = std::env::var("EDITOR")
+        .or_else(|_| std::env::var("VISUAL"))
+        .unwrap_or_else(|_| "vi".to_string());
+    let mut parts = editor.split_whitespace();
+    let editor_cmd = parts.next().unwrap_or("vi").to_string();
+    let editor_args: Vec<String> = parts.map(|s| s.to_string()).collect();
+
+    // Edit a copy next to the live file, so a valid edit can be swapped in
+    // with a rename and an invalid one never touches the original.
+    let original = fs::read_to_string(&path).context("Failed to read config file")?;
+    let format = ConfigFormat::from_path(&path);
+    let mut edit_path = path.clone().into_os_string();
+    edit_path.push(".edit");
+    let edit_path = std::path::PathBuf::from(edit_path);
+    fs::write(&edit_path, &original).context("Failed to write temporary file")?;
+
+    let status = std::process::Command::new(&editor_cmd)
+        .args(&editor_args)
+        .arg(&edit_path)
+        .status();
+    let edited = fs::read_to_string(&edit_path);
+
+    let result = match status {
+        Err(e) => Err(anyhow!("Failed to open editor '{}': {}", editor_cmd, e)),
+        Ok(status) if !status.success() => {
+            println!("Edit cancelled");
+            Ok(false)
+        }
+        Ok(_) => {
+            let edited = edited.context("Failed to read edited file")?;
+            if edited == original {
+                println!("No changes made");
+                Ok(false)
+            } else {
+                format
+                    .parse(&edited)
+                    .map(|_| true)
+                    .map_err(|e| anyhow!("Invalid config, keeping the original: {}", e))
+            }
+        }
+    };
+
+    match result {
+        Ok(true) => {
+            fs::rename(&edit_path, &path).context("Failed to replace config file")?;
+            println!("{} Config updated: {}", "✓".green().bold(), path.display());
+            Ok(())
+        }
+        other => {
+            let _ = fs::remove_file(&edit_path);
+            other.map(|_| ())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::script::ScriptLanguage;
+
+    fn make_script(content: &str, language: ScriptLanguage) -> Script {
+        Script::new("check".to_string(), content.to_string(), language)
+    }
+
+    #[test]
+    fn test_humanize_bucket_boundaries() {
+        let now: DateTime<Utc> = "2026-06-01T12:00:00Z".parse().unwrap();
+        let ago = |secs: i64| humanize_since(now - chrono::Duration::seconds(secs), now);
+
+        assert_eq!(ago(-30), "just now");
+        assert_eq!(ago(0), "just now");
+        assert_eq!(ago(9), "just now");
+        assert_eq!(ago(10), "10 seconds ago");
+        assert_eq!(ago(59), "59 seconds ago");
+        assert_eq!(ago(60), "1 minute ago");
+        assert_eq!(ago(3599), "59 minutes ago");
+        assert_eq!(ago(3600), "1 hour ago");
+        assert_eq!(ago(86_399), "23 hours ago");
+        assert_eq!(ago(86_400), "1 day ago");
+        assert_eq!(ago(7 * 86_400 - 1), "6 days ago");
+        assert_eq!(ago(7 * 86_400), "1 week ago");
+        assert_eq!(ago(30 * 86_400 - 1), "4 weeks ago");
+        assert_eq!(ago(30 * 86_400), "1 month ago");
+        assert_eq!(ago(400 * 86_400), "13 months ago");
+    }
+
+    #[test]
+    fn test_parse_age() {
+        assert_eq!(parse_age("30d").unwrap(), chrono::Duration::days(30));
+        assert_eq!(parse_age("2w").unwrap(), chrono::Duration::days(14));
+        assert_eq!(parse_age("6mo").unwrap(), chrono::Duration::days(180));
+        assert_eq!(parse_age("90m").unwrap(), chrono::Duration::minutes(90));
+        assert!(parse_age("30").is_err());
+        assert!(parse_age("d").is_err());
+        assert!(parse_age("3y").is_err());
+    }
+
+    #[test]
+    fn test_parse_duration_units() {
+        let secs = |s| parse_duration(s).unwrap().as_secs();
+        assert_eq!(secs("45"), 45);
+        assert_eq!(secs("30s"), 30);
+        assert_eq!(secs("5m"), 300);
+        assert_eq!(secs("2h"), 7200);
+        assert_eq!(secs("7d"), 604_800);
+        assert_eq!(secs("2w"), 1_209_600);
+        assert_eq!(secs("1mo"), 2_592_000);
+        assert_eq!(secs("0"), 0);
+    }
+
+    #[test]
+    fn test_parse_duration_errors() {
+        let err = |s| parse_duration(s).unwrap_err();
+        assert_eq!(err(""), "'' does not start with a number");
+        assert_eq!(err("m"), "'m' does not start with a number");
+        assert_eq!(err("-5s"), "'-5s' does not start with a number");
+        assert_eq!(
+            err("1.5h"),
+            "unknown unit in '1.5h' (use s, m, h, d, w, or mo)"
+        );
+        assert_eq!(
+            err("5 m"),
+            "unknown unit in '5 m' (use s, m, h, d, w, or mo)"
+        );
+        assert_eq!(
+            err("10y"),
+            "unknown unit in '10y' (use s, m, h, d, w, or mo)"
+        );
+        assert_eq!(
+            err("99999999999999999999"),
+            "'99999999999999999999' is too large"
+        );
+        assert_eq!(
+            err("999999999999999999mo"),
+            "'999999999999999999mo' is too large"
+        );
+    }
+
+    #[test]
+    fn test_check_script_missing_python_fails() {
+        let script = make_script("print('hi')", ScriptLanguage::Python);
+        let check = check_script(&script, |program| program != "python3");
+        assert_eq!(check.level, CheckLevel::Fail);
+        assert_eq!(
+            check.detail.as_deref(),
+            Some("interpreter 'python3' not found")
+        );
+    }
+
+    #[test]
+    fn test_check_script_unknown_language_fails() {
+        let script = make_script("echo hi", ScriptLanguage::Unknown);
+        let check = check_script(&script, |_| true);
+        assert_eq!(check.level, CheckLevel::Fail);
+        assert_eq!(
+            check.detail.as_deref(),
+            Some("unknown language; use --interpreter or re-save")
+        );
+    }
+
+    #[test]
+    fn test_check_script_dangerous_warns() {
+        let script = make_script("echo start\ndd if=/dev/zero of=out", ScriptLanguage::Bash);
+        let check = check_script(&script, |_| true);
+        assert_eq!(check.level, CheckLevel::Warn);
+        assert_eq!(check.detail.as_deref(), Some("dangerous patterns: dd if="));
+    }
+
+    #[test]
+    fn test_check_script_ok() {
+        let script = make_script("echo hi", ScriptLanguage::Shell);
+        let check = check_script(&script, |program| program == "sh");
+        assert_eq!(check.level, CheckLevel::Ok);
+        assert!(check.detail.is_none());
+    }
+
+    #[test]
+    fn test_read_script_text_rejects_non_utf8() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let good = dir.path().join("good.sh");
+        let bad = dir.path().join("bad.sh");
+        fs::write(&good, "echo héllo\n").unwrap();
+        fs::write(&bad, b"echo \xff\xfe\n").unwrap();
+
+        assert_eq!(read_script_text(&good).unwrap(), "echo héllo\n");
+        let err = read_script_text(&bad).unwrap_err().to_string();
+        assert!(err.ends_with("bad.sh: file is not valid UTF-8; binary scripts are unsupported"));
+    }
+
+    #[test]
+    fn test_color_success_rate_thresholds() {
+        for (rate, color) in [
+            (100.0, Color::Green),
+            (90.1, Color::Green),
+            (90.0, Color::Yellow),
+            (70.1, Color::Yellow),
+            (70.0, Color::Red),
+            (0.0, Color::Red),
+        ] {
+            assert_eq!(color_success_rate(rate).fgcolor, Some(color), "{rate}");
+        }
+        assert_eq!(&*color_success_rate(87.25), "87.2%");
+    }
+
+    #[test]
+    fn test_truncate_ellipsis() {
+        assert_eq!(truncate_ellipsis("deploy", 6), "deploy");
+        assert_eq!(truncate_ellipsis("deploy-prod", 6), "deplo…");
+        assert_eq!(truncate_ellipsis("déploiement", 4), "dép…");
+        assert_eq!(truncate_ellipsis("deploy", 1), "…");
+        assert_eq!(truncate_ellipsis("deploy", 0), "");
+    }
+
+    #[test]
+    fn test_human_size() {
+        assert_eq!(human_size(0), "0 B");
+        assert_eq!(human_size(1023), "1023 B");
+        assert_eq!(human_size(1024), "1.0 KB");
+        assert_eq!(human_size(1536), "1.5 KB");
+        assert_eq!(human_size(5 * 1024 * 1024), "5.0 MB");
+    }
+
+    #[test]
+    fn test_find_and_repair_duplicate_names() {
+        use crate::storage::local::LocalStorage;
+
+        let dir = tempfile::TempDir::new().unwrap();
+        let vault = dir.path().to_path_buf();
+        let older = make_script("echo old\n", ScriptLanguage::Bash);
+        let mut newer = make_script("echo new\n", ScriptLanguage::Bash);
+        {
+            let storage = LocalStorage::new(vault.clone()).unwrap();
+            let mut older = older.clone();
+            older.name = "deploy".to_string();
+            storage.save_script(&older).unwrap();
+            newer.name = "deploy-copy".to_string();
+            storage.save_script(&newer).unwrap();
+        }
+        // Edit the copy on disk so both scripts are named "deploy", with the
+        // index still pointing "deploy" at the older one.
+        newer.name = "deploy".to_string();
+        newer.updated_at = older.updated_at + chrono::Duration::hours(1);
+        fs::write(
+            vault.join(format!("{}.json", newer.id)),
+            serde_json::to_string(&newer).unwrap(),
+        )
+        .unwrap();
+
+        let storage = LocalStorage::new(vault).unwrap();
+        let duplicates = find_duplicates(&storage.list_scripts().unwrap());
+        assert_eq!(duplicates.names.len(), 1);
+        let ids: Vec<&str> = duplicates.names[0].iter().map(|s| s.id.as_str()).collect();
+        assert_eq!(ids, [newer.id.as_str(), older.id.as_str()]);
+
+        let renamed = repair_duplicates(&storage, &duplicates).unwrap();
+        let expected = format!("deploy-{}", &older.id[..8]);
+        assert_eq!(renamed, [("deploy".to_string(), expected.clone())]);
+
+        assert_eq!(storage.load_script_by_name("deploy").unwrap().id, newer.id);
+        let archived = storage.load_script_by_name(&expected).unwrap();
+        assert_eq!(archived.id, older.id);
+        assert!(archived.archived);
+        assert_eq!(storage.list_scripts().unwrap().len(), 2);
+        assert!(find_duplicates(&storage.list_scripts().unwrap()).is_empty());
+    }
+
+    #[test]
+    fn test_repair_duplicate_id_keeps_script_on_disk() {
+        use crate::storage::local::LocalStorage;
+
+        let dir = tempfile::TempDir::new().unwrap();
+        let vault = dir.path().to_path_buf();
+        let script = make_script("echo hi\n", ScriptLanguage::Bash);
+        LocalStorage::new(vault.clone())
+            .unwrap()
+            .save_script(&script)
+            .unwrap();
+        let index = serde_json::json!({
+            "entries": { script.name.clone(): script.id.clone(), "stale": script.id.clone() }
+        });
+        fs::write(vault.join("index.json"), index.to_string()).unwrap();
+
+        let storage = LocalStorage::new(vault.clone()).unwrap();
+        let duplicates = Duplicates {
+            names: Vec::new(),
+            ids: vec![script.id.clone()],
+        };
+        repair_duplicates(&storage, &duplicates).unwrap();
+        assert!(vault.join(format!("{}.json", script.id)).exists());
+        assert_eq!(
+            storage.load_script_by_name(&script.name).unwrap().id,
+            script.id
+        );
+        assert!(storage.load_script_by_name("stale").is_err());
+        assert_eq!(storage.list_scripts().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_find_duplicate_ids() {
+        let script = make_script("echo hi\n", ScriptLanguage::Bash);
+        let mut other = make_script("echo other\n", ScriptLanguage::Bash);
+        other.name = "other".to_string();
+        let duplicates = find_duplicates(&[script.clone(), other, script.clone()]);
+        assert_eq!(duplicates.ids, [script.id]);
+        // A repeated id alone is not also a duplicate name.
+        assert!(duplicates.names.is_empty());
+    }
+}
@@ -1,58 +1,86 @@
+use crate::audit::{self, AuditAction};
 use crate::cli::ExportArgs;
 use crate::cli::*;
 use crate::config::Config;
+use crate::constants::SUPPORTED_EXTENSIONS;
 use crate::context;
-use crate::script::{Script, ScriptLanguage, ScriptSummary, SyncStatus};
-use crate::storage::ListOptions;
+use crate::execution::load_history;
+use crate::script::{
+    ExecutionRecord, PhaseTimings, Script, ScriptLanguage, ScriptParam, ScriptSummary, SyncState,
+    SyncStatus, Visibility,
+};
+use crate::storage::{ListOptions, StorageBackend};
+use crate::utils::{
+    color_success_rate, human_size, humanize_duration, read_script_text, terminal_width,
+    truncate_ellipsis,
+};
 use anyhow::{Context as _, Result, anyhow};
 use chrono::Utc;
 use colored::*;
-use dialoguer::{Confirm, Input};
+use dialoguer::{Confirm, Input, Select};
 use sha2::{Digest, Sha256};
+use std::collections::HashMap;
 use std::fs;
-use std::path::Path;
+use std::path::{Path, PathBuf};
+
+pub const BUMP_LEVELS: [&str; 3] = ["patch", "minor", "major"];
 
 fn bump_patch_version(version: &str) -> String {
+    bump_version(version, "patch")
+}
+
+/// Bumps one component of a `vMAJOR.MINOR.PATCH` version, zeroing the ones
+/// below it. Versions that don't have three numeric parts get `.1` appended.
+fn bump_version(version: &str, level: &str) -> String {
     let v = version.trim_start_matches('v');
     let parts: Vec<u64> = v.split('.').filter_map(|p| p.parse().ok()).collect();
-    if parts.len() == 3 {
-        format!("v{}.{}.{}", parts[0], parts[1], parts[2] + 1)
-    } else {
-        format!("{}.1", version)
+    if parts.len() != 3 {
+        return format!("{}.1", version);
+    }
+    match level {
+        "major" => format!("v{}.0.0", parts[0] + 1),
+        "minor" => format!("v{}.{}.0", parts[0], parts[1] + 1),
+        _ => format!("v{}.{}.{}", parts[0], parts[1], parts[2] + 1),
     }
 }
 
 pub fn save_script(args: SaveArgs) -> Result<()> {
     let config = Config::load()?;
+    config.ensure_writable()?;
     let storage = config.get_storage_backend()?;
 
-    let script_path = Path::new(&args.file);
-    if !script_path.exists() {
-        return Err(anyhow!("Script file not found: {}", args.file));
+    let (name, content, language) = match (&args.file, &args.from_template) {
+        (_, Some(template)) => read_template(&args, template)?,
+        (Some(file), None) => read_script_file(file, args.name.clone())?,
+        (None, None) => return Err(anyhow!("Specify a script file or --from-template")),
+    };
+    // Checked on the final content, so filled-in templates count too.
+    config.check_script_size(content.len() as u64)?;
+    let existing = storage.load_script_by_name(&name).ok();
+
+    // A language picked by hand on an earlier save survives re-saves that
+    // can't detect one.
+    let language = match (&language, &existing) {
+        (ScriptLanguage::Unknown, Some(ex)) => ex.language.clone(),
+        _ => language,
+    };
+    let (language, bash_features) = refine_shell_language(language, &content);
+    if !bash_features.is_empty() {
+        println!(
+            "{} Uses bash-only syntax ({}); saving as {}",
+            "i".cyan(),
+            bash_features.join(", "),
+            "bash".cyan()
+        );
     }
-
-    let content = fs::read_to_string(script_path).context("Failed to read script file")?;
-
-    let derived_name = script_path
-        .file_stem()
-        .and_then(|s| s.to_str())
-        .ok_or_else(|| anyhow!("Invalid script filename"))?
-        .to_string();
-
-    let name = args.name.clone().unwrap_or(derived_name);
-
-    let extension = script_path
-        .extension()
-        .and_then(|s| s.to_str())
-        .unwrap_or("sh");
-
-    let language = ScriptLanguage::from_extension(extension);
     let mut script = Script::new(name, content, language);
+    let directives = crate::directives::parse(&script.content);
+    for problem in &directives.problems {
+        eprintln!("{} Ignoring {}", "Warning:".yellow().bold(), problem);
+    }
 
     script.context = context::detect_context()?;
 
-    let existing = storage.load_script_by_name(&script.name).ok();
-
     if !args.yes {
         println!("{}", "Saving script to vault...".cyan().bold());
         println!();
@@ -65,11 +93,28 @@ pub fn save_script(args: SaveArgs) -> Result<()> {
         }
         println!();
 
+        script.language = select_language(&script.language, |items, default| {
+            Ok(Select::new()
+                .with_prompt("Language")
+                .items(items)
+                .default(default)
+                .interact()?)
+        })?;
+
         let tags_input: String = if let Some(tags) = args.tags {
             tags
         } else {
+            let current = if directives.tags.is_empty() {
+                existing
+                    .as_ref()
+                    .map(|ex| ex.tags.join(" "))
+                    .unwrap_or_default()
+            } else {
+                directives.tags.join(" ")
+            };
             Input::new()
                 .with_prompt("Tags (space-separated)")
+                .with_initial_text(current)
                 .allow_empty(true)
                 .interact_text()?
         };
@@ -81,26 +126,94 @@ pub fn save_script(args: SaveArgs) -> Result<()> {
         script.description = if let Some(desc) = args.description {
             Some(desc)
         } else {
+            let current = directives
+                .description
+                .clone()
+                .or_else(|| existing.as_ref().and_then(|ex| ex.description.clone()))
+                .unwrap_or_default();
             let desc: String = Input::new()
                 .with_prompt("Description (optional)")
+                .with_initial_text(current)
                 .allow_empty(true)
                 .interact_text()?;
             if desc.is_empty() { None } else { Some(desc) }
         };
     } else {
-        if let Some(tags) = args.tags {
-            script.tags = tags.split_whitespace().map(|s| s.to_string()).collect();
-        }
-        script.description = args.description;
+        // Flags left out fall back to the script's own directives, then to
+        // what is already stored, so an unchanged re-save is a no-op rather
+        // than a rewrite that clears tags and description.
+        script.tags = match args.tags {
+            Some(tags) => tags.split_whitespace().map(|s| s.to_string()).collect(),
+            None if !directives.tags.is_empty() => directives.tags.clone(),
+            None => existing
+                .as_ref()
+                .map(|ex| ex.tags.clone())
+                .unwrap_or_default(),
+        };
+        script.description = args
+            .description
+            .or_else(|| directives.description.clone())
+            .or_else(|| existing.as_ref().and_then(|ex| ex.description.clone()));
     }
 
+    script.tags = with_default_tags(script.tags, &config.default_tags);
+
     if let Some(username) = &config.username {
         script.author = username.clone();
     }
 
+    script.pre_run = args
+        .pre_run
+        .or_else(|| existing.as_ref().and_then(|ex| ex.pre_run.clone()));
+    script.post_run = args
+        .post_run
+        .or_else(|| existing.as_ref().and_then(|ex| ex.post_run.clone()));
+    script.params = if !args.params.is_empty() {
+        args.params
+    } else if !directives.params.is_empty() {
+        directives.params
+    } else {
+        existing
+            .as_ref()
+            .map(|ex| ex.params.clone())
+            .unwrap_or_default()
+    };
+    script.env = if !args.script_env.is_empty() {
+        args.script_env.into_iter().collect()
+    } else if !directives.env.is_empty() {
+        directives.env.into_iter().collect()
+    } else {
+        existing
+            .as_ref()
+            .map(|ex| ex.env.clone())
+            .unwrap_or_default()
+    };
+    script.visibility = match args.visibility.or(directives.visibility) {
+        Some(visibility) => visibility,
+        None => match existing {
+            Some(ref ex) => ex.visibility.clone(),
+            None => config.default_visibility()?,
+        },
+    };
+    if !args.success_codes.is_empty() {
+        script.success_exit_codes = args.success_codes;
+    } else if !directives.success_codes.is_empty() {
+        script.success_exit_codes = directives.success_codes;
+    } else if let Some(ref ex) = existing {
+        script.success_exit_codes = ex.success_exit_codes.clone();
+    }
+
     if let Some(ref ex) = existing {
         let content_changed = ex.metadata.hash != script.metadata.hash;
-        let meta_changed = ex.tags != script.tags || ex.description != script.description;
+        let meta_changed = ex.tags != script.tags
+            || ex.language != script.language
+            || ex.description != script.description
+            || ex.pre_run != script.pre_run
+            || ex.post_run != script.post_run
+            || ex.params != script.params
+            || ex.env != script.env
+            || ex.success_exit_codes != script.success_exit_codes
+            || ex.visibility != script.visibility;
 
         if !content_changed && !meta_changed {
             println!("{} No changes: {}", "i".cyan(), script.name.yellow());
@@ -108,7 +221,7 @@ pub fn save_script(args: SaveArgs) -> Result<()> {
         }
 
         if content_changed {
-            script.version = bump_patch_version(&ex.version);
+            script.version = bump_version(&ex.version, args.bump.as_deref().unwrap_or("patch"));
         } else {
             script.version = ex.version.clone();
         }
@@ -121,6 +234,10 @@ pub fn save_script(args: SaveArgs) -> Result<()> {
         script.metadata.last_run = ex.metadata.last_run;
         script.metadata.last_run_by = ex.metadata.last_run_by.clone();
         script.metadata.avg_runtime_ms = ex.metadata.avg_runtime_ms;
+        script.archived = ex.archived;
+        script.pinned = ex.pinned;
+        script.trusted = ex.trusted;
+        script.safety_acknowledged = ex.safety_acknowledged && !content_changed;
 
         script.sync_state = ex.sync_state.clone();
         if content_changed || meta_changed {
@@ -133,10 +250,45 @@ pub fn save_script(args: SaveArgs) -> Result<()> {
             }
         }
     }
+
+    if !args.force {
+        let scripts = storage.list_scripts()?;
+        if let Some(duplicate) = find_by_hash(&scripts, &script.metadata.hash, &script.name) {
+            eprintln!(
+                "{} {} has identical content. Run it with {} or rename it with {} instead.",
+                "Warning:".yellow().bold(),
+                duplicate.name.yellow(),
+                format!("sv run {}", duplicate.name).cyan(),
+                format!("sv rename {} {}", duplicate.name, script.name).cyan()
+            );
+            if args.yes {
+                return Err(anyhow!(
+                    "Not saved: {} duplicates {}. Use --force to save anyway.",
+                    script.name,
+                    duplicate.name
+                ));
+            }
+            let proceed = Confirm::new()
+                .with_prompt("Save a duplicate anyway?")
+                .default(false)
+                .interact()?;
+            if !proceed {
+                println!("Save cancelled.");
+                return Ok(());
+            }
+        }
+    }
+
     storage.save_script(&script)?;
 
     let store = crate::versions::VersionStore::new(&Config::vault_dir()?);
     store.save_version(&script)?;
+    audit::record(
+        &config,
+        AuditAction::Save,
+        &script,
+        Some(script.version.clone()),
+    )?;
 
     println!();
     println!(
@@ -153,8 +305,237 @@ pub fn save_script(args: SaveArgs) -> Result<()> {
     Ok(())
 }
 
+/// Appends the configured `default_tags` to `tags`, leaving out any that are
+/// already there.
+fn with_default_tags(mut tags: Vec<String>, defaults: &[String]) -> Vec<String> {
+    for tag in defaults {
+        if !tags.contains(tag) {
+            tags.push(tag.clone());
+        }
+    }
+    tags
+}
+
+/// Asks which language to save as, with the detected one preselected.
+/// `pick` shows the choices and returns the index chosen.
+fn select_language(
+    detected: &ScriptLanguage,
+    pick: impl FnOnce(&[String], usize) -> Result<usize>,
+) -> Result<ScriptLanguage> {
+    let items: Vec<String> = ScriptLanguage::ALL
+        .iter()
+        .map(|lang| {
+            if lang == detected {
+                format!("{} (detected)", lang)
+            } else {
+                lang.to_string()
+            }
+        })
+        .collect();
+    let default = ScriptLanguage::ALL
+        .iter()
+        .position(|lang| lang == detected)
+        .unwrap_or(0);
+    let choice = pick(&items, default)?;
+    ScriptLanguage::ALL
+        .get(choice)
+        .cloned()
+        .ok_or_else(|| anyhow!("Invalid language choice: {}", choice))
+}
+
+/// Finds a script other than `name` whose content hash matches.
+pub fn find_by_hash<'a>(scripts: &'a [Script], hash: &str, name: &str) -> Option<&'a Script> {
+    scripts
+        .iter()
+        .find(|s| s.metadata.hash == hash && s.name != name)
+}
+
+fn read_script_file(file: &str, name: Option<String>) -> Result<(String, String, ScriptLanguage)> {
+    let script_path = Path::new(file);
+    if !script_path.exists() {
+        return Err(anyhow!("Script file not found: {}", file));
+    }
+
+    let content = read_script_text(script_path)?;
+
+    let derived_name = script_path
+        .file_stem()
+        .and_then(|s| s.to_str())
+        .ok_or_else(|| anyhow!("Invalid script filename"))?
+        .to_string();
+
+    let extension = script_path
+        .extension()
+        .and_then(|s| s.to_str())
+        .unwrap_or("sh");
+
+    Ok((
+        name.unwrap_or(derived_name),
+        content,
+        ScriptLanguage::from_extension(extension),
+    ))
+}
+
+/// A `.sh` extension only says "some shell", and `sh` fails on bash-only
+/// syntax. Such scripts are upgraded to bash unless a shebang names a shell
+/// other than `sh` or `dash`. Returns the features that caused the upgrade.
+fn refine_shell_language(
+    language: ScriptLanguage,
+    content: &str,
+) -> (ScriptLanguage, Vec<&'static str>) {
+    if language != ScriptLanguage::Shell {
+        return (language, Vec::new());
+    }
+    if ScriptLanguage::shebang_program(content).is_some_and(|p| p != "sh" && p != "dash") {
+        return (language, Vec::new());
+    }
+    match ScriptLanguage::bash_features(content) {
+        features if features.is_empty() => (language, features),
+        features => (ScriptLanguage::Bash, features),
+    }
+}
+
+/// Instantiates a template for `sv save --from-template`. `name` and
+/// `description` placeholders default to the script's name and description.
+fn read_template(args: &SaveArgs, template: &str) -> Result<(String, String, ScriptLanguage)> {
+    let name = args
+        .name
+        .clone()
+        .ok_or_else(|| anyhow!("--from-template requires --name"))?;
+    let template = crate::templates::TemplateStore::new(&Config::data_dir()?).load(template)?;
+
+    let mut values: HashMap<String, String> = args.vars.iter().cloned().collect();
+    values.entry("name".to_string()).or_insert(name.clone());
+    if let Some(ref desc) = args.description {
+        values
+            .entry("description".to_string())
+            .or_insert(desc.clone());
+    }
+
+    let content = crate::templates::instantiate(&template, values, !args.yes)?;
+    Ok((name, content, template.language))
+}
+
+#[derive(Debug)]
+struct ValidationReport {
+    language: ScriptLanguage,
+    extension_language: Option<ScriptLanguage>,
+    shebang_language: Option<ScriptLanguage>,
+    dangerous: Vec<&'static str>,
+    size_bytes: usize,
+    line_count: usize,
+}
+
+fn validate_content(extension: Option<&str>, content: &str) -> ValidationReport {
+    let extension_language = extension
+        .map(ScriptLanguage::from_extension)
+        .filter(|l| *l != ScriptLanguage::Unknown);
+    let shebang_language = ScriptLanguage::from_shebang(content);
+    let language = extension_language
+        .clone()
+        .or_else(|| shebang_language.clone())
+        .unwrap_or(ScriptLanguage::Unknown);
+
+    let script = Script::new(String::new(), content.to_string(), language.clone());
+
+    ValidationReport {
+        language,
+        extension_language,
+        shebang_language,
+        dangerous: script.dangerous_patterns(),
+        size_bytes: script.metadata.size_bytes,
+        line_count: script.metadata.line_count,
+    }
+}
+
+pub fn validate_file(args: ValidateArgs) -> Result<()> {
+    let script_path = Path::new(&args.file);
+    if !script_path.exists() {
+        return Err(anyhow!("Script file not found: {}", args.file));
+    }
+
+    let content = read_script_text(script_path)?;
+    let name = match args.name {
+        Some(name) => name,
+        None => script_path
+            .file_stem()
+            .and_then(|s| s.to_str())
+            .ok_or_else(|| anyhow!("Invalid script filename"))?
+            .to_string(),
+    };
+    let extension = script_path.extension().and_then(|s| s.to_str());
+    let report = validate_content(extension, &content);
+
+    let config = Config::load()?;
+    let storage = config.get_storage_backend()?;
+    let existing = storage.load_script_by_name(&name).ok();
+
+    println!("{}", args.file.cyan().bold());
+    println!();
+
+    let language_note = match (&report.extension_language, &report.shebang_language) {
+        (Some(ext), Some(shebang)) if ext != shebang => {
+            format!("(extension; shebang says {})", shebang).yellow()
+        }
+        (Some(_), _) => "(from extension)".dimmed(),
+        (None, Some(_)) => "(from shebang)".dimmed(),
+        (None, None) => "(not recognized)".red(),
+    };
+    println!(
+        "  {}:  {} {}",
+        "Language".bold(),
+        report.language.to_string().green(),
+        language_note
+    );
+    let size_note = match config.check_script_size(report.size_bytes as u64) {
+        Ok(()) => "".normal(),
+        Err(_) => format!(
+            "(over the {} byte limit; sv save will reject it)",
+            config.max_script_size_bytes
+        )
+        .red(),
+    };
+    println!(
+        "  {}:      {} bytes {}",
+        "Size".bold(),
+        report.size_bytes,
+        size_note
+    );
+    println!("  {}:     {}", "Lines".bold(), report.line_count);
+    match existing {
+        Some(ref ex) => println!(
+            "  {}:      {} already exists ({}); saving will update it",
+            "Name".bold(),
+            name.yellow(),
+            ex.version.dimmed()
+        ),
+        None => println!("  {}:      {} is available", "Name".bold(), name.yellow()),
+    }
+
+    println!();
+    if report.dangerous.is_empty() {
+        println!("{} Safety check passed", "✓".green().bold());
+        return Ok(());
+    }
+
+    println!("{}", "Safety check found dangerous patterns:".red().bold());
+    for pattern in &report.dangerous {
+        println!("  - {}", pattern.red());
+    }
+
+    if args.strict {
+        return Err(anyhow!(
+            "Validation failed: {} dangerous pattern(s) found",
+            report.dangerous.len()
+        ));
+    }
+
+    Ok(())
+}
+
 pub fn update_script_from_file(args: UpdateArgs) -> Result<()> {
     let config = Config::load()?;
+    config.ensure_writable()?;
     let storage = config.get_storage_backend()?;
 
     let script_path = Path::new(&args.file);
@@ -177,7 +558,8 @@ pub fn update_script_from_file(args: UpdateArgs) -> Result<()> {
         )
     })?;
 
-    let new_content = fs::read_to_string(script_path).context("Failed to read file")?;
+    config.check_script_size(fs::metadata(script_path)?.len())?;
+    let new_content = read_script_text(script_path)?;
 
     let mut hasher = Sha256::new();
     hasher.update(new_content.as_bytes());
@@ -192,6 +574,7 @@ pub fn update_script_from_file(args: UpdateArgs) -> Result<()> {
     existing.version = bump_patch_version(&existing.version);
     existing.content = new_content.clone();
     existing.metadata.hash = new_hash;
+    existing.safety_acknowledged = false;
     existing.metadata.size_bytes = new_content.len();
     existing.metadata.line_count = new_content.lines().count();
     existing.updated_at = Utc::now();
@@ -208,6 +591,12 @@ pub fn update_script_from_file(args: UpdateArgs) -> Result<()> {
 
     let store = crate::versions::VersionStore::new(&Config::vault_dir()?);
     store.save_version(&existing)?;
+    audit::record(
+        &config,
+        AuditAction::Save,
+        &existing,
+        Some(existing.version.clone()),
+    )?;
 
     println!(
         "{} Updated: {} {} -> {}",
@@ -220,6 +609,78 @@ pub fn update_script_from_file(args: UpdateArgs) -> Result<()> {
     Ok(())
 }
 
+fn matches_success_rate(script: &Script, filter: &SuccessRateArgs) -> bool {
+    let runs = script.metadata.success_count + script.metadata.failure_count;
+    if filter.has_runs && runs == 0 {
+        return false;
+    }
+    let rate = script.success_rate();
+    if filter.min_success_rate.is_some_and(|min| rate < min) {
+        return false;
+    }
+    if filter.max_success_rate.is_some_and(|max| rate > max) {
+        return false;
+    }
+    true
+}
+
+/// Ids of scripts passing the success-rate filter. Summaries don't carry run
+/// counts, so this reads the full scripts.
+fn success_rate_ids(
+    storage: &dyn crate::storage::StorageBackend,
+    filter: &SuccessRateArgs,
+) -> Result<std::collections::HashSet<String>> {
+    Ok(storage
+        .list_scripts()?
+        .iter()
+        .filter(|s| matches_success_rate(s, filter))
+        .map(|s| s.id.clone())
+        .collect())
+}
+
+fn matches_find_filters(s: &ScriptSummary, args: &FindArgs) -> bool {
+    if let Some(ref query) = args.query {
+        let q = query.to_lowercase();
+        let matches = s.name.to_lowercase().contains(&q)
+            || s.description
+                .as_ref()
+                .map(|d| d.to_lowercase().contains(&q))
+                .unwrap_or(false)
+            || s.tags.iter().any(|t| t.to_lowercase().contains(&q));
+        if !matches {
+            return false;
+        }
+    }
+
+    if args.pinned && !s.pinned {
+        return false;
+    }
+
+    if args.not_run && s.use_count > 0 {
+        return false;
+    }
+
+    if let Some(age) = args.stale
+        && s.last_run.is_some_and(|run| run > Utc::now() - age)
+    {
+        return false;
+    }
+
+    if let Some(ref tag) = args.tag
+        && !s.tags.iter().any(|t| t == tag)
+    {
+        return false;
+    }
+
+    if let Some(ref lang) = args.language
+        && s.language != *lang
+    {
+        return false;
+    }
+
+    true
+}
+
 pub fn find_scripts(args: FindArgs) -> Result<()> {
     let config = Config::load()?;
     let storage = config.get_storage_backend()?;
@@ -233,38 +694,12 @@ pub fn find_scripts(args: FindArgs) -> Result<()> {
     let summaries = storage.list_summaries(&ListOptions {
         limit: usize::MAX,
         offset: 0,
+        include_archived: args.include_archived,
     })?;
 
     let mut filtered: Vec<&ScriptSummary> = summaries
         .iter()
-        .filter(|s| {
-            if let Some(ref query) = args.query {
-                let q = query.to_lowercase();
-                let matches = s.name.to_lowercase().contains(&q)
-                    || s.description
-                        .as_ref()
-                        .map(|d| d.to_lowercase().contains(&q))
-                        .unwrap_or(false)
-                    || s.tags.iter().any(|t| t.to_lowercase().contains(&q));
-                if !matches {
-                    return false;
-                }
-            }
-
-            if let Some(ref tag) = args.tag {
-                if !s.tags.iter().any(|t| t == tag) {
-                    return false;
-                }
-            }
-
-            if let Some(ref lang) = args.language {
-                if s.language.to_string() != *lang {
-                    return false;
-                }
-            }
-
-            true
-        })
+        .filter(|s| matches_find_filters(s, &args))
         .collect();
 
     if let Some(ref ctx) = current_ctx {
@@ -277,53 +712,100 @@ pub fn find_scripts(args: FindArgs) -> Result<()> {
         filtered.retain(|s| matching_ids.contains(&s.id));
     }
 
+    if args.success.is_active() {
+        let ids = success_rate_ids(storage.as_ref(), &args.success)?;
+        filtered.retain(|s| ids.contains(&s.id));
+    }
+
     if args.team {
         tracing::debug!("--team filter requested but visibility not in summary; skipping");
     }
 
     if args.recent {
-        filtered.sort_by(|a, b| b.last_run.cmp(&a.last_run));
+        filtered.sort_by_key(|s| std::cmp::Reverse(s.last_run));
     } else {
         filtered.sort_by(|a, b| a.name.cmp(&b.name));
     }
 
+    if args.count {
+        println!("{}", filtered.len());
+        return Ok(());
+    }
+
+    if let Some(ref tag) = args.tag_all {
+        return tag_all(&config, storage.as_ref(), &filtered, tag, args.yes);
+    }
+
+    if args.porcelain {
+        for script in &filtered {
+            println!("{}", porcelain_line(script));
+        }
+        return Ok(());
+    }
+
+    if args.json_lines {
+        return match write_json_lines(storage.as_ref(), &filtered, std::io::stdout().lock()) {
+            Err(e)
+                if e.downcast_ref::<std::io::Error>()
+                    .is_some_and(|e| e.kind() == std::io::ErrorKind::BrokenPipe) =>
+            {
+                Ok(())
+            }
+            result => result,
+        };
+    }
+
     if filtered.is_empty() {
         println!("No scripts found matching your criteria.");
         return Ok(());
     }
 
+    let records = load_history()?;
+    let last = last_runs(&records);
+    let shown = &filtered[..filtered.len().min(20)];
+    let longest_name = shown.iter().map(|s| s.name.chars().count()).max();
+    let layout = find_layout(terminal_width(), longest_name.unwrap_or(0));
+
     println!("{}", "Scripts".cyan().bold());
     println!();
     println!(
-        "{:<30} {:<10} {:<8} {:<20}",
+        "    {:<name$} {:<10} {:<8} {:<8} {}",
         "NAME".bold(),
         "VERSION".bold(),
         "USES".bold(),
-        "LAST RUN".bold()
+        "SUCCESS".bold(),
+        "LAST RUN".bold(),
+        name = layout.name
     );
-    println!("{}", "─".repeat(70).dimmed());
+    println!("{}", "─".repeat(layout.rule).dimmed());
 
-    for script in filtered.iter().take(20) {
+    for script in shown {
         let last_run = match script.last_run {
-            Some(run) => {
-                let delta = chrono::Utc::now() - run;
-                if delta.num_days() > 0 {
-                    format!("{} days ago", delta.num_days())
-                } else if delta.num_hours() > 0 {
-                    format!("{} hours ago", delta.num_hours())
-                } else {
-                    format!("{} minutes ago", delta.num_minutes())
-                }
-            }
+            Some(run) => humanize_duration(run),
             None => "Never".dimmed().to_string(),
         };
+        let success = if script.success_count + script.failure_count > 0 {
+            color_success_rate(script.success_rate())
+        } else {
+            "-".dimmed()
+        };
+
+        let name = if layout.truncate {
+            truncate_ellipsis(&script.name, layout.name)
+        } else {
+            script.name.clone()
+        };
 
         println!(
-            "{:<30} {:<10} {:<8} {:<20}",
-            script.name.yellow(),
+            "{} {} {:<name_width$} {:<10} {:<8} {:<8} {}",
+            pin_marker(script.pinned),
+            run_status_glyph(last.get(script.id.as_str()).copied()),
+            name.yellow(),
             script.version.dimmed(),
             script.use_count.to_string().green(),
-            last_run
+            success,
+            last_run,
+            name_width = layout.name
         );
     }
 
@@ -335,6 +817,185 @@ pub fn find_scripts(args: FindArgs) -> Result<()> {
     Ok(())
 }
 
+/// NAME column width in `sv find` when the terminal width is unknown.
+const FIND_NAME_WIDTH: usize = 30;
+/// Narrowest NAME column; below this, long names overflow instead.
+const FIND_MIN_NAME_WIDTH: usize = 12;
+/// Everything on a `sv find` row except the name: the pin and status glyphs,
+/// version, uses, success, and a last run such as "11 months ago".
+const FIND_FIXED_WIDTH: usize = 4 + 1 + 10 + 1 + 8 + 1 + 8 + 1 + 13;
+
+/// Column sizes for the `sv find` table.
+#[derive(Debug, PartialEq)]
+struct FindLayout {
+    name: usize,
+    /// Whether names longer than the column are cut with an ellipsis.
+    truncate: bool,
+    rule: usize,
+}
+
+/// Fits the NAME column to the longest name and the terminal. Without a
+/// known width (piped output) names are never cut, so scripts can still
+/// match them.
+fn find_layout(term_width: Option<usize>, longest_name: usize) -> FindLayout {
+    let Some(width) = term_width else {
+        return FindLayout {
+            name: FIND_NAME_WIDTH,
+            truncate: false,
+            rule: FIND_FIXED_WIDTH + FIND_NAME_WIDTH,
+        };
+    };
+    let wanted = longest_name.max("NAME".len());
+    let name = wanted
+        .min(width.saturating_sub(FIND_FIXED_WIDTH))
+        .max(wanted.min(FIND_MIN_NAME_WIDTH));
+    FindLayout {
+        name,
+        truncate: true,
+        rule: (FIND_FIXED_WIDTH + name).min(width),
+    }
+}
+
+/// `sv find --json-lines`: loads and writes one full script at a time, so
+/// only the summaries are held in memory however large the vault is.
+fn write_json_lines(
+    storage: &dyn StorageBackend,
+    matches: &[&ScriptSummary],
+    mut out: impl std::io::Write,
+) -> Result<()> {
+    for summary in matches {
+        let script = storage.load_script(&summary.id)?;
+        writeln!(out, "{}", serde_json::to_string(&script)?)?;
+    }
+    out.flush()?;
+    Ok(())
+}
+
+/// `sv find --tag-all`: adds `tag` to each matching script that doesn't
+/// already have it, after confirming the count unless `yes` is set.
+fn tag_all(
+    config: &Config,
+    storage: &dyn StorageBackend,
+    matches: &[&ScriptSummary],
+    tag: &str,
+    yes: bool,
+) -> Result<()> {
+    if tag.trim().is_empty() || tag.contains(char::is_whitespace) {
+        return Err(anyhow!("Invalid tag '{}': tags cannot contain spaces", tag));
+    }
+    config.ensure_writable()?;
+
+    let untagged: Vec<&&ScriptSummary> = matches
+        .iter()
+        .filter(|s| !s.tags.iter().any(|t| t == tag))
+        .collect();
+    if untagged.is_empty() {
+        println!(
+            "{} No matching scripts without tag '{}'",
+            "i".cyan(),
+            tag.cyan()
+        );
+        return Ok(());
+    }
+
+    if !yes {
+        for summary in &untagged {
+            println!("  {}", summary.name.yellow());
+        }
+        println!();
+        let proceed = Confirm::new()
+            .with_prompt(format!(
+                "Add tag '{}' to {} script(s)?",
+                tag,
+                untagged.len()
+            ))
+            .default(false)
+            .interact()?;
+        if !proceed {
+            println!("Cancelled");
+            return Ok(());
+        }
+    }
+
+    for summary in &untagged {
+        let mut script = storage.load_script(&summary.id)?;
+        script.tags.push(tag.to_string());
+        script.updated_at = Utc::now();
+        storage.update_script(&script)?;
+        audit::record(
+            config,
+            AuditAction::Save,
+            &script,
+            Some(format!("tagged {}", tag)),
+        )?;
+    }
+
+    println!(
+        "{} Tagged {} script(s) with '{}'",
+        "✓".green().bold(),
+        untagged.len(),
+        tag.cyan()
+    );
+    Ok(())
+}
+
+/// One `sv find --porcelain` line: name, version, language, use count and
+/// last run (RFC 3339 UTC, empty if never run), separated by tabs.
+fn porcelain_line(script: &ScriptSummary) -> String {
+    let last_run = script
+        .last_run
+        .map(|t| t.to_rfc3339_opts(chrono::SecondsFormat::Secs, true))
+        .unwrap_or_default();
+    format!(
+        "{}\t{}\t{}\t{}\t{}",
+        script.name, script.version, script.language, script.use_count, last_run
+    )
+}
+
+fn pin_marker(pinned: bool) -> ColoredString {
+    if pinned { "★".yellow() } else { " ".normal() }
+}
+
+/// Each script's most recent run, keyed by script id, so listings look up the
+/// status once per script instead of rescanning history. Record-only entries
+/// are skipped since they never ran.
+fn last_runs(records: &[ExecutionRecord]) -> HashMap<&str, &ExecutionRecord> {
+    let mut last: HashMap<&str, &ExecutionRecord> = HashMap::new();
+    for record in records.iter().filter(|r| r.was_executed()) {
+        last.entry(record.script_id.as_str())
+            .and_modify(|seen| {
+                if record.executed_at >= seen.executed_at {
+                    *seen = record;
+                }
+            })
+            .or_insert(record);
+    }
+    last
+}
+
+/// ✓ when the last run succeeded, ✗ when it failed, · when there is none.
+fn run_status_glyph(last: Option<&ExecutionRecord>) -> ColoredString {
+    match last {
+        Some(record) if record.was_successful() => "✓".green(),
+        Some(_) => "✗".red(),
+        None => "·".dimmed(),
+    }
+}
+
+/// Pinned scripts always come first; within each group, order by most recent
+/// run or by name.
+fn sort_for_listing(summaries: &mut [ScriptSummary], recent: bool) {
+    summaries.sort_by(|a, b| {
+        b.pinned.cmp(&a.pinned).then_with(|| {
+            if recent {
+                b.last_run.cmp(&a.last_run)
+            } else {
+                a.name.cmp(&b.name)
+            }
+        })
+    });
+}
+
 pub fn list_scripts(args: ListArgs) -> Result<()> {
     let config = Config::load()?;
     let storage = config.get_storage_backend()?;
@@ -342,25 +1003,36 @@ pub fn list_scripts(args: ListArgs) -> Result<()> {
     let opts = ListOptions {
         limit: args.limit,
         offset: args.offset,
+        include_archived: args.include_archived,
     };
 
     let mut summaries = storage.list_summaries(&opts)?;
 
-    if summaries.is_empty() {
+    if summaries.is_empty() && !args.count {
         println!("No scripts saved yet.");
         return Ok(());
     }
 
-    if args.mine {
-        if let Some(ref username) = config.username {
-            let full = storage.list_scripts()?;
-            let mine_ids: std::collections::HashSet<String> = full
-                .iter()
-                .filter(|s| s.author == *username)
-                .map(|s| s.id.clone())
-                .collect();
-            summaries.retain(|s| mine_ids.contains(&s.id));
-        }
+    if args.mine
+        && let Some(ref username) = config.username
+    {
+        let full = storage.list_scripts()?;
+        let mine_ids: std::collections::HashSet<String> = full
+            .iter()
+            .filter(|s| s.author == *username)
+            .map(|s| s.id.clone())
+            .collect();
+        summaries.retain(|s| mine_ids.contains(&s.id));
+    }
+
+    if args.success.is_active() {
+        let ids = success_rate_ids(storage.as_ref(), &args.success)?;
+        summaries.retain(|s| ids.contains(&s.id));
+    }
+
+    if args.count {
+        println!("{}", summaries.len());
+        return Ok(());
     }
 
     if summaries.is_empty() {
@@ -368,29 +1040,137 @@ pub fn list_scripts(args: ListArgs) -> Result<()> {
         return Ok(());
     }
 
-    if args.recent {
-        summaries.sort_by(|a, b| b.last_run.cmp(&a.last_run));
-    } else {
-        summaries.sort_by(|a, b| a.name.cmp(&b.name));
-    }
+    sort_for_listing(&mut summaries, args.recent);
+    let records = load_history()?;
+    let last = last_runs(&records);
 
     println!("{}", "Scripts".cyan().bold());
     println!();
 
-    for summary in &summaries {
-        println!("  {} {}", summary.name.yellow(), summary.version.dimmed());
-        if let Some(desc) = &summary.description {
-            println!("    {}", desc.dimmed());
-        }
+    if args.tree {
+        let scripts = storage.list_scripts()?;
+        for (group, members) in group_for_tree(&summaries, &scripts) {
+            println!(
+                "{} {}",
+                group.bold(),
+                format!("({})", members.len()).dimmed()
+            );
+            for (i, summary) in members.iter().enumerate() {
+                let branch = if i + 1 == members.len() {
+                    "└─"
+                } else {
+                    "├─"
+                };
+                let archived = if summary.archived {
+                    format!(" {}", "[archived]".dimmed())
+                } else {
+                    String::new()
+                };
+                println!(
+                    "  {} {}{} {} {}{}",
+                    branch.dimmed(),
+                    pin_marker(summary.pinned),
+                    run_status_glyph(last.get(summary.id.as_str()).copied()),
+                    summary.name.yellow(),
+                    summary.version.dimmed(),
+                    archived
+                );
+            }
+            println!();
+        }
+        print_vault_footer(storage.as_ref())?;
+        return Ok(());
+    }
+
+    for summary in &summaries {
+        let archived = if summary.archived {
+            format!(" {}", "[archived]".dimmed())
+        } else {
+            String::new()
+        };
+        let lines = if summary.line_count == 1 {
+            "line"
+        } else {
+            "lines"
+        };
+        println!(
+            "{} {} {} {}{}  {}",
+            pin_marker(summary.pinned),
+            run_status_glyph(last.get(summary.id.as_str()).copied()),
+            summary.name.yellow(),
+            summary.version.dimmed(),
+            archived,
+            format!(
+                "{} {}, {}",
+                summary.line_count,
+                lines,
+                human_size(summary.size_bytes as u64)
+            )
+            .dimmed()
+        );
+        if let Some(desc) = &summary.description {
+            println!("    {}", desc.dimmed());
+        }
         if !summary.tags.is_empty() {
             println!("    Tags: {}", summary.tags.join(", ").cyan());
         }
         println!();
     }
 
+    print_vault_footer(storage.as_ref())
+}
+
+fn print_vault_footer(storage: &dyn StorageBackend) -> Result<()> {
+    let metadata = storage.get_metadata()?;
+    println!(
+        "Vault: {} scripts, {}",
+        metadata.total_scripts,
+        human_size(metadata.total_size_bytes)
+    );
     Ok(())
 }
 
+/// Label for the script's project in `sv list --tree`: its git repo, else
+/// the directory it was saved from, else "Global".
+fn tree_group(script: &Script) -> &str {
+    [&script.context.git_repo, &script.context.directory]
+        .into_iter()
+        .flatten()
+        .map(|s| s.as_str())
+        .find(|s| !s.is_empty())
+        .unwrap_or(TREE_GLOBAL_GROUP)
+}
+
+const TREE_GLOBAL_GROUP: &str = "Global";
+
+/// Splits the listing into `sv list --tree` groups, ordered by label with
+/// "Global" last. Scripts keep their listing order within a group.
+fn group_for_tree<'a>(
+    summaries: &'a [ScriptSummary],
+    scripts: &[Script],
+) -> Vec<(String, Vec<&'a ScriptSummary>)> {
+    let labels: HashMap<&str, &str> = scripts
+        .iter()
+        .map(|s| (s.id.as_str(), tree_group(s)))
+        .collect();
+
+    let mut groups: std::collections::BTreeMap<&str, Vec<&ScriptSummary>> = Default::default();
+    for summary in summaries {
+        let label = labels
+            .get(summary.id.as_str())
+            .copied()
+            .unwrap_or(TREE_GLOBAL_GROUP);
+        groups.entry(label).or_default().push(summary);
+    }
+
+    let global = groups.remove(TREE_GLOBAL_GROUP);
+    groups
+        .into_iter()
+        .map(|(label, members)| (label.to_string(), members))
+        .chain(global.map(|members| (TREE_GLOBAL_GROUP.to_string(), members)))
+        .collect()
+}
+
 pub fn show_info(args: InfoArgs) -> Result<()> {
     let config = Config::load()?;
     let storage = config.get_storage_backend()?;
@@ -405,6 +1185,7 @@ pub fn show_info(args: InfoArgs) -> Result<()> {
         script.language.to_string().green()
     );
     println!("  {}: {}", "Author".bold(), script.author);
+    println!("  {}: {}", "Visibility".bold(), script.visibility);
     println!(
         "  {}: {}",
         "Created".bold(),
@@ -419,6 +1200,73 @@ pub fn show_info(args: InfoArgs) -> Result<()> {
         println!("  {}: {}", "Tags".bold(), script.tags.join(", ").cyan());
     }
 
+    if script.archived {
+        println!("  {}: {}", "Status".bold(), "archived".dimmed());
+    }
+
+    if script.trusted {
+        println!(
+            "  {}: {}",
+            "Trusted".bold(),
+            "yes (runs without confirmation)".green()
+        );
+    }
+
+    let patterns = script.dangerous_patterns();
+    if !patterns.is_empty() {
+        let note = if script.safety_acknowledged {
+            " (acknowledged)"
+        } else {
+            ""
+        };
+        println!(
+            "  {}: {}{}",
+            "Dangerous".bold(),
+            patterns.join(", ").red(),
+            note.dimmed()
+        );
+    }
+
+    if let Some(hook) = &script.pre_run {
+        println!("  {}: {}", "Pre-run".bold(), hook.cyan());
+    }
+    if let Some(hook) = &script.post_run {
+        println!("  {}: {}", "Post-run".bold(), hook.cyan());
+    }
+
+    if script.success_exit_codes != [0] {
+        let codes: Vec<String> = script
+            .success_exit_codes
+            .iter()
+            .map(|c| c.to_string())
+            .collect();
+        println!("  {}: {}", "Success codes".bold(), codes.join(", ").green());
+    }
+
+    if !script.params.is_empty() {
+        println!();
+        println!("  {}:", "Parameters".bold());
+        for param in &script.params {
+            let detail = match (&param.default, &param.description) {
+                (Some(default), Some(desc)) => format!("{} (default: {})", desc, default),
+                (Some(default), None) => format!("default: {}", default),
+                (None, Some(desc)) => format!("{} (required)", desc),
+                (None, None) => "required".to_string(),
+            };
+            println!("    {:<12} {}", param.name.yellow(), detail.dimmed());
+        }
+    }
+
+    if !script.env.is_empty() {
+        println!();
+        println!("  {}:", "Environment".bold());
+        let mut vars: Vec<_> = script.env.iter().collect();
+        vars.sort();
+        for (key, value) in vars {
+            println!("    {}={}", key.yellow(), value);
+        }
+    }
+
     println!();
     println!("  {}:", "Context".bold());
     if let Some(dir) = &script.context.directory {
@@ -434,13 +1282,13 @@ pub fn show_info(args: InfoArgs) -> Result<()> {
     println!();
     if script.metadata.use_count > 0 {
         println!(
-            "  {} runs, {:.1}% success{}",
+            "  {} runs, {} success{}",
             script.metadata.use_count,
-            script.success_rate(),
+            color_success_rate(script.success_rate()),
             script
                 .metadata
                 .last_run
-                .map(|t| format!(", last run {}", t.format("%Y-%m-%d")))
+                .map(|t| format!(", last run {}", humanize_duration(t)))
                 .unwrap_or_default()
                 .dimmed()
         );
@@ -448,6 +1296,41 @@ pub fn show_info(args: InfoArgs) -> Result<()> {
         println!("  {}", "Never run".dimmed());
     }
 
+    let records = load_history()?;
+    if let Some(record) = last_failure(&records, &script.id) {
+        println!(
+            "  {}: exit code {} on {}",
+            "Last run failed".red().bold(),
+            record.exit_code,
+            record.executed_at.format("%Y-%m-%d %H:%M")
+        );
+        if let Some(snippet) = record.error.as_deref().and_then(error_snippet) {
+            println!("    {}", snippet.red());
+        }
+    }
+
+    if let Some(limit) = args.history {
+        let runs = recent_runs(&records, &script.id, limit);
+        println!();
+        println!("  {}:", "Recent runs".bold());
+        if runs.is_empty() {
+            println!("    {}", "No runs recorded".dimmed());
+        }
+        for record in runs {
+            println!(
+                "    {:<20} {:<10} {:<10}",
+                record
+                    .executed_at
+                    .format("%Y-%m-%d %H:%M:%S")
+                    .to_string()
+                    .dimmed(),
+                crate::execution::exit_status_label(record),
+                crate::execution::format_record_duration(record)
+            );
+        }
+        println!();
+    }
+
     println!(
         "  Run {} for full execution breakdown",
         format!("sv stats {}", script.name).yellow()
@@ -456,12 +1339,72 @@ pub fn show_info(args: InfoArgs) -> Result<()> {
     Ok(())
 }
 
+/// The script's last `limit` history records, newest first.
+fn recent_runs<'a>(
+    records: &'a [ExecutionRecord],
+    script_id: &str,
+    limit: usize,
+) -> Vec<&'a ExecutionRecord> {
+    let mut runs: Vec<&ExecutionRecord> = records
+        .iter()
+        .filter(|r| r.script_id == script_id)
+        .collect();
+    runs.sort_by_key(|r| std::cmp::Reverse(r.executed_at));
+    runs.truncate(limit);
+    runs
+}
+
+/// Returns the script's most recent execution record, but only if that run
+/// failed. An older failure followed by a success is not reported.
+fn last_failure<'a>(
+    records: &'a [ExecutionRecord],
+    script_id: &str,
+) -> Option<&'a ExecutionRecord> {
+    records
+        .iter()
+        .filter(|r| r.script_id == script_id && r.was_executed())
+        .max_by_key(|r| r.executed_at)
+        .filter(|r| !r.was_successful())
+}
+
+/// First few non-blank lines of a recorded error, capped so `sv info` stays
+/// short. Returns `None` when the error is empty.
+fn error_snippet(error: &str) -> Option<String> {
+    const MAX_LINES: usize = 3;
+    const MAX_CHARS: usize = 200;
+
+    let lines: Vec<&str> = error
+        .lines()
+        .map(str::trim_end)
+        .filter(|l| !l.trim().is_empty())
+        .collect();
+    if lines.is_empty() {
+        return None;
+    }
+
+    let mut snippet = lines[..lines.len().min(MAX_LINES)].join("\n    ");
+    let mut truncated = lines.len() > MAX_LINES;
+    if snippet.chars().count() > MAX_CHARS {
+        snippet = snippet.chars().take(MAX_CHARS).collect();
+        truncated = true;
+    }
+    if truncated {
+        snippet.push_str(" ...");
+    }
+    Some(snippet)
+}
+
 pub fn show_stats(args: StatsArgs) -> Result<()> {
+    let name = match args.name {
+        Some(name) if !args.all => name,
+        _ => return show_vault_stats(),
+    };
+
     let config = Config::load()?;
     let storage = config.get_storage_backend()?;
     let script = storage
-        .load_script_by_name(&args.name)
-        .map_err(|_| anyhow!("Script not found: {}", args.name))?;
+        .load_script_by_name(&name)
+        .map_err(|_| anyhow!("Script not found: {}", name))?;
 
     println!("{}", script.name.cyan().bold());
     println!();
@@ -483,20 +1426,36 @@ pub fn show_stats(args: StatsArgs) -> Result<()> {
     println!("    Successful:   {}", script.metadata.success_count);
     println!("    Failed:       {}", script.metadata.failure_count);
 
-    let rate = script.success_rate();
-    let rate_colored = if rate >= 90.0 {
-        format!("{:.1}%", rate).green().to_string()
-    } else if rate >= 70.0 {
-        format!("{:.1}%", rate).yellow().to_string()
-    } else {
-        format!("{:.1}%", rate).red().to_string()
-    };
-    println!("    Success rate: {}", rate_colored);
+    println!(
+        "    Success rate: {}",
+        color_success_rate(script.success_rate())
+    );
 
     if let Some(avg_ms) = script.metadata.avg_runtime_ms {
         println!("    Avg runtime:  {:.2}s", avg_ms as f64 / 1000.0);
     }
 
+    let timed: Vec<PhaseTimings> = load_history()?
+        .iter()
+        .filter(|r| r.script_id == script.id)
+        .filter_map(|r| r.timings)
+        .collect();
+    if let Some(avg) = average_timings(&timed) {
+        let phase = |us: u64| crate::execution::format_phase(std::time::Duration::from_micros(us));
+        println!();
+        println!(
+            "  {} (average of {} runs with --capture-timing):",
+            "Timing".bold(),
+            timed.len()
+        );
+        println!("    Script written: {}", phase(avg.write_us));
+        println!("    Started:        {}", phase(avg.spawn_us));
+        if let Some(us) = avg.first_output_us {
+            println!("    First output:   {}", phase(us));
+        }
+        println!("    Finished:       {}", phase(avg.total_us));
+    }
+
     if let Some(last_run) = script.metadata.last_run {
         println!();
         println!("  {}:", "Last Run".bold());
@@ -509,6 +1468,124 @@ pub fn show_stats(args: StatsArgs) -> Result<()> {
     Ok(())
 }
 
+/// Mean of each phase. First output is averaged over the runs that printed
+/// something, and is `None` if none did.
+fn average_timings(timings: &[PhaseTimings]) -> Option<PhaseTimings> {
+    if timings.is_empty() {
+        return None;
+    }
+    let mean = |values: Vec<u64>| {
+        if values.is_empty() {
+            None
+        } else {
+            Some(values.iter().sum::<u64>() / values.len() as u64)
+        }
+    };
+    Some(PhaseTimings {
+        write_us: mean(timings.iter().map(|t| t.write_us).collect())?,
+        spawn_us: mean(timings.iter().map(|t| t.spawn_us).collect())?,
+        first_output_us: mean(timings.iter().filter_map(|t| t.first_output_us).collect()),
+        total_us: mean(timings.iter().map(|t| t.total_us).collect())?,
+    })
+}
+
+#[derive(Debug, Default, PartialEq)]
+struct VaultStats {
+    total_scripts: usize,
+    total_runs: usize,
+    successful_runs: usize,
+    total_duration_ms: u64,
+    most_used: Option<(String, usize)>,
+    most_failed: Option<(String, usize)>,
+}
+
+impl VaultStats {
+    fn success_rate(&self) -> f64 {
+        if self.total_runs == 0 {
+            return 0.0;
+        }
+        (self.successful_runs as f64 / self.total_runs as f64) * 100.0
+    }
+}
+
+fn compute_vault_stats(scripts: &[Script], records: &[ExecutionRecord]) -> VaultStats {
+    let names: HashMap<&str, &str> = scripts
+        .iter()
+        .map(|s| (s.id.as_str(), s.name.as_str()))
+        .collect();
+
+    let mut runs: HashMap<&str, usize> = HashMap::new();
+    let mut failures: HashMap<&str, usize> = HashMap::new();
+    let mut stats = VaultStats {
+        total_scripts: scripts.len(),
+        ..Default::default()
+    };
+
+    for record in records.iter().filter(|r| r.was_executed()) {
+        stats.total_runs += 1;
+        stats.total_duration_ms += record.duration_ms;
+        if record.was_successful() {
+            stats.successful_runs += 1;
+        } else {
+            *failures.entry(record.script_id.as_str()).or_default() += 1;
+        }
+        *runs.entry(record.script_id.as_str()).or_default() += 1;
+    }
+
+    // Ties go to the alphabetically first name so the summary is stable between runs.
+    let top = |counts: &HashMap<&str, usize>| {
+        counts
+            .iter()
+            .filter_map(|(id, count)| names.get(id).map(|name| (*name, *count)))
+            .max_by(|a, b| a.1.cmp(&b.1).then_with(|| b.0.cmp(a.0)))
+            .map(|(name, count)| (name.to_string(), count))
+    };
+    stats.most_used = top(&runs);
+    stats.most_failed = top(&failures);
+
+    stats
+}
+
+fn show_vault_stats() -> Result<()> {
+    let scripts = load_scripts_local()?;
+    let records = load_history()?;
+    let stats = compute_vault_stats(&scripts, &records);
+
+    println!("{}", "Vault Statistics".cyan().bold());
+    println!();
+
+    println!("  {}:", "Scripts".bold());
+    println!("    Total scripts: {}", stats.total_scripts);
+
+    println!();
+    println!("  {}:", "Execution".bold());
+    println!("    Total runs:    {}", stats.total_runs);
+    println!("    Successful:    {}", stats.successful_runs);
+    println!(
+        "    Failed:        {}",
+        stats.total_runs - stats.successful_runs
+    );
+    println!(
+        "    Success rate:  {}",
+        color_success_rate(stats.success_rate())
+    );
+    println!(
+        "    Total time:    {:.2}s",
+        stats.total_duration_ms as f64 / 1000.0
+    );
+
+    if let Some((name, count)) = &stats.most_used {
+        println!();
+        println!("  {}:", "Highlights".bold());
+        println!("    Most used:     {} ({} runs)", name.yellow(), count);
+        if let Some((name, count)) = &stats.most_failed {
+            println!("    Most failed:   {} ({} failures)", name.yellow(), count);
+        }
+    }
+
+    Ok(())
+}
+
 pub fn cat_script(args: CatArgs) -> Result<()> {
     let config = Config::load()?;
     let storage = config.get_storage_backend()?;
@@ -523,6 +1600,7 @@ pub fn cat_script(args: CatArgs) -> Result<()> {
 
 pub fn edit_script(args: EditArgs) -> Result<()> {
     let config = Config::load()?;
+    config.ensure_writable()?;
     let storage = config.get_storage_backend()?;
 
     let mut script = storage
@@ -572,10 +1650,13 @@ pub fn edit_script(args: EditArgs) -> Result<()> {
         return Ok(());
     }
 
+    config.check_script_size(new_content.len() as u64)?;
+
     let old_version = script.version.clone();
     script.version = bump_patch_version(&script.version);
     script.content = new_content.clone();
     script.metadata.hash = new_hash;
+    script.safety_acknowledged = false;
     script.metadata.size_bytes = new_content.len();
     script.metadata.line_count = new_content.lines().count();
     script.updated_at = Utc::now();
@@ -589,6 +1670,12 @@ pub fn edit_script(args: EditArgs) -> Result<()> {
     }
 
     storage.update_script(&script)?;
+    audit::record(
+        &config,
+        AuditAction::Save,
+        &script,
+        Some(script.version.clone()),
+    )?;
 
     println!(
         "{} Updated: {} {} -> {}",
@@ -603,6 +1690,7 @@ pub fn edit_script(args: EditArgs) -> Result<()> {
 
 pub fn rename_script(args: RenameArgs) -> Result<()> {
     let config = Config::load()?;
+    config.ensure_writable()?;
     let storage = config.get_storage_backend()?;
 
     let mut script = storage
@@ -613,11 +1701,28 @@ pub fn rename_script(args: RenameArgs) -> Result<()> {
         return Err(anyhow!("A script named '{}' already exists", args.new_name));
     }
 
+    if args.dry_run {
+        println!(
+            "{} Would rename {} -> {}",
+            "Dry run:".yellow().bold(),
+            script.name.yellow(),
+            args.new_name.yellow()
+        );
+        print_dry_run_footer();
+        return Ok(());
+    }
+
     let old_name = script.name.clone();
     script.name = args.new_name.clone();
     script.updated_at = Utc::now();
 
     storage.update_script(&script)?;
+    audit::record(
+        &config,
+        AuditAction::Rename,
+        &script,
+        Some(format!("{} -> {}", old_name, script.name)),
+    )?;
 
     println!(
         "{} Renamed: {} -> {}",
@@ -629,98 +1734,504 @@ pub fn rename_script(args: RenameArgs) -> Result<()> {
     Ok(())
 }
 
-pub fn copy_script(args: CopyArgs) -> Result<()> {
+pub fn set_archived(args: ArchiveArgs, archived: bool) -> Result<()> {
     let config = Config::load()?;
+    config.ensure_writable()?;
     let storage = config.get_storage_backend()?;
 
-    let source = storage
-        .load_script_by_name(&args.source)
-        .map_err(|_| anyhow!("Script not found: {}", args.source))?;
+    let mut script = storage
+        .load_script_by_name(&args.name)
+        .map_err(|_| anyhow!("Script not found: {}", args.name))?;
 
-    if storage.load_script_by_name(&args.dest).is_ok() {
-        return Err(anyhow!("A script named '{}' already exists", args.dest));
+    let verb = if archived { "archived" } else { "unarchived" };
+    if script.archived == archived {
+        println!(
+            "{} Script '{}' is already {}",
+            "i".cyan(),
+            script.name.yellow(),
+            verb
+        );
+        return Ok(());
     }
 
-    let mut copy = source.clone();
-    copy.id = uuid::Uuid::new_v4().to_string();
-    copy.name = args.dest.clone();
-    copy.version = "v1.0.0".to_string();
-    copy.created_at = Utc::now();
-    copy.updated_at = Utc::now();
-    copy.metadata.use_count = 0;
-    copy.metadata.success_count = 0;
-    copy.metadata.failure_count = 0;
-    copy.metadata.last_run = None;
-    copy.metadata.last_run_by = None;
-    copy.metadata.avg_runtime_ms = None;
+    if args.dry_run {
+        println!(
+            "{} Script '{}' would be {}",
+            "Dry run:".yellow().bold(),
+            script.name.yellow(),
+            verb
+        );
+        print_dry_run_footer();
+        return Ok(());
+    }
 
-    storage.save_script(&copy)?;
+    script.archived = archived;
+    script.updated_at = Utc::now();
+    storage.update_script(&script)?;
+    let action = if archived {
+        AuditAction::Archive
+    } else {
+        AuditAction::Unarchive
+    };
+    audit::record(&config, action, &script, None)?;
 
     println!(
-        "{} Copied: {} -> {}",
+        "{} Script '{}' {}",
         "✓".green().bold(),
-        args.source.yellow(),
-        args.dest.yellow()
+        script.name.yellow(),
+        verb
     );
+    if archived {
+        println!(
+            "  Hidden from find and list. Use {} to see it.",
+            "--include-archived".yellow()
+        );
+    }
 
     Ok(())
 }
 
-pub fn delete_script(args: DeleteArgs) -> Result<()> {
+pub fn set_pinned(args: PinArgs, pinned: bool) -> Result<()> {
     let config = Config::load()?;
+    config.ensure_writable()?;
     let storage = config.get_storage_backend()?;
 
-    let script = storage
+    let mut script = storage
         .load_script_by_name(&args.name)
         .map_err(|_| anyhow!("Script not found: {}", args.name))?;
 
-    if !args.yes {
-        println!("{}", script.name.yellow().bold());
-
-        if let Some(desc) = &script.description {
-            println!("  {}", desc.dimmed());
-        }
-        if !script.tags.is_empty() {
-            println!("  Tags: {}", script.tags.join(", ").cyan());
-        }
-        println!("  Uses: {}", script.metadata.use_count);
-        println!();
-
-        let confirmed = Confirm::new()
-            .with_prompt("Delete this script?")
-            .default(false)
-            .interact()?;
-
-        if !confirmed {
-            println!("Cancelled");
-            return Ok(());
-        }
+    let verb = if pinned { "pinned" } else { "unpinned" };
+    if script.pinned == pinned {
+        println!(
+            "{} Script '{}' is already {}",
+            "i".cyan(),
+            script.name.yellow(),
+            verb
+        );
+        return Ok(());
     }
 
-    storage.delete_script(&script.id)?;
-    let store = crate::versions::VersionStore::new(&Config::vault_dir()?);
-    store.purge_script(&script.id)?;
-    purge_script_history(&script.id)?;
+    script.pinned = pinned;
+    storage.update_script(&script)?;
 
-    println!("{} Deleted: {}", "✓".green().bold(), args.name.yellow());
+    println!(
+        "{} Script '{}' {}",
+        "✓".green().bold(),
+        script.name.yellow(),
+        verb
+    );
 
     Ok(())
 }
 
-fn purge_script_history(script_id: &str) -> Result<()> {
-    let history_path = Config::history_path()?;
+pub fn set_trusted(args: TrustArgs, trusted: bool) -> Result<()> {
+    let config = Config::load()?;
+    config.ensure_writable()?;
+    let storage = config.get_storage_backend()?;
 
-    if !history_path.exists() {
+    let mut script = storage
+        .load_script_by_name(&args.name)
+        .map_err(|_| anyhow!("Script not found: {}", args.name))?;
+
+    let verb = if trusted { "trusted" } else { "untrusted" };
+    if script.trusted == trusted {
+        println!(
+            "{} Script '{}' is already {}",
+            "i".cyan(),
+            script.name.yellow(),
+            verb
+        );
         return Ok(());
     }
 
-    let contents = fs::read_to_string(&history_path)?;
-    let retained: Vec<&str> = contents
-        .lines()
-        .filter(|line| {
-            if line.is_empty() {
-                return false;
-            }
+    script.trusted = trusted;
+    storage.update_script(&script)?;
+
+    println!(
+        "{} Script '{}' {}",
+        "✓".green().bold(),
+        script.name.yellow(),
+        verb
+    );
+    if trusted {
+        println!(
+            "  It will run without the confirmation prompt. Dangerous-pattern warnings still apply."
+        );
+    }
+
+    Ok(())
+}
+
+pub fn set_safety_acknowledged(args: TrustArgs, acknowledged: bool) -> Result<()> {
+    let config = Config::load()?;
+    config.ensure_writable()?;
+    let storage = config.get_storage_backend()?;
+
+    let mut script = storage
+        .load_script_by_name(&args.name)
+        .map_err(|_| anyhow!("Script not found: {}", args.name))?;
+
+    let patterns = script.dangerous_patterns();
+    if acknowledged && patterns.is_empty() {
+        println!(
+            "{} Script '{}' has no dangerous commands to acknowledge",
+            "i".cyan(),
+            script.name.yellow()
+        );
+        return Ok(());
+    }
+
+    if script.safety_acknowledged == acknowledged {
+        let state = if acknowledged {
+            "already acknowledged"
+        } else {
+            "not acknowledged"
+        };
+        println!(
+            "{} Dangerous commands in '{}' are {}",
+            "i".cyan(),
+            script.name.yellow(),
+            state
+        );
+        return Ok(());
+    }
+
+    script.safety_acknowledged = acknowledged;
+    storage.update_script(&script)?;
+
+    if acknowledged {
+        println!(
+            "{} Acknowledged dangerous commands in '{}': {}",
+            "✓".green().bold(),
+            script.name.yellow(),
+            patterns.join(", ").red()
+        );
+        println!("  Runs will skip the safety prompt until the script's content changes.");
+    } else {
+        println!(
+            "{} Script '{}' will ask before running dangerous commands again",
+            "✓".green().bold(),
+            script.name.yellow()
+        );
+    }
+
+    Ok(())
+}
+
+pub fn copy_script(args: CopyArgs) -> Result<()> {
+    let config = Config::load()?;
+    config.ensure_writable()?;
+    let storage = config.get_storage_backend()?;
+
+    let source = storage
+        .load_script_by_name(&args.source)
+        .map_err(|_| anyhow!("Script not found: {}", args.source))?;
+
+    if storage.load_script_by_name(&args.dest).is_ok() {
+        return Err(anyhow!("A script named '{}' already exists", args.dest));
+    }
+
+    let mut copy = source.clone();
+    copy.id = uuid::Uuid::new_v4().to_string();
+    copy.name = args.dest.clone();
+    copy.version = "v1.0.0".to_string();
+    copy.created_at = Utc::now();
+    copy.updated_at = Utc::now();
+    copy.metadata.use_count = 0;
+    copy.metadata.success_count = 0;
+    copy.metadata.failure_count = 0;
+    copy.metadata.last_run = None;
+    copy.metadata.last_run_by = None;
+    copy.metadata.avg_runtime_ms = None;
+
+    storage.save_script(&copy)?;
+    audit::record(
+        &config,
+        AuditAction::Save,
+        &copy,
+        Some(format!("copy of {}", source.name)),
+    )?;
+
+    println!(
+        "{} Copied: {} -> {}",
+        "✓".green().bold(),
+        args.source.yellow(),
+        args.dest.yellow()
+    );
+
+    Ok(())
+}
+
+/// Line comment prefix used for the section markers in a merged script.
+fn comment_prefix(language: &ScriptLanguage) -> &'static str {
+    match language {
+        ScriptLanguage::JavaScript => "//",
+        ScriptLanguage::Batch => "REM",
+        _ => "#",
+    }
+}
+
+/// Concatenates two same-language scripts, each under a marker naming its
+/// source. The first script's shebang is kept at the top and the second's is
+/// dropped.
+fn merge_content(first: &Script, second: &Script) -> Result<String> {
+    if first.language != second.language {
+        return Err(anyhow!(
+            "Cannot merge {} ({}) with {} ({}): scripts must be in the same language",
+            first.name,
+            first.language,
+            second.name,
+            second.language
+        ));
+    }
+
+    let split_shebang = |content: &str| -> (Option<String>, String) {
+        match content.split_once('\n') {
+            Some((line, rest)) if line.starts_with("#!") => {
+                (Some(line.to_string()), rest.to_string())
+            }
+            None if content.starts_with("#!") => (Some(content.to_string()), String::new()),
+            _ => (None, content.to_string()),
+        }
+    };
+    let (shebang, first_body) = split_shebang(&first.content);
+    let (_, second_body) = split_shebang(&second.content);
+
+    let prefix = comment_prefix(&first.language);
+    let mut merged = String::new();
+    if let Some(line) = shebang {
+        merged.push_str(&line);
+        merged.push('\n');
+    }
+    for (i, (name, body)) in [(&first.name, first_body), (&second.name, second_body)]
+        .into_iter()
+        .enumerate()
+    {
+        if i > 0 {
+            merged.push('\n');
+        }
+        merged.push_str(&format!("{} --- {} ---\n", prefix, name));
+        merged.push_str(body.trim_matches('\n'));
+        merged.push('\n');
+    }
+    Ok(merged)
+}
+
+pub fn merge_scripts(args: MergeArgs) -> Result<()> {
+    let config = Config::load()?;
+    config.ensure_writable()?;
+    let storage = config.get_storage_backend()?;
+
+    let first = storage
+        .load_script_by_name(&args.first)
+        .map_err(|_| anyhow!("Script not found: {}", args.first))?;
+    let second = storage
+        .load_script_by_name(&args.second)
+        .map_err(|_| anyhow!("Script not found: {}", args.second))?;
+
+    let content = merge_content(&first, &second)?;
+    config.check_script_size(content.len() as u64)?;
+
+    let name = if args.yes {
+        args.name
+            .ok_or_else(|| anyhow!("sv merge --yes requires --name"))?
+    } else {
+        println!("{}", "Merged script preview".cyan().bold());
+        println!("{}", "─".repeat(60).dimmed());
+        print!("{}", content);
+        println!("{}", "─".repeat(60).dimmed());
+        println!();
+
+        let suggested = args
+            .name
+            .unwrap_or_else(|| format!("{}-{}", first.name, second.name));
+        Input::new()
+            .with_prompt("Name for the merged script")
+            .with_initial_text(suggested)
+            .interact_text()?
+    };
+
+    if storage.load_script_by_name(&name).is_ok() {
+        return Err(anyhow!("A script named '{}' already exists", name));
+    }
+
+    if !args.yes {
+        let proceed = Confirm::new()
+            .with_prompt(format!("Save merged script as '{}'?", name))
+            .default(true)
+            .interact()?;
+        if !proceed {
+            println!("Merge cancelled.");
+            return Ok(());
+        }
+    }
+
+    let mut merged = Script::new(name, content, first.language.clone());
+    merged.context = first.context.clone();
+    merged.description = Some(format!("Merged from {} and {}", first.name, second.name));
+    merged.tags = first.tags.clone();
+    for tag in &second.tags {
+        if !merged.tags.contains(tag) {
+            merged.tags.push(tag.clone());
+        }
+    }
+    if let Some(username) = &config.username {
+        merged.author = username.clone();
+    }
+
+    storage.save_script(&merged)?;
+    crate::versions::VersionStore::new(&Config::vault_dir()?).save_version(&merged)?;
+    audit::record(
+        &config,
+        AuditAction::Merge,
+        &merged,
+        Some(format!("{} + {}", first.name, second.name)),
+    )?;
+
+    println!(
+        "{} Merged: {} + {} -> {}",
+        "✓".green().bold(),
+        first.name.yellow(),
+        second.name.yellow(),
+        merged.name.yellow()
+    );
+
+    Ok(())
+}
+
+pub fn share_script(args: ShareArgs) -> Result<()> {
+    let config = Config::load()?;
+    let storage = config.get_storage_backend()?;
+
+    if args.team {
+        return Err(anyhow!(
+            "Sharing with a team is not supported yet. Use 'sv share --public' or sv sync."
+        ));
+    }
+
+    let mut script = storage
+        .load_script_by_name(&args.name)
+        .map_err(|_| anyhow!("Script not found: {}", args.name))?;
+
+    let token = config
+        .github_token
+        .clone()
+        .or_else(|| std::env::var("GITHUB_TOKEN").ok())
+        .filter(|t| !t.is_empty())
+        .ok_or_else(|| {
+            anyhow!("No GitHub token. Set github_token in config.json or the GITHUB_TOKEN environment variable.")
+        })?;
+
+    let url = crate::gist::create(crate::gist::GITHUB_API, &token, &script, args.public)?;
+
+    if args.public && script.visibility != Visibility::Public && !config.is_read_only() {
+        script.visibility = Visibility::Public;
+        storage.update_script(&script)?;
+    }
+    let kind = if args.public { "public" } else { "secret" };
+    audit::record(
+        &config,
+        AuditAction::Share,
+        &script,
+        Some(format!("{} gist {}", kind, url)),
+    )?;
+
+    println!(
+        "{} Shared {} as a {} gist",
+        "✓".green().bold(),
+        script.name.yellow(),
+        kind
+    );
+    println!("  {}", url.cyan());
+
+    Ok(())
+}
+
+pub fn delete_script(args: DeleteArgs) -> Result<()> {
+    let config = Config::load()?;
+    config.ensure_writable()?;
+    let storage = config.get_storage_backend()?;
+
+    let script = storage
+        .load_script_by_name(&args.name)
+        .map_err(|_| anyhow!("Script not found: {}", args.name))?;
+    let store = crate::versions::VersionStore::new(&Config::vault_dir()?);
+
+    if args.dry_run {
+        let versions = store.list_versions(&script.id)?.len();
+        let runs = load_history()?
+            .iter()
+            .filter(|r| r.script_id == script.id)
+            .count();
+        println!(
+            "{} Would delete {} {}",
+            "Dry run:".yellow().bold(),
+            script.name.yellow(),
+            script.version.dimmed()
+        );
+        println!("  Versions: {}", versions);
+        println!("  History records: {}", runs);
+        print_dry_run_footer();
+        return Ok(());
+    }
+
+    if !args.yes {
+        println!("{}", script.name.yellow().bold());
+
+        if let Some(desc) = &script.description {
+            println!("  {}", desc.dimmed());
+        }
+        if !script.tags.is_empty() {
+            println!("  Tags: {}", script.tags.join(", ").cyan());
+        }
+        println!("  Uses: {}", script.metadata.use_count);
+        println!();
+
+        let confirmed = Confirm::new()
+            .with_prompt("Delete this script?")
+            .default(false)
+            .interact()?;
+
+        if !confirmed {
+            println!("Cancelled");
+            return Ok(());
+        }
+    }
+
+    storage.delete_script(&script.id)?;
+    store.purge_script(&script.id)?;
+    purge_script_history(&script.id)?;
+    audit::record(
+        &config,
+        AuditAction::Delete,
+        &script,
+        Some(script.version.clone()),
+    )?;
+
+    println!("{} Deleted: {}", "✓".green().bold(), args.name.yellow());
+
+    Ok(())
+}
+
+fn print_dry_run_footer() {
+    println!();
+    println!("{}", "Dry run complete. No changes applied.".yellow());
+}
+
+fn purge_script_history(script_id: &str) -> Result<()> {
+    let history_path = Config::history_path()?;
+
+    if !history_path.exists() {
+        return Ok(());
+    }
+
+    let contents = fs::read_to_string(&history_path)?;
+    let retained: Vec<&str> = contents
+        .lines()
+        .filter(|line| {
+            if line.is_empty() {
+                return false;
+            }
             serde_json::from_str::<serde_json::Value>(line)
                 .ok()
                 .and_then(|v| {
@@ -805,14 +2316,21 @@ pub fn diff_versions(args: DiffArgs) -> Result<()> {
         .load_script_by_name(&args.name)
         .map_err(|_| anyhow!("Script not found: {}", args.name))?;
 
+    if let Some(file) = &args.file {
+        return diff_against_file(&script, file);
+    }
+    let (Some(version1), Some(version2)) = (&args.version1, &args.version2) else {
+        return Err(anyhow!("Give two versions to compare, or --file <PATH>"));
+    };
+
     let store = crate::versions::VersionStore::new(&Config::vault_dir()?);
-    let (a, b) = store.diff_versions(&script.id, &args.version1, &args.version2)?;
+    let (a, b) = store.diff_versions(&script.id, version1, version2)?;
 
     println!(
         "{} {} vs {}",
         args.name.cyan().bold(),
-        args.version1.yellow(),
-        args.version2.yellow()
+        version1.yellow(),
+        version2.yellow()
     );
     println!();
 
@@ -849,6 +2367,44 @@ pub fn diff_versions(args: DiffArgs) -> Result<()> {
     Ok(())
 }
 
+/// Unified diff from the stored script to a working copy on disk.
+fn diff_against_file(script: &Script, file: &str) -> Result<()> {
+    let path = Path::new(file);
+    if !path.exists() {
+        return Err(anyhow!("File not found: {}", file));
+    }
+    let working = read_script_text(path)?;
+
+    let stored_label = format!("{} ({})", script.name, script.version);
+    let diff = crate::versions::unified_diff(&stored_label, file, &script.content, &working);
+    if diff.is_empty() {
+        println!(
+            "{} No differences: {} matches {}",
+            "i".cyan(),
+            script.name.yellow(),
+            file
+        );
+        return Ok(());
+    }
+
+    for line in diff.lines() {
+        if line.starts_with("---") || line.starts_with("+++") {
+            println!("{}", line.bold());
+        } else if line.starts_with("@@") {
+            println!("{}", line.cyan());
+        } else if line.starts_with('-') {
+            println!("{}", line.red());
+        } else if line.starts_with('+') {
+            println!("{}", line.green());
+        } else if line.starts_with('\\') {
+            println!("{}", line.dimmed());
+        } else {
+            println!("{}", line);
+        }
+    }
+    Ok(())
+}
+
 pub fn checkout_version(args: CheckoutArgs) -> Result<()> {
     let parts: Vec<&str> = args.script_version.splitn(2, '@').collect();
     if parts.len() != 2 {
@@ -859,6 +2415,7 @@ pub fn checkout_version(args: CheckoutArgs) -> Result<()> {
     let (name, version) = (parts[0], parts[1]);
 
     let config = Config::load()?;
+    config.ensure_writable()?;
     let storage = config.get_storage_backend()?;
     let current = storage
         .load_script_by_name(name)
@@ -870,6 +2427,7 @@ pub fn checkout_version(args: CheckoutArgs) -> Result<()> {
     let mut restored = current.clone();
     restored.content = snapshot.content.clone();
     restored.metadata.hash = snapshot.metadata.hash.clone();
+    restored.safety_acknowledged &= restored.metadata.hash == current.metadata.hash;
     restored.metadata.size_bytes = snapshot.metadata.size_bytes;
     restored.metadata.line_count = snapshot.metadata.line_count;
     restored.version = bump_patch_version(&current.version);
@@ -885,6 +2443,12 @@ pub fn checkout_version(args: CheckoutArgs) -> Result<()> {
 
     store.save_version(&restored)?;
     storage.update_script(&restored)?;
+    audit::record(
+        &config,
+        AuditAction::Checkout,
+        &restored,
+        Some(format!("{} as {}", version, restored.version)),
+    )?;
 
     println!(
         "{} Restored: {} from {} as {}",
@@ -898,16 +2462,51 @@ pub fn checkout_version(args: CheckoutArgs) -> Result<()> {
 }
 
 pub fn export_scripts(args: ExportArgs) -> Result<()> {
+    if args.encrypt && args.output.is_none() && args.zip.is_none() {
+        return Err(anyhow!(
+            "--encrypt writes binary data; pass --output FILE or --zip PATH"
+        ));
+    }
     let config = Config::load()?;
     let storage = config.get_storage_backend()?;
-    let scripts = storage.list_scripts()?;
+    let scripts = match args.single {
+        Some(ref name) => vec![
+            storage
+                .load_script_by_name(name)
+                .map_err(|_| anyhow!("Script not found: {}", name))?,
+        ],
+        None => storage.list_scripts()?,
+    };
 
     if scripts.is_empty() {
         println!("No scripts to export.");
         return Ok(());
     }
 
+    let passphrase = if args.encrypt {
+        Some(crate::encryption::passphrase(true)?)
+    } else {
+        None
+    };
+    let seal = |bytes: Vec<u8>| match passphrase {
+        Some(ref passphrase) => crate::encryption::encrypt(&bytes, passphrase),
+        None => Ok(bytes),
+    };
+
+    if let Some(zip_path) = args.zip {
+        fs::write(&zip_path, seal(export_zip(&scripts)?)?)
+            .with_context(|| format!("Failed to write {}", zip_path))?;
+        println!(
+            "{} Exported {} scripts to: {}",
+            "✓".green().bold(),
+            scripts.len(),
+            zip_path.yellow()
+        );
+        return Ok(());
+    }
+
     let output = match args.format.to_lowercase().as_str() {
+        "json" if args.single.is_some() => serde_json::to_string_pretty(&scripts[0])?,
         "json" => export_json(&scripts)?,
         "markdown" | "md" => export_markdown(&scripts)?,
         _ => {
@@ -919,7 +2518,7 @@ pub fn export_scripts(args: ExportArgs) -> Result<()> {
     };
 
     if let Some(output_file) = args.output {
-        fs::write(&output_file, output)?;
+        fs::write(&output_file, seal(output.into_bytes())?)?;
         println!(
             "{} Exported {} scripts to: {}",
             "✓".green().bold(),
@@ -933,7 +2532,230 @@ pub fn export_scripts(args: ExportArgs) -> Result<()> {
     Ok(())
 }
 
-fn export_json(scripts: &[Script]) -> Result<String> {
+/// File name for a script inside a `--zip` export. Path separators are
+/// replaced so every script lands at the top of the archive.
+fn zip_entry_name(script: &Script) -> String {
+    format!(
+        "{}.{}",
+        script.name.replace(['/', '\\'], "_"),
+        script.language.extension()
+    )
+}
+
+const ZIP_MANIFEST: &str = "manifest.json";
+
+/// `manifest.json` in a `--zip` export. Only `file` and `name` are required
+/// on import, so a hand-written manifest can leave the rest out.
+#[derive(serde::Serialize, serde::Deserialize)]
+struct ZipManifest {
+    #[serde(default)]
+    exported_at: String,
+    #[serde(default)]
+    export_version: String,
+    #[serde(default)]
+    total_scripts: usize,
+    scripts: Vec<ZipManifestEntry>,
+}
+
+#[derive(serde::Serialize, serde::Deserialize)]
+struct ZipManifestEntry {
+    file: String,
+    name: String,
+    #[serde(default)]
+    version: Option<String>,
+    #[serde(default)]
+    language: Option<ScriptLanguage>,
+    #[serde(default)]
+    tags: Vec<String>,
+    #[serde(default)]
+    description: Option<String>,
+    #[serde(default)]
+    author: Option<String>,
+    #[serde(default)]
+    visibility: Option<Visibility>,
+    #[serde(default)]
+    created_at: Option<chrono::DateTime<Utc>>,
+    #[serde(default)]
+    updated_at: Option<chrono::DateTime<Utc>>,
+    #[serde(default)]
+    hash: Option<String>,
+    #[serde(default)]
+    params: Vec<ScriptParam>,
+    #[serde(default)]
+    env: HashMap<String, String>,
+    #[serde(default)]
+    success_exit_codes: Option<Vec<i32>>,
+}
+
+impl ZipManifestEntry {
+    fn new(file: String, script: &Script) -> Self {
+        Self {
+            file,
+            name: script.name.clone(),
+            version: Some(script.version.clone()),
+            language: Some(script.language.clone()),
+            tags: script.tags.clone(),
+            description: script.description.clone(),
+            author: Some(script.author.clone()),
+            visibility: Some(script.visibility.clone()),
+            created_at: Some(script.created_at),
+            updated_at: Some(script.updated_at),
+            hash: Some(script.metadata.hash.clone()),
+            params: script.params.clone(),
+            env: script.env.clone(),
+            success_exit_codes: Some(script.success_exit_codes.clone()),
+        }
+    }
+
+    /// Rebuilds the script from this entry and the file's content. Fields
+    /// the manifest leaves out get the same defaults as `sv save`.
+    fn into_script(self, content: String) -> Script {
+        let language = self
+            .language
+            .unwrap_or_else(|| language_from_file_name(&self.file));
+        let mut script = Script::new(self.name, content, language);
+        if let Some(version) = self.version {
+            script.version = version;
+        }
+        script.tags = self.tags;
+        script.description = self.description;
+        if let Some(author) = self.author {
+            script.author = author;
+        }
+        if let Some(visibility) = self.visibility {
+            script.visibility = visibility;
+        }
+        if let Some(created_at) = self.created_at {
+            script.created_at = created_at;
+        }
+        if let Some(updated_at) = self.updated_at {
+            script.updated_at = updated_at;
+        }
+        script.params = self.params;
+        script.env = self.env;
+        if let Some(codes) = self.success_exit_codes {
+            script.success_exit_codes = codes;
+        }
+        script
+    }
+}
+
+fn language_from_file_name(file: &str) -> ScriptLanguage {
+    ScriptLanguage::from_extension(
+        Path::new(file)
+            .extension()
+            .and_then(|e| e.to_str())
+            .unwrap_or("sh"),
+    )
+}
+
+/// Writes every script to its own `<name>.<ext>` entry, plus a
+/// `manifest.json` with the metadata needed to tell them apart again.
+fn export_zip(scripts: &[Script]) -> Result<Vec<u8>> {
+    use std::io::Write;
+
+    let mut zip = zip::ZipWriter::new(std::io::Cursor::new(Vec::new()));
+    let options = zip::write::SimpleFileOptions::default()
+        .compression_method(zip::CompressionMethod::Deflated)
+        .unix_permissions(0o755);
+
+    let mut entries = Vec::with_capacity(scripts.len());
+    for script in scripts {
+        let name = zip_entry_name(script);
+        zip.start_file(name.as_str(), options)?;
+        zip.write_all(script.content.as_bytes())?;
+        entries.push(ZipManifestEntry::new(name, script));
+    }
+
+    let manifest = ZipManifest {
+        exported_at: Utc::now().to_rfc3339(),
+        export_version: "1.0".to_string(),
+        total_scripts: entries.len(),
+        scripts: entries,
+    };
+    zip.start_file(ZIP_MANIFEST, options.unix_permissions(0o644))?;
+    zip.write_all(serde_json::to_string_pretty(&manifest)?.as_bytes())?;
+    Ok(zip.finish()?.into_inner())
+}
+
+/// Reads a zip entry as UTF-8 script text; `None` if there is no such entry.
+fn read_zip_text<R: std::io::Read + std::io::Seek>(
+    archive: &mut zip::ZipArchive<R>,
+    name: &str,
+) -> Result<Option<String>, String> {
+    use std::io::Read;
+
+    let mut entry = match archive.by_name(name) {
+        Ok(entry) => entry,
+        Err(zip::result::ZipError::FileNotFound) => return Ok(None),
+        Err(e) => return Err(format!("{}: {}", name, e)),
+    };
+    let mut bytes = Vec::new();
+    entry
+        .read_to_end(&mut bytes)
+        .map_err(|e| format!("{}: {}", name, e))?;
+    String::from_utf8(bytes)
+        .map(Some)
+        .map_err(|_| format!("{}: file is not valid UTF-8", name))
+}
+
+/// Reads a `sv export --zip` archive. With a `manifest.json` the scripts and
+/// their metadata come from it; without one every file becomes a script named
+/// after the file, with the language taken from its extension.
+fn parse_zip_import(bytes: &[u8]) -> Result<ParsedImport> {
+    let mut archive = zip::ZipArchive::new(std::io::Cursor::new(bytes))
+        .map_err(|e| anyhow!("Not a valid zip archive: {}", e))?;
+    let mut parsed = ParsedImport {
+        scripts: Vec::new(),
+        invalid: Vec::new(),
+    };
+
+    let manifest = match read_zip_text(&mut archive, ZIP_MANIFEST) {
+        Ok(Some(text)) => Some(
+            serde_json::from_str::<ZipManifest>(&text)
+                .map_err(|e| anyhow!("Invalid {} in archive: {}", ZIP_MANIFEST, e))?,
+        ),
+        Ok(None) => None,
+        Err(e) => return Err(anyhow!(e)),
+    };
+
+    let Some(manifest) = manifest else {
+        let files: Vec<String> = archive
+            .file_names()
+            .filter(|name| !name.ends_with('/'))
+            .map(str::to_string)
+            .collect();
+        for (index, file) in files.into_iter().enumerate() {
+            let path = Path::new(&file);
+            let Some(stem) = path.file_stem().and_then(|s| s.to_str()) else {
+                continue;
+            };
+            match read_zip_text(&mut archive, &file) {
+                Ok(Some(content)) => parsed.scripts.push(Script::new(
+                    stem.to_string(),
+                    content,
+                    language_from_file_name(&file),
+                )),
+                Ok(None) => {}
+                Err(problem) => parsed.invalid.push((index, vec![problem])),
+            }
+        }
+        return Ok(parsed);
+    };
+
+    for (index, entry) in manifest.scripts.into_iter().enumerate() {
+        match read_zip_text(&mut archive, &entry.file) {
+            Ok(Some(content)) => parsed.scripts.push(entry.into_script(content)),
+            Ok(None) => parsed
+                .invalid
+                .push((index, vec![format!("{}: missing from archive", entry.file)])),
+            Err(problem) => parsed.invalid.push((index, vec![problem])),
+        }
+    }
+    Ok(parsed)
+}
+
+fn export_json(scripts: &[Script]) -> Result<String> {
     #[derive(serde::Serialize)]
     struct ExportData<'a> {
         exported_at: String,
@@ -1002,7 +2824,7 @@ fn export_markdown(scripts: &[Script]) -> Result<String> {
             ));
         }
 
-        out.push_str("\n");
+        out.push('\n');
 
         if script.context.directory.is_some() || script.context.git_repo.is_some() {
             out.push_str("### Context\n\n");
@@ -1015,7 +2837,7 @@ fn export_markdown(scripts: &[Script]) -> Result<String> {
             if let Some(branch) = &script.context.git_branch {
                 out.push_str(&format!("- Branch: `{}`\n", branch));
             }
-            out.push_str("\n");
+            out.push('\n');
         }
 
         out.push_str("### Script\n\n");
@@ -1029,3 +2851,1152 @@ fn export_markdown(scripts: &[Script]) -> Result<String> {
 
     Ok(out)
 }
+
+/// Scripts read from an import file. Entries that failed validation are kept
+/// as their index in the file and the problems found.
+struct ParsedImport {
+    scripts: Vec<Script>,
+    invalid: Vec<(usize, Vec<String>)>,
+}
+
+const IMPORT_COUNT_FIELDS: &[&str] = &[
+    "size_bytes",
+    "line_count",
+    "use_count",
+    "success_count",
+    "failure_count",
+];
+
+/// Checks the fields people most often get wrong when hand-editing an export
+/// and reports each one, so the user doesn't have to decode a serde error.
+/// Anything else that keeps the entry from deserializing is reported as-is.
+fn script_problems(value: &serde_json::Value) -> Vec<String> {
+    use serde_json::Value;
+
+    let Some(obj) = value.as_object() else {
+        return vec!["not a JSON object".to_string()];
+    };
+    let mut problems = Vec::new();
+
+    match obj.get("name") {
+        None => problems.push("name: missing".to_string()),
+        Some(Value::String(name)) if !name.trim().is_empty() => {}
+        Some(_) => problems.push("name: must be a non-empty string".to_string()),
+    }
+    match obj.get("content") {
+        None => problems.push("content: missing".to_string()),
+        Some(Value::String(_)) => {}
+        Some(_) => problems.push("content: must be a string".to_string()),
+    }
+    match obj.get("language") {
+        None => problems.push("language: missing".to_string()),
+        Some(lang) if serde_json::from_value::<ScriptLanguage>(lang.clone()).is_err() => {
+            problems.push(format!("language: unknown language {}", lang))
+        }
+        Some(_) => {}
+    }
+    if let Some(metadata) = obj.get("metadata").and_then(Value::as_object) {
+        for field in IMPORT_COUNT_FIELDS {
+            if let Some(n) = metadata.get(*field)
+                && !n.is_u64()
+            {
+                problems.push(format!(
+                    "metadata.{}: must be a non-negative integer, got {}",
+                    field, n
+                ));
+            }
+        }
+    }
+
+    if problems.is_empty()
+        && let Err(e) = serde_json::from_value::<Script>(value.clone())
+    {
+        problems.push(e.to_string());
+    }
+    problems
+}
+
+/// Accepts a full `sv export --format json` document, a single script from
+/// `--single`, or a bare array of scripts.
+fn parse_import(raw: &str) -> Result<ParsedImport> {
+    use serde_json::Value;
+
+    let not_export = || anyhow!("Not a ScriptVault JSON export or script");
+    let entries = match serde_json::from_str(raw).map_err(|_| not_export())? {
+        Value::Array(items) => items,
+        Value::Object(mut obj) => match obj.remove("scripts") {
+            Some(Value::Array(items)) => items,
+            Some(other) => {
+                obj.insert("scripts".to_string(), other);
+                vec![Value::Object(obj)]
+            }
+            None => vec![Value::Object(obj)],
+        },
+        _ => return Err(not_export()),
+    };
+
+    let mut parsed = ParsedImport {
+        scripts: Vec::new(),
+        invalid: Vec::new(),
+    };
+    for (index, entry) in entries.into_iter().enumerate() {
+        let problems = script_problems(&entry);
+        if problems.is_empty() {
+            parsed.scripts.push(serde_json::from_value(entry)?);
+        } else {
+            parsed.invalid.push((index, problems));
+        }
+    }
+    Ok(parsed)
+}
+
+fn format_import_problems(invalid: &[(usize, Vec<String>)]) -> String {
+    invalid
+        .iter()
+        .map(|(index, problems)| format!("  [{}] {}", index, problems.join("; ")))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+pub fn import_scripts(args: ImportArgs) -> Result<()> {
+    let config = Config::load()?;
+    config.ensure_writable()?;
+    let storage = config.get_storage_backend()?;
+    let store = crate::versions::VersionStore::new(&Config::vault_dir()?);
+
+    let mut raw = fs::read(&args.file)
+        .with_context(|| format!("Failed to read import file: {}", args.file))?;
+    match (crate::encryption::is_encrypted(&raw), args.decrypt) {
+        (true, true) => {
+            raw = crate::encryption::decrypt(&raw, &crate::encryption::passphrase(false)?)?;
+        }
+        (true, false) => {
+            return Err(anyhow!(
+                "{} is an encrypted ScriptVault bundle. Pass --decrypt to import it.",
+                args.file
+            ));
+        }
+        (false, true) => {
+            return Err(anyhow!(
+                "{} is not an encrypted ScriptVault bundle",
+                args.file
+            ));
+        }
+        (false, false) => {}
+    }
+    let ParsedImport { scripts, invalid } = if raw.starts_with(b"PK\x03\x04") {
+        parse_zip_import(&raw)?
+    } else {
+        let text = std::str::from_utf8(&raw)
+            .map_err(|_| anyhow!("Not a ScriptVault JSON export or script"))?;
+        parse_import(text)?
+    };
+
+    if !invalid.is_empty() {
+        let report = format_import_problems(&invalid);
+        if !args.skip_invalid {
+            return Err(anyhow!(
+                "{} of {} scripts in {} are invalid:\n{}\nFix them or pass --skip-invalid to import the rest.",
+                invalid.len(),
+                invalid.len() + scripts.len(),
+                args.file,
+                report
+            ));
+        }
+        eprintln!(
+            "{} Skipping {} invalid scripts:\n{}",
+            "Warning:".yellow().bold(),
+            invalid.len(),
+            report
+        );
+    }
+
+    let mut imported = 0;
+    let mut skipped = 0;
+    for mut script in scripts {
+        config.check_script_size(script.content.len() as u64)?;
+
+        if let Ok(existing) = storage.load_script_by_name(&script.name) {
+            if !args.overwrite {
+                println!(
+                    "{} Skipped (already exists): {}",
+                    "i".cyan(),
+                    script.name.yellow()
+                );
+                skipped += 1;
+                continue;
+            }
+            script.id = existing.id;
+        } else if storage.script_exists(&script.id)? {
+            script.id = uuid::Uuid::new_v4().to_string();
+        }
+        script.sync_state = SyncState::default();
+
+        storage.save_script(&script)?;
+        store.save_version(&script)?;
+        audit::record(
+            &config,
+            AuditAction::Import,
+            &script,
+            Some(script.version.clone()),
+        )?;
+        println!(
+            "{} Imported: {} {}",
+            "✓".green().bold(),
+            script.name.yellow(),
+            script.version.dimmed()
+        );
+        imported += 1;
+    }
+
+    println!();
+    if invalid.is_empty() {
+        println!("Imported {} scripts, skipped {}", imported, skipped);
+    } else {
+        println!(
+            "Imported {} scripts, skipped {}, {} invalid",
+            imported,
+            skipped,
+            invalid.len()
+        );
+    }
+    Ok(())
+}
+
+/// Regular files under `dir`, sorted by path. Hidden files and directories
+/// are left out, and so is anything matched by `.gitignore` or `.ignore`
+/// files unless `no_ignore` is set. Ignore files apply whether or not `dir`
+/// is inside a git repository.
+fn files_in_dir(dir: &Path, recursive: bool, no_ignore: bool) -> Result<Vec<PathBuf>> {
+    let mut walker = ignore::WalkBuilder::new(dir);
+    walker
+        .standard_filters(!no_ignore)
+        .hidden(true)
+        .require_git(false)
+        .max_depth((!recursive).then_some(1));
+
+    let mut files = Vec::new();
+    for entry in walker.build() {
+        let entry = entry?;
+        if entry.file_type().is_some_and(|t| t.is_file()) {
+            files.push(entry.into_path());
+        }
+    }
+    files.sort();
+    Ok(files)
+}
+
+/// The language of a file for `sv import-dir`: by extension when it is one
+/// of `SUPPORTED_EXTENSIONS`, by `#!` line when there is no extension.
+/// Anything else is not treated as a script.
+fn import_language(path: &Path, content: &str) -> Option<ScriptLanguage> {
+    match path.extension().and_then(|e| e.to_str()) {
+        Some(ext) if SUPPORTED_EXTENSIONS.contains(&ext.to_lowercase().as_str()) => {
+            Some(ScriptLanguage::from_extension(ext))
+        }
+        Some(_) => None,
+        None => ScriptLanguage::from_shebang(content),
+    }
+}
+
+pub fn import_dir(args: ImportDirArgs) -> Result<()> {
+    let config = Config::load()?;
+    config.ensure_writable()?;
+    let storage = config.get_storage_backend()?;
+    let store = crate::versions::VersionStore::new(&Config::vault_dir()?);
+
+    let dir = Path::new(&args.path);
+    if !dir.is_dir() {
+        return Err(anyhow!("Not a directory: {}", args.path));
+    }
+    let context = context::detect_context()?;
+
+    let mut imported = 0;
+    let mut skipped = 0;
+    for path in files_in_dir(dir, args.recursive, args.no_ignore)? {
+        let display = path
+            .strip_prefix(dir)
+            .unwrap_or(&path)
+            .display()
+            .to_string();
+        let Ok(content) = fs::read_to_string(&path) else {
+            skipped += 1;
+            continue;
+        };
+        let Some(language) = import_language(&path, &content) else {
+            skipped += 1;
+            continue;
+        };
+        let Some(name) = path.file_stem().and_then(|s| s.to_str()) else {
+            skipped += 1;
+            continue;
+        };
+
+        if let Err(e) = config.check_script_size(content.len() as u64) {
+            println!("{} Skipped {}: {}", "i".cyan(), display, e);
+            skipped += 1;
+            continue;
+        }
+        if storage.load_script_by_name(name).is_ok() {
+            println!(
+                "{} Skipped (already exists): {} {}",
+                "i".cyan(),
+                name.yellow(),
+                display.dimmed()
+            );
+            skipped += 1;
+            continue;
+        }
+
+        let mut script = Script::new(name.to_string(), content, language);
+        script.context = context.clone();
+        script.tags = with_default_tags(args.tags.clone(), &config.default_tags);
+        storage.save_script(&script)?;
+        store.save_version(&script)?;
+        audit::record(&config, AuditAction::Import, &script, Some(display.clone()))?;
+        println!(
+            "{} Imported: {} {}",
+            "✓".green().bold(),
+            script.name.yellow(),
+            display.dimmed()
+        );
+        imported += 1;
+    }
+
+    println!();
+    println!("Imported {} scripts, skipped {}", imported, skipped);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::script::ScriptContext;
+
+    fn make_record(script_id: &str, exit_code: i32, duration_ms: u64) -> ExecutionRecord {
+        ExecutionRecord {
+            id: uuid::Uuid::new_v4().to_string(),
+            script_id: script_id.to_string(),
+            script_version: "v1.0.0".to_string(),
+            executed_by: "test".to_string(),
+            executed_at: Utc::now(),
+            exit_code,
+            duration_ms,
+            output: None,
+            error: None,
+            context: ScriptContext {
+                directory: None,
+                git_repo: None,
+                git_branch: None,
+                environment: HashMap::new(),
+                os: None,
+                arch: None,
+                hostname: None,
+            },
+            succeeded: None,
+            resource_usage: None,
+            timings: None,
+        }
+    }
+
+    fn make_script(name: &str) -> Script {
+        Script::new(
+            name.to_string(),
+            "echo test".to_string(),
+            ScriptLanguage::Bash,
+        )
+    }
+
+    #[test]
+    fn test_vault_stats_aggregates_runs() {
+        let deploy = make_script("deploy");
+        let backup = make_script("backup");
+        let unused = make_script("unused");
+        let records = vec![
+            make_record(&deploy.id, 0, 1000),
+            make_record(&deploy.id, 0, 2000),
+            make_record(&deploy.id, 1, 500),
+            make_record(&backup.id, 2, 250),
+            make_record(&backup.id, 2, 250),
+        ];
+        let scripts = vec![deploy, backup, unused];
+
+        let stats = compute_vault_stats(&scripts, &records);
+        assert_eq!(stats.total_scripts, 3);
+        assert_eq!(stats.total_runs, 5);
+        assert_eq!(stats.successful_runs, 2);
+        assert_eq!(stats.total_duration_ms, 4000);
+        assert!((stats.success_rate() - 40.0).abs() < f64::EPSILON);
+        assert_eq!(stats.most_used, Some(("deploy".to_string(), 3)));
+        assert_eq!(stats.most_failed, Some(("backup".to_string(), 2)));
+    }
+
+    #[test]
+    fn test_vault_stats_ignores_deleted_scripts_for_highlights() {
+        let deploy = make_script("deploy");
+        let records = vec![
+            make_record("deleted-id", 1, 100),
+            make_record("deleted-id", 1, 100),
+            make_record(&deploy.id, 0, 100),
+        ];
+        let scripts = vec![deploy];
+
+        let stats = compute_vault_stats(&scripts, &records);
+        assert_eq!(stats.total_runs, 3);
+        assert_eq!(stats.most_used, Some(("deploy".to_string(), 1)));
+        assert_eq!(stats.most_failed, None);
+    }
+
+    fn make_summary(name: &str, pinned: bool) -> ScriptSummary {
+        let mut script = make_script(name);
+        script.pinned = pinned;
+        ScriptSummary::from(&script)
+    }
+
+    fn find_args() -> FindArgs {
+        FindArgs {
+            query: None,
+            here: false,
+            tag: None,
+            language: None,
+            team: false,
+            git_repo: None,
+            recent: false,
+            include_archived: false,
+            pinned: false,
+            not_run: false,
+            stale: None,
+            success: SuccessRateArgs::default(),
+            count: false,
+            porcelain: false,
+            json_lines: false,
+            tag_all: None,
+            yes: false,
+        }
+    }
+
+    #[test]
+    fn test_pinned_scripts_sort_first() {
+        let mut summaries = vec![
+            make_summary("alpha", false),
+            make_summary("zulu", true),
+            make_summary("bravo", false),
+            make_summary("mike", true),
+        ];
+        sort_for_listing(&mut summaries, false);
+        let names: Vec<&str> = summaries.iter().map(|s| s.name.as_str()).collect();
+        assert_eq!(names, ["mike", "zulu", "alpha", "bravo"]);
+    }
+
+    #[test]
+    fn test_group_for_tree_mixed_contexts() {
+        let in_repo = |name: &str, dir: &str| {
+            let mut script = make_script(name);
+            script.context.git_repo = Some("github.com/acme/api".to_string());
+            script.context.directory = Some(dir.to_string());
+            script
+        };
+        let mut scratch = make_script("scratch");
+        scratch.context.directory = Some("/home/me/tmp".to_string());
+        let mut blank = make_script("blank");
+        blank.context.directory = Some(String::new());
+        let scripts = vec![
+            in_repo("deploy", "/home/me/api"),
+            make_script("notes"),
+            scratch,
+            in_repo("build", "/home/me/api/tools"),
+            blank,
+        ];
+        let summaries: Vec<ScriptSummary> = scripts.iter().map(ScriptSummary::from).collect();
+
+        let groups: Vec<(String, Vec<&str>)> = group_for_tree(&summaries, &scripts)
+            .into_iter()
+            .map(|(label, members)| (label, members.iter().map(|s| s.name.as_str()).collect()))
+            .collect();
+        assert_eq!(
+            groups,
+            [
+                ("/home/me/tmp".to_string(), vec!["scratch"]),
+                ("github.com/acme/api".to_string(), vec!["deploy", "build"]),
+                ("Global".to_string(), vec!["notes", "blank"]),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_find_pinned_filter() {
+        let pinned = make_summary("deploy", true);
+        let unpinned = make_summary("backup", false);
+
+        let args = FindArgs {
+            pinned: true,
+            ..find_args()
+        };
+        assert!(matches_find_filters(&pinned, &args));
+        assert!(!matches_find_filters(&unpinned, &args));
+
+        let args = find_args();
+        assert!(matches_find_filters(&pinned, &args));
+        assert!(matches_find_filters(&unpinned, &args));
+    }
+
+    #[test]
+    fn test_find_not_run_and_stale_filters() {
+        let never = make_summary("never", false);
+        let mut recent = make_summary("recent", false);
+        recent.use_count = 3;
+        recent.last_run = Some(Utc::now() - chrono::Duration::days(1));
+        let mut old = make_summary("old", false);
+        old.use_count = 1;
+        old.last_run = Some(Utc::now() - chrono::Duration::days(90));
+
+        let not_run = FindArgs {
+            not_run: true,
+            ..find_args()
+        };
+        assert!(matches_find_filters(&never, &not_run));
+        assert!(!matches_find_filters(&recent, &not_run));
+        assert!(!matches_find_filters(&old, &not_run));
+
+        let stale = FindArgs {
+            stale: Some(chrono::Duration::days(30)),
+            ..find_args()
+        };
+        assert!(matches_find_filters(&never, &stale));
+        assert!(!matches_find_filters(&recent, &stale));
+        assert!(matches_find_filters(&old, &stale));
+
+        let stale_tagged = FindArgs {
+            tag: Some("ops".to_string()),
+            ..stale
+        };
+        assert!(!matches_find_filters(&old, &stale_tagged));
+    }
+
+    #[test]
+    fn test_validate_reports_dangerous_patterns() {
+        let content = "#!/bin/bash\necho cleaning\nrm -rf /\ndd if=/dev/zero of=/dev/sda\n";
+        let report = validate_content(Some("sh"), content);
+        assert_eq!(report.dangerous, ["rm -rf /", "dd if="]);
+        assert_eq!(report.line_count, 4);
+        assert_eq!(report.size_bytes, content.len());
+    }
+
+    #[test]
+    fn test_validate_clean_file_uses_shebang_without_extension() {
+        let report = validate_content(None, "#!/usr/bin/env python3\nprint('hi')\n");
+        assert!(report.dangerous.is_empty());
+        assert_eq!(report.language, ScriptLanguage::Python);
+        assert_eq!(report.extension_language, None);
+    }
+
+    #[test]
+    fn test_vault_stats_empty_history() {
+        let scripts = vec![make_script("deploy")];
+        let stats = compute_vault_stats(&scripts, &[]);
+        assert_eq!(stats.total_scripts, 1);
+        assert_eq!(stats.total_runs, 0);
+        assert_eq!(stats.success_rate(), 0.0);
+        assert_eq!(stats.most_used, None);
+    }
+
+    #[test]
+    fn test_bump_version_levels() {
+        assert_eq!(bump_version("v1.2.3", "patch"), "v1.2.4");
+        assert_eq!(bump_version("v1.2.3", "minor"), "v1.3.0");
+        assert_eq!(bump_version("v1.2.3", "major"), "v2.0.0");
+        assert_eq!(bump_version("draft", "major"), "draft.1");
+    }
+
+    #[test]
+    fn test_parse_import_accepts_export_single_and_list() {
+        let deploy = make_script("deploy");
+        let backup = make_script("backup");
+
+        let single = serde_json::to_string(&deploy).unwrap();
+        let parsed = parse_import(&single).unwrap().scripts;
+        assert_eq!(parsed.len(), 1);
+        assert_eq!(parsed[0].name, "deploy");
+
+        let full = export_json(&[deploy.clone(), backup.clone()]).unwrap();
+        let names: Vec<_> = parse_import(&full)
+            .unwrap()
+            .scripts
+            .into_iter()
+            .map(|s| s.name)
+            .collect();
+        assert_eq!(names, ["deploy", "backup"]);
+
+        let list = serde_json::to_string(&vec![backup]).unwrap();
+        assert_eq!(parse_import(&list).unwrap().scripts[0].name, "backup");
+
+        assert!(parse_import("42").is_err());
+        assert!(parse_import("not json").is_err());
+    }
+
+    #[test]
+    fn test_parse_import_reports_invalid_entries() {
+        let valid = serde_json::to_value(make_script("deploy")).unwrap();
+
+        let mut no_name = valid.clone();
+        no_name.as_object_mut().unwrap().remove("name");
+
+        let mut bad_fields = valid.clone();
+        bad_fields["language"] = "Cobol".into();
+        bad_fields["metadata"]["use_count"] = (-3).into();
+
+        let mut no_dates = valid.clone();
+        no_dates["name"] = "other".into();
+        no_dates.as_object_mut().unwrap().remove("created_at");
+
+        let raw = serde_json::json!({
+            "scripts": [no_name, valid, bad_fields, "oops", no_dates]
+        })
+        .to_string();
+        let parsed = parse_import(&raw).unwrap();
+
+        assert_eq!(parsed.scripts.len(), 1);
+        assert_eq!(parsed.scripts[0].name, "deploy");
+        assert_eq!(
+            parsed.invalid,
+            [
+                (0, vec!["name: missing".to_string()]),
+                (
+                    2,
+                    vec![
+                        "language: unknown language \"Cobol\"".to_string(),
+                        "metadata.use_count: must be a non-negative integer, got -3".to_string(),
+                    ]
+                ),
+                (3, vec!["not a JSON object".to_string()]),
+                (4, vec!["missing field `created_at`".to_string()]),
+            ]
+        );
+
+        let single = parse_import("{\"name\": \"broken\"}").unwrap();
+        assert!(single.scripts.is_empty());
+        assert_eq!(single.invalid[0].1[0], "content: missing");
+    }
+
+    fn script_with_runs(name: &str, success: u64, failure: u64) -> Script {
+        let mut script = make_script(name);
+        script.metadata.success_count = success;
+        script.metadata.failure_count = failure;
+        script
+    }
+
+    #[test]
+    fn test_success_rate_filter_thresholds() {
+        let scripts = [
+            script_with_runs("flaky", 1, 3),
+            script_with_runs("solid", 9, 1),
+            script_with_runs("even", 2, 2),
+            script_with_runs("unused", 0, 0),
+        ];
+        let names = |filter: SuccessRateArgs| -> Vec<&str> {
+            scripts
+                .iter()
+                .filter(|s| matches_success_rate(s, &filter))
+                .map(|s| s.name.as_str())
+                .collect()
+        };
+
+        let below_half = SuccessRateArgs {
+            max_success_rate: Some(50.0),
+            ..Default::default()
+        };
+        assert_eq!(names(below_half), ["flaky", "even", "unused"]);
+
+        let below_half_with_runs = SuccessRateArgs {
+            max_success_rate: Some(50.0),
+            has_runs: true,
+            ..Default::default()
+        };
+        assert_eq!(names(below_half_with_runs), ["flaky", "even"]);
+
+        let reliable = SuccessRateArgs {
+            min_success_rate: Some(90.0),
+            ..Default::default()
+        };
+        assert_eq!(names(reliable), ["solid"]);
+
+        let band = SuccessRateArgs {
+            min_success_rate: Some(25.0),
+            max_success_rate: Some(50.0),
+            ..Default::default()
+        };
+        assert_eq!(names(band), ["flaky", "even"]);
+    }
+
+    #[test]
+    fn test_last_failure_reports_failed_latest_run() {
+        let mut failed = make_record("a", 2, 10);
+        failed.error = Some("cp: cannot stat 'dist/': No such file or directory\n".to_string());
+        let mut other = make_record("b", 0, 10);
+        other.executed_at = failed.executed_at + chrono::Duration::seconds(5);
+        let mut earlier = make_record("a", 0, 10);
+        earlier.executed_at = failed.executed_at - chrono::Duration::seconds(5);
+        let records = vec![earlier, failed, other];
+
+        let record = last_failure(&records, "a").unwrap();
+        assert_eq!(record.exit_code, 2);
+        assert_eq!(
+            error_snippet(record.error.as_deref().unwrap()).unwrap(),
+            "cp: cannot stat 'dist/': No such file or directory"
+        );
+    }
+
+    #[test]
+    fn test_last_failure_ignores_recovered_script() {
+        let failed = make_record("a", 1, 10);
+        let mut ok = make_record("a", 0, 10);
+        ok.executed_at = failed.executed_at + chrono::Duration::seconds(5);
+        assert!(last_failure(&[failed, ok], "a").is_none());
+        assert!(last_failure(&[], "a").is_none());
+    }
+
+    #[test]
+    fn test_average_timings() {
+        assert_eq!(average_timings(&[]), None);
+        let timings = [
+            PhaseTimings {
+                write_us: 100,
+                spawn_us: 1_000,
+                first_output_us: Some(5_000),
+                total_us: 10_000,
+            },
+            PhaseTimings {
+                write_us: 300,
+                spawn_us: 3_000,
+                first_output_us: None,
+                total_us: 30_000,
+            },
+        ];
+        assert_eq!(
+            average_timings(&timings),
+            Some(PhaseTimings {
+                write_us: 200,
+                spawn_us: 2_000,
+                first_output_us: Some(5_000),
+                total_us: 20_000,
+            })
+        );
+    }
+
+    #[test]
+    fn test_recent_runs_newest_first_and_limited() {
+        let mut records = Vec::new();
+        for (code, secs) in [(0, 0), (1, 30), (2, 10), (3, 20)] {
+            let mut record = make_record("a", code, 10);
+            record.executed_at += chrono::Duration::seconds(secs);
+            records.push(record);
+        }
+        records.push(make_record("b", 9, 10));
+
+        let codes = |limit| -> Vec<i32> {
+            recent_runs(&records, "a", limit)
+                .iter()
+                .map(|r| r.exit_code)
+                .collect()
+        };
+        assert_eq!(codes(3), [1, 3, 2]);
+        assert_eq!(codes(10), [1, 3, 2, 0]);
+        assert!(codes(0).is_empty());
+    }
+
+    #[test]
+    fn test_run_status_glyph_follows_latest_run() {
+        let at = |record: &mut ExecutionRecord, secs: i64| {
+            record.executed_at = Utc::now() + chrono::Duration::seconds(secs);
+        };
+        let mut a_old_fail = make_record("a", 1, 10);
+        at(&mut a_old_fail, -20);
+        let a_ok = make_record("a", 0, 10);
+        let mut b_ok = make_record("b", 0, 10);
+        at(&mut b_ok, -20);
+        let b_fail = make_record("b", 3, 10);
+        let mut c_allowed = make_record("c", 1, 10);
+        c_allowed.succeeded = Some(true);
+        let mut d_record_only = make_record("d", crate::constants::NOT_EXECUTED_EXIT_CODE, 0);
+        at(&mut d_record_only, 5);
+        let d_fail = make_record("d", 2, 10);
+        // Out of order on disk; the newest run wins regardless.
+        let records = vec![
+            a_ok,
+            a_old_fail,
+            b_fail,
+            b_ok,
+            c_allowed,
+            d_fail,
+            d_record_only,
+        ];
+
+        let last = last_runs(&records);
+        let glyph = |id: &str| run_status_glyph(last.get(id).copied()).to_string();
+        assert!(glyph("a").contains('✓'));
+        assert!(glyph("b").contains('✗'));
+        assert!(glyph("c").contains('✓'));
+        assert!(glyph("d").contains('✗'));
+        assert!(glyph("never").contains('·'));
+    }
+
+    #[test]
+    fn test_find_layout_fits_terminal() {
+        let layout = |width, longest| {
+            let l = find_layout(width, longest);
+            (l.name, l.truncate, l.rule)
+        };
+        // Piped: the old fixed column, never truncated.
+        assert_eq!(layout(None, 50), (30, false, 77));
+        // Wide terminal: the column shrinks to the longest name.
+        assert_eq!(layout(Some(120), 20), (20, true, 67));
+        assert_eq!(layout(Some(120), 2), (4, true, 51));
+        // 80 columns leave 33 for a 50-character name.
+        assert_eq!(layout(Some(80), 50), (33, true, 80));
+        // Very narrow: keep a usable column and let the row wrap.
+        assert_eq!(layout(Some(40), 50), (12, true, 40));
+        assert_eq!(layout(Some(40), 8), (8, true, 40));
+    }
+
+    #[test]
+    fn test_error_snippet_truncates() {
+        assert_eq!(error_snippet("\n  \n"), None);
+        assert_eq!(
+            error_snippet("one\n\ntwo\nthree\nfour").unwrap(),
+            "one\n    two\n    three ..."
+        );
+        let long = "x".repeat(300);
+        let snippet = error_snippet(&long).unwrap();
+        assert_eq!(snippet.len(), 204);
+        assert!(snippet.ends_with(" ..."));
+    }
+
+    #[test]
+    fn test_find_by_hash_skips_same_name() {
+        let deploy = make_script("deploy");
+        let mut other = make_script("other");
+        other.content = "echo other".to_string();
+        other.metadata.hash = "different".to_string();
+        let scripts = vec![deploy.clone(), other];
+
+        let hash = &deploy.metadata.hash;
+        assert_eq!(find_by_hash(&scripts, hash, "copy").unwrap().name, "deploy");
+        assert!(find_by_hash(&scripts, hash, "deploy").is_none());
+        assert!(find_by_hash(&scripts, "missing", "copy").is_none());
+    }
+
+    #[test]
+    fn test_merge_content_concatenates_same_language() {
+        let mut first = make_script("build");
+        first.content = "#!/usr/bin/env bash\nset -e\nmake\n".to_string();
+        let mut second = make_script("deploy");
+        second.content = "#!/bin/bash\n\nrsync -a dist/ host:/srv\n".to_string();
+
+        assert_eq!(
+            merge_content(&first, &second).unwrap(),
+            "#!/usr/bin/env bash\n\
+             # --- build ---\nset -e\nmake\n\n\
+             # --- deploy ---\nrsync -a dist/ host:/srv\n"
+        );
+    }
+
+    #[test]
+    fn test_merge_content_rejects_mixed_languages() {
+        let first = make_script("build");
+        let second = Script::new(
+            "report".to_string(),
+            "print('hi')".to_string(),
+            ScriptLanguage::Python,
+        );
+        let err = merge_content(&first, &second).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Cannot merge build (bash) with report (python): scripts must be in the same language"
+        );
+    }
+
+    #[test]
+    fn test_files_in_dir_skips_hidden_and_respects_recursion() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let root = dir.path();
+        fs::create_dir_all(root.join("sub/deeper")).unwrap();
+        fs::create_dir_all(root.join(".git")).unwrap();
+        for file in [
+            "b.sh",
+            "a.py",
+            ".hidden.sh",
+            ".git/hook.sh",
+            "sub/c.rb",
+            "sub/deeper/d.sh",
+        ] {
+            fs::write(root.join(file), "echo hi\n").unwrap();
+        }
+
+        let names = |files: Vec<PathBuf>| -> Vec<String> {
+            files
+                .iter()
+                .map(|p| p.strip_prefix(root).unwrap().display().to_string())
+                .collect()
+        };
+        assert_eq!(
+            names(files_in_dir(root, false, false).unwrap()),
+            ["a.py", "b.sh"]
+        );
+        assert_eq!(
+            names(files_in_dir(root, true, false).unwrap()),
+            ["a.py", "b.sh", "sub/c.rb", "sub/deeper/d.sh"]
+        );
+    }
+
+    #[test]
+    fn test_files_in_dir_honors_gitignore() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let root = dir.path();
+        fs::create_dir_all(root.join("vendor/lib")).unwrap();
+        fs::write(root.join(".gitignore"), "vendor/\n*.gen.sh\n").unwrap();
+        for file in ["deploy.sh", "build.gen.sh", "vendor/lib/helper.sh"] {
+            fs::write(root.join(file), "echo hi\n").unwrap();
+        }
+
+        let names = |files: Vec<PathBuf>| -> Vec<String> {
+            files
+                .iter()
+                .map(|p| p.strip_prefix(root).unwrap().display().to_string())
+                .collect()
+        };
+        assert_eq!(
+            names(files_in_dir(root, true, false).unwrap()),
+            ["deploy.sh"]
+        );
+        assert_eq!(
+            names(files_in_dir(root, true, true).unwrap()),
+            ["build.gen.sh", "deploy.sh", "vendor/lib/helper.sh"]
+        );
+    }
+
+    #[test]
+    fn test_refine_shell_language_detects_bashisms() {
+        let refine = |content: &str| refine_shell_language(ScriptLanguage::Shell, content);
+        assert_eq!(
+            refine("if [[ -n $x ]]; then echo set; fi\n"),
+            (ScriptLanguage::Bash, vec!["[[ ]] tests"])
+        );
+        assert_eq!(
+            refine(
+                "#!/bin/sh\nhosts=(a b)\nfor h in \"${hosts[@]}\"; do\n  grep x <<< \"$h\"\ndone\n"
+            ),
+            (ScriptLanguage::Bash, vec!["arrays", "here-strings"])
+        );
+        assert_eq!(
+            refine("# [[ only in a comment ]]\nif [ -n \"$x\" ]; then\n  y=$(date)\nfi\n"),
+            (ScriptLanguage::Shell, vec![])
+        );
+        assert_eq!(
+            refine("#!/bin/zsh\n[[ -n $x ]]\n"),
+            (ScriptLanguage::Shell, vec![])
+        );
+        assert_eq!(
+            refine_shell_language(ScriptLanguage::Python, "[[ -n $x ]]\n"),
+            (ScriptLanguage::Python, vec![])
+        );
+    }
+
+    #[test]
+    fn test_import_language_by_extension_or_shebang() {
+        let lang = |path: &str, content: &str| import_language(Path::new(path), content);
+        assert_eq!(lang("deploy.sh", ""), Some(ScriptLanguage::Shell));
+        assert_eq!(lang("Report.PY", ""), Some(ScriptLanguage::Python));
+        assert_eq!(
+            lang("backup", "#!/usr/bin/env bash\necho\n"),
+            Some(ScriptLanguage::Bash)
+        );
+        assert_eq!(lang("notes", "just text\n"), None);
+        assert_eq!(lang("README.md", "#!/bin/sh\n"), None);
+    }
+
+    #[test]
+    fn test_select_language_preselects_detected() {
+        let lang = select_language(&ScriptLanguage::Python, |items, default| {
+            assert_eq!(items[default], "python (detected)");
+            Ok(default)
+        })
+        .unwrap();
+        assert_eq!(lang, ScriptLanguage::Python);
+    }
+
+    #[test]
+    fn test_select_language_override() {
+        let lang = select_language(&ScriptLanguage::Unknown, |items, _| {
+            Ok(items.iter().position(|i| i == "ruby").unwrap())
+        })
+        .unwrap();
+        assert_eq!(lang, ScriptLanguage::Ruby);
+
+        assert!(select_language(&ScriptLanguage::Bash, |items, _| Ok(items.len())).is_err());
+    }
+
+    #[test]
+    fn test_porcelain_line_fields() {
+        let mut script = Script::new(
+            "deploy".to_string(),
+            "echo hi\n".to_string(),
+            ScriptLanguage::Python,
+        );
+        let mut summary = ScriptSummary::from(&script);
+        assert_eq!(porcelain_line(&summary), "deploy\tv1.0.0\tpython\t0\t");
+
+        script.metadata.use_count = 4;
+        script.metadata.last_run = Some(
+            chrono::DateTime::parse_from_rfc3339("2024-03-01T12:30:00Z")
+                .unwrap()
+                .with_timezone(&Utc),
+        );
+        summary = ScriptSummary::from(&script);
+        assert_eq!(
+            porcelain_line(&summary),
+            "deploy\tv1.0.0\tpython\t4\t2024-03-01T12:30:00Z"
+        );
+    }
+
+    #[test]
+    fn test_with_default_tags() {
+        let tags = |list: &[&str]| -> Vec<String> { list.iter().map(|t| t.to_string()).collect() };
+        assert_eq!(
+            with_default_tags(tags(&["deploy"]), &tags(&["team-ops", "managed"])),
+            ["deploy", "team-ops", "managed"]
+        );
+        assert_eq!(
+            with_default_tags(
+                tags(&["managed", "deploy"]),
+                &tags(&["team-ops", "managed"])
+            ),
+            ["managed", "deploy", "team-ops"]
+        );
+        assert_eq!(with_default_tags(Vec::new(), &[]), Vec::<String>::new());
+    }
+
+    #[test]
+    fn test_export_zip_writes_one_entry_per_script() {
+        use std::io::Read;
+
+        let deploy = make_script("deploy");
+        let mut report = Script::new(
+            "reports/weekly".to_string(),
+            "print('weekly')\n".to_string(),
+            ScriptLanguage::Python,
+        );
+        report.tags = vec!["reports".to_string()];
+        let bytes = export_zip(&[deploy, report]).unwrap();
+
+        let mut archive = zip::ZipArchive::new(std::io::Cursor::new(bytes)).unwrap();
+        let mut names: Vec<&str> = archive.file_names().collect();
+        names.sort();
+        assert_eq!(names, ["deploy.sh", "manifest.json", "reports_weekly.py"]);
+
+        let mut content = String::new();
+        archive
+            .by_name("reports_weekly.py")
+            .unwrap()
+            .read_to_string(&mut content)
+            .unwrap();
+        assert_eq!(content, "print('weekly')\n");
+
+        let mut manifest = String::new();
+        archive
+            .by_name("manifest.json")
+            .unwrap()
+            .read_to_string(&mut manifest)
+            .unwrap();
+        let manifest: serde_json::Value = serde_json::from_str(&manifest).unwrap();
+        assert_eq!(manifest["total_scripts"], 2);
+        assert_eq!(manifest["scripts"][1]["file"], "reports_weekly.py");
+        assert_eq!(manifest["scripts"][1]["name"], "reports/weekly");
+        assert_eq!(manifest["scripts"][1]["tags"][0], "reports");
+        assert!(manifest["scripts"][1].get("content").is_none());
+    }
+
+    #[test]
+    fn test_zip_export_round_trips_through_import() {
+        let mut deploy = make_script("deploy");
+        deploy.version = "v1.2.0".to_string();
+        deploy.tags = vec!["ops".to_string(), "prod".to_string()];
+        deploy.description = Some("Ship it".to_string());
+        deploy.visibility = Visibility::Team;
+        deploy.success_exit_codes = vec![0, 3];
+        deploy.env = HashMap::from([("REGION".to_string(), "eu".to_string())]);
+        deploy.params = vec![ScriptParam::parse("target=staging").unwrap()];
+        let bytes = export_zip(std::slice::from_ref(&deploy)).unwrap();
+
+        let parsed = parse_zip_import(&bytes).unwrap();
+        assert!(parsed.invalid.is_empty());
+        let [imported] = parsed.scripts.as_slice() else {
+            panic!("expected one script");
+        };
+        assert_eq!(imported.name, "deploy");
+        assert_eq!(imported.content, deploy.content);
+        assert_eq!(imported.metadata.hash, deploy.metadata.hash);
+        assert_eq!(imported.version, "v1.2.0");
+        assert_eq!(imported.language, ScriptLanguage::Bash);
+        assert_eq!(imported.tags, deploy.tags);
+        assert_eq!(imported.description, deploy.description);
+        assert_eq!(imported.author, deploy.author);
+        assert_eq!(imported.visibility, Visibility::Team);
+        assert_eq!(imported.created_at, deploy.created_at);
+        assert_eq!(imported.success_exit_codes, [0, 3]);
+        assert_eq!(imported.env, deploy.env);
+        assert_eq!(imported.params, deploy.params);
+    }
+
+    #[test]
+    fn test_zip_import_without_manifest_uses_file_names() {
+        use std::io::Write;
+
+        let mut zip = zip::ZipWriter::new(std::io::Cursor::new(Vec::new()));
+        let options = zip::write::SimpleFileOptions::default();
+        zip.add_directory("tools/", options).unwrap();
+        zip.start_file("tools/report.py", options).unwrap();
+        zip.write_all(b"print('report')\n").unwrap();
+        zip.start_file("backup.sh", options).unwrap();
+        zip.write_all(b"echo backup\n").unwrap();
+        zip.start_file("blob.sh", options).unwrap();
+        zip.write_all(&[0xff, 0xfe]).unwrap();
+        let bytes = zip.finish().unwrap().into_inner();
+
+        let parsed = parse_zip_import(&bytes).unwrap();
+        let found: Vec<(&str, &ScriptLanguage)> = parsed
+            .scripts
+            .iter()
+            .map(|s| (s.name.as_str(), &s.language))
+            .collect();
+        assert_eq!(
+            found,
+            [
+                ("report", &ScriptLanguage::Python),
+                ("backup", &ScriptLanguage::Shell)
+            ]
+        );
+        assert_eq!(
+            parsed.invalid,
+            [(2, vec!["blob.sh: file is not valid UTF-8".to_string()])]
+        );
+    }
+
+    #[test]
+    fn test_zip_import_reports_files_missing_from_manifest() {
+        use std::io::Write;
+
+        let mut zip = zip::ZipWriter::new(std::io::Cursor::new(Vec::new()));
+        let options = zip::write::SimpleFileOptions::default();
+        zip.start_file(ZIP_MANIFEST, options).unwrap();
+        zip.write_all(br#"{"scripts": [{"file": "gone.sh", "name": "gone"}]}"#)
+            .unwrap();
+        let bytes = zip.finish().unwrap().into_inner();
+
+        let parsed = parse_zip_import(&bytes).unwrap();
+        assert!(parsed.scripts.is_empty());
+        assert_eq!(
+            parsed.invalid,
+            [(0, vec!["gone.sh: missing from archive".to_string()])]
+        );
+    }
+}
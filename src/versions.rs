@@ -125,6 +125,16 @@ impl VersionStore {
     }
 }
 
+/// Unified diff of two texts with three lines of context, or an empty
+/// string when they are identical.
+pub fn unified_diff(old_label: &str, new_label: &str, old: &str, new: &str) -> String {
+    similar::TextDiff::from_lines(old, new)
+        .unified_diff()
+        .context_radius(3)
+        .header(old_label, new_label)
+        .to_string()
+}
+
 fn sanitize_version(v: &str) -> String {
     v.chars()
         .map(|c| {
@@ -164,6 +174,9 @@ mod tests {
                 git_repo: None,
                 git_branch: None,
                 environment: HashMap::new(),
+                os: None,
+                arch: None,
+                hostname: None,
             },
             metadata: ScriptMetadata {
                 hash: format!("hash-{}", version),
@@ -178,6 +191,15 @@ mod tests {
             },
             visibility: Visibility::Private,
             sync_state: SyncState::default(),
+            archived: false,
+            pinned: false,
+            trusted: false,
+            safety_acknowledged: false,
+            pre_run: None,
+            post_run: None,
+            params: Vec::new(),
+            env: HashMap::new(),
+            success_exit_codes: vec![0],
         }
     }
 
@@ -249,4 +271,22 @@ mod tests {
         store.purge_script("test-id").unwrap();
         assert_eq!(store.list_versions("test-id").unwrap().len(), 0);
     }
+
+    #[test]
+    fn test_unified_diff_added_and_removed_lines() {
+        let old = "#!/bin/bash\nset -e\necho one\necho two\n";
+        let new = "#!/bin/bash\nset -e\necho two\necho three\n";
+        let diff = unified_diff("vault", "file", old, new);
+
+        assert!(diff.starts_with("--- vault\n+++ file\n@@ -1,4 +1,4 @@\n"));
+        let changed: Vec<&str> = diff
+            .lines()
+            .skip(2)
+            .filter(|l| l.starts_with('+') || l.starts_with('-'))
+            .collect();
+        assert_eq!(changed, ["-echo one", "+echo three"]);
+        assert!(diff.contains("\n set -e\n"));
+
+        assert_eq!(unified_diff("vault", "file", old, old), "");
+    }
 }
@@ -0,0 +1,248 @@
+use crate::script::ExecutionRecord;
+use anyhow::Result;
+use serde_json::{Value, json};
+
+const SECRET_MARKERS: &[&str] = &[
+    "TOKEN",
+    "SECRET",
+    "PASSWORD",
+    "PASSWD",
+    "KEY",
+    "AUTH",
+    "CREDENTIAL",
+];
+const REDACTED: &str = "[redacted]";
+
+/// Total time `send` may spend across all attempts, so a slow or dead
+/// endpoint delays `sv run` by at most this much after the script exits.
+#[cfg(feature = "webhook")]
+const BUDGET: std::time::Duration = std::time::Duration::from_millis(1500);
+#[cfg(feature = "webhook")]
+const MAX_ATTEMPTS: u32 = 3;
+
+fn looks_secret(key: &str) -> bool {
+    let upper = key.to_uppercase();
+    SECRET_MARKERS.iter().any(|m| upper.contains(m))
+}
+
+/// Builds the webhook body. Environment values whose names look like
+/// secrets are redacted, and so is any occurrence of those values in the
+/// captured output and error.
+pub fn build_payload(record: &ExecutionRecord, script_name: &str) -> Result<Value> {
+    let success = record.was_successful();
+    let secrets: Vec<&str> = record
+        .context
+        .environment
+        .iter()
+        .filter(|(key, value)| looks_secret(key) && !value.is_empty())
+        .map(|(_, value)| value.as_str())
+        .collect();
+
+    let mut record = serde_json::to_value(record)?;
+    if let Some(env) = record
+        .pointer_mut("/context/environment")
+        .and_then(Value::as_object_mut)
+    {
+        for (key, value) in env.iter_mut() {
+            if looks_secret(key) {
+                *value = Value::String(REDACTED.to_string());
+            }
+        }
+    }
+    for field in ["output", "error"] {
+        if let Some(Value::String(text)) = record.get_mut(field) {
+            for secret in &secrets {
+                *text = text.replace(secret, REDACTED);
+            }
+        }
+    }
+
+    Ok(json!({
+        "event": "script.executed",
+        "script_name": script_name,
+        "success": success,
+        "record": record,
+    }))
+}
+
+/// Posts the payload, retrying transport errors and 5xx responses. All
+/// attempts and backoff share `BUDGET`, so a dead endpoint can't hold up
+/// the CLI.
+#[cfg(feature = "webhook")]
+pub fn send(url: &str, payload: &Value) -> Result<()> {
+    let deadline = std::time::Instant::now() + BUDGET;
+    let agent = ureq::AgentBuilder::new().build();
+
+    let mut attempts = 0;
+    let mut last_err = None;
+    for attempt in 1..=MAX_ATTEMPTS {
+        let remaining = deadline.saturating_duration_since(std::time::Instant::now());
+        if remaining.is_zero() {
+            break;
+        }
+        attempts = attempt;
+        match agent
+            .post(url)
+            .timeout(remaining)
+            .send_json(payload.clone())
+        {
+            Ok(_) => return Ok(()),
+            Err(ureq::Error::Status(code, _)) if code < 500 => {
+                return Err(anyhow::anyhow!("Webhook returned error {}", code));
+            }
+            Err(e) => {
+                tracing::debug!(attempt, error = %e, "webhook delivery failed");
+                last_err = Some(e);
+            }
+        }
+        if attempt < MAX_ATTEMPTS {
+            let backoff = std::time::Duration::from_millis(200 * attempt as u64);
+            let remaining = deadline.saturating_duration_since(std::time::Instant::now());
+            std::thread::sleep(backoff.min(remaining));
+        }
+    }
+
+    Err(anyhow::anyhow!(
+        "Webhook delivery failed after {} attempts: {}",
+        attempts,
+        last_err.map(|e| e.to_string()).unwrap_or_default()
+    ))
+}
+
+#[cfg(not(feature = "webhook"))]
+pub fn send(_url: &str, _payload: &Value) -> Result<()> {
+    Err(anyhow::anyhow!(
+        "Webhooks are not available in this build (rebuild with --features webhook)"
+    ))
+}
+
+pub fn notify(url: &str, record: &ExecutionRecord, script_name: &str) -> Result<()> {
+    let payload = build_payload(record, script_name)?;
+    send(url, &payload)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::script::ScriptContext;
+    use std::collections::HashMap;
+
+    fn make_record(exit_code: i32) -> ExecutionRecord {
+        let mut environment = HashMap::new();
+        environment.insert("NODE_ENV".to_string(), "production".to_string());
+        environment.insert("GITHUB_TOKEN".to_string(), "ghp_abc123".to_string());
+        environment.insert("db_password".to_string(), "hunter2".to_string());
+
+        ExecutionRecord {
+            id: "rec-1".to_string(),
+            script_id: "script-1".to_string(),
+            script_version: "v1.0.2".to_string(),
+            executed_by: "test".to_string(),
+            executed_at: chrono::Utc::now(),
+            exit_code,
+            duration_ms: 1940,
+            output: Some("done\n".to_string()),
+            error: None,
+            context: ScriptContext {
+                directory: Some("/home/user/app".to_string()),
+                git_repo: None,
+                git_branch: None,
+                environment,
+                os: None,
+                arch: None,
+                hostname: None,
+            },
+            succeeded: None,
+            resource_usage: None,
+            timings: None,
+        }
+    }
+
+    #[test]
+    fn test_payload_redacts_secret_env_values() {
+        let payload = build_payload(&make_record(0), "deploy").unwrap();
+        let env = &payload["record"]["context"]["environment"];
+        assert_eq!(env["NODE_ENV"], "production");
+        assert_eq!(env["GITHUB_TOKEN"], REDACTED);
+        assert_eq!(env["db_password"], REDACTED);
+    }
+
+    #[test]
+    fn test_payload_redacts_secret_values_in_output() {
+        let mut record = make_record(1);
+        record.output = Some("pushing with ghp_abc123\n".to_string());
+        record.error = Some("auth failed for hunter2\n".to_string());
+
+        let payload = build_payload(&record, "deploy").unwrap();
+        assert_eq!(payload["record"]["output"], "pushing with [redacted]\n");
+        assert_eq!(payload["record"]["error"], "auth failed for [redacted]\n");
+    }
+
+    #[test]
+    fn test_payload_shape() {
+        let payload = build_payload(&make_record(2), "deploy").unwrap();
+        assert_eq!(payload["event"], "script.executed");
+        assert_eq!(payload["script_name"], "deploy");
+        assert_eq!(payload["success"], false);
+        assert_eq!(payload["record"]["exit_code"], 2);
+        assert_eq!(payload["record"]["script_version"], "v1.0.2");
+    }
+
+    #[cfg(feature = "webhook")]
+    #[test]
+    fn test_send_posts_payload_to_mock_server() {
+        let mut server = mockito::Server::new();
+        let mock = server
+            .mock("POST", "/hook")
+            .match_header("content-type", "application/json")
+            .match_body(mockito::Matcher::PartialJson(json!({
+                "event": "script.executed",
+                "script_name": "deploy",
+                "success": true,
+                "record": {
+                    "id": "rec-1",
+                    "exit_code": 0,
+                    "duration_ms": 1940,
+                    "context": { "environment": { "GITHUB_TOKEN": REDACTED } }
+                }
+            })))
+            .with_status(200)
+            .expect(1)
+            .create();
+
+        notify(&format!("{}/hook", server.url()), &make_record(0), "deploy").unwrap();
+        mock.assert();
+    }
+
+    #[cfg(feature = "webhook")]
+    #[test]
+    fn test_send_retries_server_errors() {
+        let mut server = mockito::Server::new();
+        let mock = server
+            .mock("POST", "/hook")
+            .with_status(503)
+            .expect(MAX_ATTEMPTS as usize)
+            .create();
+
+        let started = std::time::Instant::now();
+        let result = notify(&format!("{}/hook", server.url()), &make_record(1), "deploy");
+        assert!(result.is_err());
+        assert!(started.elapsed() <= BUDGET + std::time::Duration::from_millis(250));
+        mock.assert();
+    }
+
+    #[cfg(feature = "webhook")]
+    #[test]
+    fn test_send_does_not_retry_client_errors() {
+        let mut server = mockito::Server::new();
+        let mock = server
+            .mock("POST", "/hook")
+            .with_status(404)
+            .expect(1)
+            .create();
+
+        let result = notify(&format!("{}/hook", server.url()), &make_record(0), "deploy");
+        assert!(result.is_err());
+        mock.assert();
+    }
+}
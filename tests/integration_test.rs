@@ -25,6 +25,9 @@ fn make_script(name: &str, content: &str) -> Script {
             git_repo: None,
             git_branch: None,
             environment: HashMap::new(),
+            os: None,
+            arch: None,
+            hostname: None,
         },
         metadata: ScriptMetadata {
             hash: uuid::Uuid::new_v4().to_string(),
@@ -39,6 +42,15 @@ fn make_script(name: &str, content: &str) -> Script {
         },
         visibility: Visibility::Private,
         sync_state: SyncState::default(),
+        archived: false,
+        pinned: false,
+        trusted: false,
+        safety_acknowledged: false,
+        pre_run: None,
+        post_run: None,
+        params: Vec::new(),
+        env: HashMap::new(),
+        success_exit_codes: vec![0],
     }
 }
 fn storage(tmp: &TempDir) -> LocalStorage {
@@ -325,7 +337,13 @@ fn test_execution_record_successful() {
             git_repo: None,
             git_branch: None,
             environment: HashMap::new(),
+            os: None,
+            arch: None,
+            hostname: None,
         },
+        succeeded: None,
+        resource_usage: None,
+        timings: None,
     };
     assert!(record.was_successful());
 }
@@ -347,7 +365,13 @@ fn test_execution_record_failed() {
             git_repo: None,
             git_branch: None,
             environment: HashMap::new(),
+            os: None,
+            arch: None,
+            hostname: None,
         },
+        succeeded: None,
+        resource_usage: None,
+        timings: None,
     };
     assert!(!record.was_successful());
 }
@@ -423,12 +447,18 @@ fn test_contexts_match_by_git_repo() {
         git_repo: Some("github.com/user/repo".to_string()),
         git_branch: Some("main".to_string()),
         environment: HashMap::new(),
+        os: None,
+        arch: None,
+        hostname: None,
     };
     let ctx2 = ScriptContext {
         directory: Some("/home/user/b".to_string()),
         git_repo: Some("github.com/user/repo".to_string()),
         git_branch: Some("develop".to_string()),
         environment: HashMap::new(),
+        os: None,
+        arch: None,
+        hostname: None,
     };
     assert!(contexts_match(&ctx1, &ctx2));
 }
@@ -440,6 +470,9 @@ fn test_contexts_match_by_exact_directory() {
         git_repo: None,
         git_branch: None,
         environment: HashMap::new(),
+        os: None,
+        arch: None,
+        hostname: None,
     };
     assert!(contexts_match(&ctx, &ctx.clone()));
 }
@@ -451,12 +484,18 @@ fn test_contexts_match_by_parent_directory() {
         git_repo: None,
         git_branch: None,
         environment: HashMap::new(),
+        os: None,
+        arch: None,
+        hostname: None,
     };
     let child = ScriptContext {
         directory: Some("/home/user/project/src".to_string()),
         git_repo: None,
         git_branch: None,
         environment: HashMap::new(),
+        os: None,
+        arch: None,
+        hostname: None,
     };
     assert!(contexts_match(&parent, &child));
     assert!(contexts_match(&child, &parent));
@@ -469,12 +508,18 @@ fn test_contexts_do_not_match_different_repos() {
         git_repo: Some("github.com/user/repo1".to_string()),
         git_branch: None,
         environment: HashMap::new(),
+        os: None,
+        arch: None,
+        hostname: None,
     };
     let ctx2 = ScriptContext {
         directory: Some("/home/user/b".to_string()),
         git_repo: Some("github.com/user/repo2".to_string()),
         git_branch: None,
         environment: HashMap::new(),
+        os: None,
+        arch: None,
+        hostname: None,
     };
     assert!(!contexts_match(&ctx1, &ctx2));
 }
@@ -528,3 +573,2611 @@ fn test_script_not_found_by_id_errors() {
             .is_err()
     );
 }
+
+#[cfg(unix)]
+fn sv_in(home: &TempDir, args: &[&str]) -> std::process::Output {
+    std::process::Command::new(env!("CARGO_BIN_EXE_sv"))
+        .args(args)
+        .env("SCRIPTVAULT_HOME", home.path())
+        .env("SCRIPTVAULT_CI", "1")
+        .current_dir(home.path())
+        .output()
+        .unwrap()
+}
+
+#[cfg(unix)]
+#[test]
+fn test_sv_run_exit_code_mirrors_script() {
+    let home = TempDir::new().unwrap();
+    let sv = |args: &[&str]| sv_in(&home, args);
+
+    std::fs::write(home.path().join("fails.sh"), "exit 7\n").unwrap();
+    std::fs::write(home.path().join("passes.sh"), "exit 0\n").unwrap();
+    assert!(
+        sv(&["save", "fails.sh", "--name", "fails", "--yes"])
+            .status
+            .success()
+    );
+    assert!(
+        sv(&["save", "passes.sh", "--name", "passes", "--yes"])
+            .status
+            .success()
+    );
+
+    assert_eq!(sv(&["run", "fails"]).status.code(), Some(7));
+    assert_eq!(sv(&["run", "passes"]).status.code(), Some(0));
+
+    let history = std::fs::read_to_string(home.path().join("history.jsonl")).unwrap();
+    assert_eq!(history.lines().count(), 2);
+}
+
+#[cfg(unix)]
+#[test]
+fn test_run_add_path_prepends_directories_to_path() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let home = TempDir::new().unwrap();
+    // sv writes its default config on first use; add a project-local entry.
+    assert!(sv_in(&home, &["list"]).status.success());
+    let config_path = home.path().join("config.json");
+    let mut config: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(&config_path).unwrap()).unwrap();
+    config["prepend_path"] = serde_json::json!(["node_modules/.bin", "not-here"]);
+    std::fs::write(&config_path, config.to_string()).unwrap();
+
+    for (dir, tool) in [("tools", "greet-tool"), ("node_modules/.bin", "node-tool")] {
+        let dir = home.path().join(dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        let tool = dir.join(tool);
+        std::fs::write(&tool, "#!/bin/sh\necho \"hello from $0\"\n").unwrap();
+        std::fs::set_permissions(&tool, std::fs::Permissions::from_mode(0o755)).unwrap();
+    }
+    std::fs::write(
+        home.path().join("greet.sh"),
+        "greet-tool\nnode-tool\necho \"PATH=$PATH\"\n",
+    )
+    .unwrap();
+    assert!(
+        sv_in(&home, &["save", "greet.sh", "--yes"])
+            .status
+            .success()
+    );
+
+    let run = sv_in(&home, &["run", "greet", "--add-path", "tools"]);
+    let stdout = String::from_utf8_lossy(&run.stdout);
+    assert!(
+        run.status.success(),
+        "{}",
+        String::from_utf8_lossy(&run.stderr)
+    );
+    assert!(stdout.contains("greet-tool"));
+    assert!(stdout.contains("node-tool"));
+    let tools = home.path().join("tools").canonicalize().unwrap();
+    let bin = home
+        .path()
+        .join("node_modules/.bin")
+        .canonicalize()
+        .unwrap();
+    assert!(stdout.contains(&format!("PATH={}:{}:", tools.display(), bin.display())));
+
+    let missing = sv_in(&home, &["run", "greet", "--add-path", "nope"]);
+    assert!(!missing.status.success());
+    assert!(
+        String::from_utf8_lossy(&missing.stderr).contains("--add-path nope: no such directory")
+    );
+}
+
+#[cfg(unix)]
+#[test]
+fn test_run_output_format_json_summarizes_run() {
+    let home = TempDir::new().unwrap();
+    std::fs::write(
+        home.path().join("check.sh"),
+        "echo checking\necho 'disk low' >&2\nexit 3\n",
+    )
+    .unwrap();
+    std::fs::write(home.path().join("quiet.sh"), "true\n").unwrap();
+    for file in ["check.sh", "quiet.sh"] {
+        assert!(sv_in(&home, &["save", file, "--yes"]).status.success());
+    }
+
+    let run = sv_in(&home, &["run", "check", "--output-format", "json"]);
+    assert_eq!(run.status.code(), Some(3));
+    // The script's output is captured, not streamed, so stdout is pure JSON.
+    let summary: serde_json::Value = serde_json::from_slice(&run.stdout).unwrap();
+    assert_eq!(summary["script"], "check");
+    assert_eq!(summary["script_version"], "v1.0.0");
+    assert_eq!(summary["exit_code"], 3);
+    assert_eq!(summary["succeeded"], false);
+    assert!(summary["duration_ms"].is_u64());
+    assert_eq!(summary["output"], "checking\n");
+    assert_eq!(summary["error"], "disk low\n");
+    assert!(!String::from_utf8_lossy(&run.stderr).contains("disk low"));
+
+    let run = sv_in(&home, &["run", "quiet", "--output-format", "json"]);
+    assert!(run.status.success());
+    let summary: serde_json::Value = serde_json::from_slice(&run.stdout).unwrap();
+    assert_eq!(summary["exit_code"], 0);
+    assert_eq!(summary["succeeded"], true);
+    assert!(summary["output"].is_null());
+    assert!(summary["error"].is_null());
+
+    // The run is still recorded like any other.
+    let history = std::fs::read_to_string(home.path().join("history.jsonl")).unwrap();
+    assert_eq!(history.lines().count(), 2);
+}
+
+#[cfg(unix)]
+#[test]
+fn test_run_output_format_json_keeps_notices_off_stdout() {
+    let home = TempDir::new().unwrap();
+    std::fs::write(
+        home.path().join("flaky.sh"),
+        "if [ -f tried ]; then exit 0; fi\ntouch tried\nexit 1\n",
+    )
+    .unwrap();
+    assert!(
+        sv_in(&home, &["save", "flaky.sh", "--yes"])
+            .status
+            .success()
+    );
+
+    let run = sv_in(
+        &home,
+        &[
+            "run",
+            "flaky",
+            "--output-format",
+            "json",
+            "--show-env",
+            "--capture-timing",
+            "--measure-only",
+            "--until-success",
+            "--retry-delay",
+            "0",
+        ],
+    );
+    assert!(run.status.success());
+    let summary: serde_json::Value = serde_json::from_slice(&run.stdout).unwrap();
+    assert_eq!(summary["succeeded"], true);
+    let stderr = String::from_utf8_lossy(&run.stderr);
+    assert!(stderr.contains("Attempt 2/5"), "{stderr}");
+    assert!(stderr.contains("Captured environment:"), "{stderr}");
+    assert!(stderr.contains("Retrying in 0s"), "{stderr}");
+}
+
+#[cfg(unix)]
+#[test]
+fn test_failing_pre_run_hook_prevents_execution() {
+    let home = TempDir::new().unwrap();
+    std::fs::write(home.path().join("guarded.sh"), "touch ran.marker\n").unwrap();
+    let saved = sv_in(
+        &home,
+        &["save", "guarded.sh", "--pre-run", "exit 3", "--yes"],
+    );
+    assert!(saved.status.success());
+
+    let run = sv_in(&home, &["run", "guarded"]);
+    assert!(!run.status.success());
+    assert!(!home.path().join("ran.marker").exists());
+}
+
+#[cfg(unix)]
+#[test]
+fn test_post_run_hook_runs_after_success() {
+    let home = TempDir::new().unwrap();
+    std::fs::write(home.path().join("main.sh"), "touch ran.marker\n").unwrap();
+    let saved = sv_in(
+        &home,
+        &[
+            "save",
+            "main.sh",
+            "--pre-run",
+            "test ! -e ran.marker",
+            "--post-run",
+            "test -e ran.marker && touch post.marker",
+            "--yes",
+        ],
+    );
+    assert!(saved.status.success());
+
+    let run = sv_in(&home, &["run", "main"]);
+    assert!(run.status.success());
+    assert!(home.path().join("ran.marker").exists());
+    assert!(home.path().join("post.marker").exists());
+}
+
+#[cfg(unix)]
+#[test]
+fn test_save_enforces_max_script_size() {
+    let home = TempDir::new().unwrap();
+    // Let sv write its default config under this home first, then lower the limit.
+    assert!(sv_in(&home, &["list"]).status.success());
+    let config_path = home.path().join("config.json");
+    let mut config: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(&config_path).unwrap()).unwrap();
+    config["max_script_size_bytes"] = 64.into();
+    std::fs::write(&config_path, config.to_string()).unwrap();
+
+    std::fs::write(home.path().join("small.sh"), "echo ok\n").unwrap();
+    std::fs::write(home.path().join("big.sh"), "echo x\n".repeat(20)).unwrap();
+
+    assert!(
+        sv_in(&home, &["save", "small.sh", "--yes"])
+            .status
+            .success()
+    );
+
+    let big = sv_in(&home, &["save", "big.sh", "--yes"]);
+    assert!(!big.status.success());
+    assert!(String::from_utf8_lossy(&big.stderr).contains("exceeds the 64 byte limit"));
+    assert!(!sv_in(&home, &["cat", "big"]).status.success());
+
+    // A template under the limit can still fill in to more than it.
+    std::fs::write(home.path().join("say.sh"), "echo {{text}}\n").unwrap();
+    assert!(
+        sv_in(&home, &["template", "add", "say.sh"])
+            .status
+            .success()
+    );
+    let text = format!("text={}", "x".repeat(80));
+    let templated = sv_in(
+        &home,
+        &[
+            "save",
+            "--from-template",
+            "say",
+            "--name",
+            "loud",
+            "--var",
+            &text,
+            "--yes",
+        ],
+    );
+    assert!(!templated.status.success());
+    assert!(String::from_utf8_lossy(&templated.stderr).contains("exceeds the 64 byte limit"));
+
+    // So can two scripts that each fit on their own.
+    std::fs::write(home.path().join("half.sh"), "echo first half of the job\n").unwrap();
+    assert!(sv_in(&home, &["save", "half.sh", "--yes"]).status.success());
+    let merge = sv_in(
+        &home,
+        &["merge", "small", "half", "--name", "both", "--yes"],
+    );
+    assert!(!merge.status.success());
+    assert!(String::from_utf8_lossy(&merge.stderr).contains("exceeds the 64 byte limit"));
+    assert!(!sv_in(&home, &["cat", "both"]).status.success());
+}
+
+#[cfg(unix)]
+#[test]
+fn test_run_stored_version() {
+    let home = TempDir::new().unwrap();
+    let script = home.path().join("build.sh");
+
+    std::fs::write(&script, "touch first.marker\n").unwrap();
+    assert!(
+        sv_in(&home, &["save", "build.sh", "--yes"])
+            .status
+            .success()
+    );
+    std::fs::write(&script, "touch second.marker\n").unwrap();
+    assert!(
+        sv_in(&home, &["save", "build.sh", "--yes"])
+            .status
+            .success()
+    );
+
+    let run = sv_in(&home, &["run", "build", "--version", "v1.0.0"]);
+    assert!(run.status.success());
+    assert!(home.path().join("first.marker").exists());
+    assert!(!home.path().join("second.marker").exists());
+
+    let history = std::fs::read_to_string(home.path().join("history.jsonl")).unwrap();
+    let record: serde_json::Value = serde_json::from_str(history.lines().last().unwrap()).unwrap();
+    assert_eq!(record["script_version"], "v1.0.0");
+
+    // The stored script itself is still the latest version.
+    let cat = sv_in(&home, &["cat", "build"]);
+    assert!(String::from_utf8_lossy(&cat.stdout).contains("second.marker"));
+
+    let missing = sv_in(&home, &["run", "build", "--version", "v9.9.9"]);
+    assert!(!missing.status.success());
+    assert!(String::from_utf8_lossy(&missing.stderr).contains("Version v9.9.9 not found"));
+}
+
+#[cfg(unix)]
+#[test]
+fn test_resave_bumps_version_only_on_content_change() {
+    let home = TempDir::new().unwrap();
+    let script = home.path().join("tool.sh");
+    let stored = |home: &TempDir| -> serde_json::Value {
+        let path = std::fs::read_dir(home.path().join("vault"))
+            .unwrap()
+            .map(|e| e.unwrap().path())
+            .find(|p| p.extension().is_some_and(|e| e == "json") && !p.ends_with("index.json"))
+            .unwrap();
+        serde_json::from_str(&std::fs::read_to_string(path).unwrap()).unwrap()
+    };
+
+    std::fs::write(&script, "echo one\n").unwrap();
+    assert!(sv_in(&home, &["save", "tool.sh", "--yes"]).status.success());
+    assert_eq!(stored(&home)["version"], "v1.0.0");
+
+    std::fs::write(&script, "echo two\n").unwrap();
+    assert!(sv_in(&home, &["save", "tool.sh", "--yes"]).status.success());
+    let second = stored(&home);
+    assert_eq!(second["version"], "v1.0.1");
+
+    let unchanged = sv_in(&home, &["save", "tool.sh", "--yes"]);
+    assert!(String::from_utf8_lossy(&unchanged.stdout).contains("No changes"));
+    assert_eq!(stored(&home)["updated_at"], second["updated_at"]);
+    assert_eq!(stored(&home)["version"], "v1.0.1");
+
+    std::fs::write(&script, "echo three\n").unwrap();
+    let minor = sv_in(&home, &["save", "tool.sh", "--bump", "minor", "--yes"]);
+    assert!(minor.status.success());
+    assert_eq!(stored(&home)["version"], "v1.1.0");
+
+    let versions = sv_in(&home, &["versions", "tool"]);
+    let listing = String::from_utf8_lossy(&versions.stdout);
+    assert!(listing.contains("v1.0.0") && listing.contains("v1.0.1"));
+}
+
+#[cfg(unix)]
+#[test]
+fn test_identical_resave_keeps_updated_at_and_metadata() {
+    let home = TempDir::new().unwrap();
+    std::fs::write(home.path().join("tidy.sh"), "echo tidy\n").unwrap();
+    let saved = sv_in(
+        &home,
+        &[
+            "save",
+            "tidy.sh",
+            "--tags",
+            "cleanup cron",
+            "--description",
+            "Tidies up",
+            "--yes",
+        ],
+    );
+    assert!(saved.status.success());
+
+    let read_stored = || -> String {
+        let path = std::fs::read_dir(home.path().join("vault"))
+            .unwrap()
+            .map(|e| e.unwrap().path())
+            .find(|p| p.extension().is_some_and(|e| e == "json") && !p.ends_with("index.json"))
+            .unwrap();
+        std::fs::read_to_string(path).unwrap()
+    };
+    let before = read_stored();
+
+    let resave = sv_in(&home, &["save", "tidy.sh", "--yes"]);
+    assert!(resave.status.success());
+    assert!(String::from_utf8_lossy(&resave.stdout).contains("No changes: tidy"));
+    assert_eq!(read_stored(), before);
+
+    let stored: serde_json::Value = serde_json::from_str(&before).unwrap();
+    assert_eq!(stored["tags"], serde_json::json!(["cleanup", "cron"]));
+    assert_eq!(stored["description"], "Tidies up");
+}
+
+#[cfg(unix)]
+#[test]
+fn test_single_export_import_round_trip() {
+    let home = TempDir::new().unwrap();
+    std::fs::write(home.path().join("share.sh"), "echo shared\n").unwrap();
+    std::fs::write(home.path().join("other.sh"), "echo other\n").unwrap();
+    for file in ["share.sh", "other.sh"] {
+        assert!(
+            sv_in(&home, &["save", file, "--tags", "gist", "--yes"])
+                .status
+                .success()
+        );
+    }
+
+    let export = sv_in(
+        &home,
+        &[
+            "export",
+            "--single",
+            "share",
+            "--format",
+            "json",
+            "--output",
+            "share.json",
+        ],
+    );
+    assert!(export.status.success());
+    let exported: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(home.path().join("share.json")).unwrap())
+            .unwrap();
+    assert_eq!(exported["name"], "share");
+
+    assert!(sv_in(&home, &["delete", "share", "--yes"]).status.success());
+    let import = sv_in(&home, &["import", "share.json"]);
+    assert!(import.status.success());
+
+    let cat = sv_in(&home, &["cat", "share"]);
+    assert_eq!(String::from_utf8_lossy(&cat.stdout).trim(), "echo shared");
+    let list = String::from_utf8_lossy(&sv_in(&home, &["list"]).stdout).to_string();
+    assert!(list.contains("other"));
+
+    let again = sv_in(&home, &["import", "share.json"]);
+    assert!(String::from_utf8_lossy(&again.stdout).contains("Skipped (already exists): share"));
+}
+
+#[test]
+fn test_export_zip_contains_scripts_and_manifest() {
+    let home = TempDir::new().unwrap();
+    std::fs::write(home.path().join("deploy.sh"), "echo deploy\n").unwrap();
+    std::fs::write(home.path().join("report.py"), "print('report')\n").unwrap();
+    for file in ["deploy.sh", "report.py"] {
+        assert!(sv_in(&home, &["save", file, "--yes"]).status.success());
+    }
+
+    let out = sv_in(&home, &["export", "--zip", "vault.zip"]);
+    assert!(out.status.success());
+    assert!(String::from_utf8_lossy(&out.stdout).contains("Exported 2 scripts to: vault.zip"));
+
+    let file = std::fs::File::open(home.path().join("vault.zip")).unwrap();
+    let mut archive = zip::ZipArchive::new(file).unwrap();
+    let mut names: Vec<&str> = archive.file_names().collect();
+    names.sort();
+    assert_eq!(names, ["deploy.sh", "manifest.json", "report.py"]);
+
+    let mut content = String::new();
+    std::io::Read::read_to_string(&mut archive.by_name("deploy.sh").unwrap(), &mut content)
+        .unwrap();
+    assert_eq!(content, "echo deploy\n");
+
+    let conflict = sv_in(&home, &["export", "--zip", "a.zip", "--format", "json"]);
+    assert!(!conflict.status.success());
+}
+
+#[test]
+fn test_import_zip_round_trips_export() {
+    let home = TempDir::new().unwrap();
+    std::fs::write(home.path().join("deploy.sh"), "echo deploy\n").unwrap();
+    let save = sv_in(
+        &home,
+        &[
+            "save",
+            "deploy.sh",
+            "--tags",
+            "ops prod",
+            "--description",
+            "Ship it",
+            "--visibility",
+            "team",
+            "--yes",
+        ],
+    );
+    assert!(save.status.success());
+    assert!(
+        sv_in(&home, &["export", "--zip", "vault.zip"])
+            .status
+            .success()
+    );
+    let storage = LocalStorage::new(home.path().join("vault")).unwrap();
+    let original = storage.load_script_by_name("deploy").unwrap();
+
+    assert!(
+        sv_in(&home, &["delete", "deploy", "--yes"])
+            .status
+            .success()
+    );
+    let import = sv_in(&home, &["import", "vault.zip"]);
+    let stdout = String::from_utf8_lossy(&import.stdout);
+    assert!(import.status.success(), "{stdout}");
+    assert!(stdout.contains("Imported 1 scripts, skipped 0"), "{stdout}");
+
+    let imported = storage.load_script_by_name("deploy").unwrap();
+    assert_eq!(imported.content, original.content);
+    assert_eq!(imported.tags, original.tags);
+    assert_eq!(imported.description, original.description);
+    assert_eq!(imported.visibility, Visibility::Team);
+    assert_eq!(imported.version, original.version);
+    assert_eq!(imported.language, original.language);
+}
+
+#[test]
+fn test_encrypted_export_round_trips_with_passphrase() {
+    let home = TempDir::new().unwrap();
+    let sv_with = |passphrase: &str, args: &[&str]| {
+        std::process::Command::new(env!("CARGO_BIN_EXE_sv"))
+            .args(args)
+            .env("SCRIPTVAULT_HOME", home.path())
+            .env("SCRIPTVAULT_CI", "1")
+            .env("SCRIPTVAULT_PASSPHRASE", passphrase)
+            .current_dir(home.path())
+            .output()
+            .unwrap()
+    };
+    std::fs::write(home.path().join("deploy.sh"), "echo secret-deploy\n").unwrap();
+    assert!(
+        sv_in(&home, &["save", "deploy.sh", "--yes"])
+            .status
+            .success()
+    );
+
+    let export = sv_with(
+        "hunter2",
+        &["export", "--format", "json", "-o", "vault.enc", "--encrypt"],
+    );
+    assert!(export.status.success());
+    let bundle = std::fs::read(home.path().join("vault.enc")).unwrap();
+    assert!(bundle.starts_with(b"SVBUNDLE"));
+    assert!(!String::from_utf8_lossy(&bundle).contains("secret-deploy"));
+    assert!(!sv_in(&home, &["export", "--encrypt"]).status.success());
+
+    assert!(
+        sv_in(&home, &["delete", "deploy", "--yes"])
+            .status
+            .success()
+    );
+    let plain = sv_in(&home, &["import", "vault.enc"]);
+    assert!(!plain.status.success());
+    assert!(String::from_utf8_lossy(&plain.stderr).contains("Pass --decrypt"));
+
+    let wrong = sv_with("hunter3", &["import", "vault.enc", "--decrypt"]);
+    assert!(!wrong.status.success());
+    assert!(String::from_utf8_lossy(&wrong.stderr).contains("Wrong passphrase"));
+
+    let import = sv_with("hunter2", &["import", "vault.enc", "--decrypt"]);
+    let stdout = String::from_utf8_lossy(&import.stdout);
+    assert!(import.status.success(), "{stdout}");
+    assert!(stdout.contains("Imported 1 scripts, skipped 0"), "{stdout}");
+    let storage = LocalStorage::new(home.path().join("vault")).unwrap();
+    let imported = storage.load_script_by_name("deploy").unwrap();
+    assert_eq!(imported.content, "echo secret-deploy\n");
+}
+
+#[test]
+fn test_dry_run_destructive_commands_change_nothing() {
+    let home = TempDir::new().unwrap();
+    std::fs::write(home.path().join("deploy.sh"), "echo deploy\n").unwrap();
+    assert!(
+        sv_in(&home, &["save", "deploy.sh", "--yes"])
+            .status
+            .success()
+    );
+    assert!(sv_in(&home, &["run", "deploy"]).status.success());
+    let history_path = home.path().join("history.jsonl");
+    let history = std::fs::read_to_string(&history_path).unwrap();
+
+    let out = sv_in(&home, &["delete", "deploy", "--dry-run"]);
+    let stdout = String::from_utf8_lossy(&out.stdout);
+    assert!(out.status.success(), "{stdout}");
+    assert!(stdout.contains("Would delete deploy v1.0.0"), "{stdout}");
+    assert!(stdout.contains("History records: 1"), "{stdout}");
+
+    let out = sv_in(&home, &["archive", "deploy", "--dry-run"]);
+    assert!(String::from_utf8_lossy(&out.stdout).contains("'deploy' would be archived"));
+    let out = sv_in(&home, &["rename", "deploy", "ship", "--dry-run"]);
+    assert!(String::from_utf8_lossy(&out.stdout).contains("Would rename deploy -> ship"));
+
+    let storage = LocalStorage::new(home.path().join("vault")).unwrap();
+    let script = storage.load_script_by_name("deploy").unwrap();
+    assert!(!script.archived);
+    assert!(storage.load_script_by_name("ship").is_err());
+    assert_eq!(std::fs::read_to_string(&history_path).unwrap(), history);
+}
+
+#[cfg(unix)]
+#[test]
+fn test_history_output_is_capped_but_terminal_is_not() {
+    let home = TempDir::new().unwrap();
+    assert!(sv_in(&home, &["list"]).status.success());
+    let config_path = home.path().join("config.json");
+    let mut config: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(&config_path).unwrap()).unwrap();
+    config["max_output_bytes"] = 32.into();
+    std::fs::write(&config_path, config.to_string()).unwrap();
+
+    let line = "x".repeat(100);
+    std::fs::write(home.path().join("noisy.sh"), format!("echo {}\n", line)).unwrap();
+    assert!(
+        sv_in(&home, &["save", "noisy.sh", "--yes"])
+            .status
+            .success()
+    );
+
+    let run = sv_in(&home, &["run", "noisy"]);
+    assert!(run.status.success());
+    assert!(String::from_utf8_lossy(&run.stdout).contains(&line));
+
+    let history = std::fs::read_to_string(home.path().join("history.jsonl")).unwrap();
+    let record: serde_json::Value = serde_json::from_str(history.lines().last().unwrap()).unwrap();
+    assert_eq!(
+        record["output"],
+        format!("{}... [truncated 69 bytes]", "x".repeat(32))
+    );
+}
+
+#[cfg(unix)]
+fn sv_with_editor(home: &TempDir, editor_body: &str, args: &[&str]) -> std::process::Output {
+    use std::os::unix::fs::PermissionsExt;
+    let editor = home.path().join("fake-editor.sh");
+    std::fs::write(&editor, format!("#!/bin/sh\n{}\n", editor_body)).unwrap();
+    std::fs::set_permissions(&editor, std::fs::Permissions::from_mode(0o755)).unwrap();
+
+    std::process::Command::new(env!("CARGO_BIN_EXE_sv"))
+        .args(args)
+        .env("SCRIPTVAULT_HOME", home.path())
+        .env("SCRIPTVAULT_CI", "1")
+        .env("EDITOR", &editor)
+        .current_dir(home.path())
+        .output()
+        .unwrap()
+}
+
+#[cfg(unix)]
+#[test]
+fn test_open_config_prints_path() {
+    let home = TempDir::new().unwrap();
+    let out = sv_in(&home, &["open-config"]);
+    assert!(out.status.success());
+    let printed = String::from_utf8_lossy(&out.stdout).trim().to_string();
+    assert_eq!(
+        printed,
+        home.path().join("config.json").display().to_string()
+    );
+    assert!(home.path().join("config.json").exists());
+}
+
+#[cfg(unix)]
+#[test]
+fn test_open_config_rejects_invalid_edit() {
+    let home = TempDir::new().unwrap();
+    assert!(sv_in(&home, &["open-config"]).status.success());
+    let config_path = home.path().join("config.json");
+    let original = std::fs::read_to_string(&config_path).unwrap();
+
+    let out = sv_with_editor(
+        &home,
+        "echo '{ broken' > \"$1\"",
+        &["open-config", "--edit"],
+    );
+    assert!(!out.status.success());
+    assert!(String::from_utf8_lossy(&out.stderr).contains("Invalid config"));
+    assert_eq!(std::fs::read_to_string(&config_path).unwrap(), original);
+    assert!(!home.path().join("config.json.edit").exists());
+}
+
+#[cfg(unix)]
+#[test]
+fn test_open_config_applies_valid_edit() {
+    let home = TempDir::new().unwrap();
+    assert!(sv_in(&home, &["open-config"]).status.success());
+
+    let out = sv_with_editor(
+        &home,
+        "sed -i 's/\"auto_sync\": false/\"auto_sync\": true/' \"$1\"",
+        &["open-config", "--edit"],
+    );
+    assert!(out.status.success());
+    let config: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(home.path().join("config.json")).unwrap())
+            .unwrap();
+    assert_eq!(config["auto_sync"], true);
+}
+
+#[cfg(unix)]
+#[test]
+fn test_count_flags_match_filtered_results() {
+    let home = TempDir::new().unwrap();
+    for (name, tags, body) in [
+        ("alpha", "ops", "exit 0"),
+        ("bravo", "ops", "exit 1"),
+        ("charlie", "docs", "true"),
+    ] {
+        std::fs::write(home.path().join(format!("{}.sh", name)), body).unwrap();
+        let file = format!("{}.sh", name);
+        assert!(
+            sv_in(&home, &["save", &file, "--tags", tags, "--yes"])
+                .status
+                .success()
+        );
+    }
+    let count = |args: &[&str]| -> String {
+        let out = sv_in(&home, args);
+        assert!(out.status.success());
+        String::from_utf8_lossy(&out.stdout).trim().to_string()
+    };
+
+    assert_eq!(count(&["history", "--count"]), "0");
+    sv_in(&home, &["run", "alpha"]);
+    sv_in(&home, &["run", "bravo"]);
+    sv_in(&home, &["run", "alpha"]);
+
+    assert_eq!(count(&["list", "--count"]), "3");
+    assert_eq!(count(&["find", "--tag", "ops", "--count"]), "2");
+    assert_eq!(count(&["find", "--tag", "nope", "--count"]), "0");
+    assert_eq!(count(&["history", "--count"]), "3");
+    assert_eq!(count(&["history", "alpha", "--count"]), "2");
+    assert_eq!(count(&["history", "--failed", "--count"]), "1");
+}
+
+#[cfg(unix)]
+#[test]
+fn test_toml_config_is_read_and_written_back() {
+    let home = TempDir::new().unwrap();
+    let vault = home.path().join("vault");
+    std::fs::write(
+        home.path().join("config.toml"),
+        format!(
+            "api_endpoint = \"http://127.0.0.1:9/v1\"\n\
+             vault_path = \"{0}\"\n\
+             auto_sync = false\n\
+             confirm_before_run = true\n\
+             default_visibility = \"private\"\n\n\
+             [storage]\n\
+             path = \"{0}\"\n",
+            vault.display()
+        ),
+    )
+    .unwrap();
+
+    let login = sv_in(&home, &["auth", "login", "--token", "tomluser"]);
+    assert!(login.status.success());
+
+    assert!(!home.path().join("config.json").exists());
+    let written = std::fs::read_to_string(home.path().join("config.toml")).unwrap();
+    assert!(written.contains("username = \"tomluser\""));
+
+    let path = sv_in(&home, &["open-config"]);
+    assert!(
+        String::from_utf8_lossy(&path.stdout)
+            .trim()
+            .ends_with("config.toml")
+    );
+}
+
+#[cfg(unix)]
+#[test]
+fn test_show_env_lists_configured_captured_vars() {
+    let home = TempDir::new().unwrap();
+    assert!(sv_in(&home, &["list"]).status.success());
+    let config_path = home.path().join("config.json");
+    let mut config: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(&config_path).unwrap()).unwrap();
+    config["captured_env_vars"] = serde_json::json!(["SV_DEPLOY_TARGET"]);
+    std::fs::write(&config_path, config.to_string()).unwrap();
+
+    std::fs::write(home.path().join("env.sh"), "echo ok\n").unwrap();
+    assert!(sv_in(&home, &["save", "env.sh", "--yes"]).status.success());
+
+    let run = std::process::Command::new(env!("CARGO_BIN_EXE_sv"))
+        .args(["run", "env", "--show-env"])
+        .env("SCRIPTVAULT_HOME", home.path())
+        .env("SCRIPTVAULT_CI", "1")
+        .env("SV_DEPLOY_TARGET", "staging")
+        .current_dir(home.path())
+        .output()
+        .unwrap();
+    assert!(run.status.success());
+    let stdout = String::from_utf8_lossy(&run.stdout);
+    assert!(stdout.contains("Captured environment:"));
+    assert!(stdout.contains("SV_DEPLOY_TARGET=staging"));
+    assert!(!stdout.contains("SHELL="));
+
+    let history = std::fs::read_to_string(home.path().join("history.jsonl")).unwrap();
+    let record: serde_json::Value = serde_json::from_str(history.lines().last().unwrap()).unwrap();
+    assert_eq!(
+        record["context"]["environment"],
+        serde_json::json!({ "SV_DEPLOY_TARGET": "staging" })
+    );
+}
+#[cfg(unix)]
+#[test]
+fn test_info_shows_last_failure() {
+    let home = TempDir::new().unwrap();
+    std::fs::write(
+        home.path().join("flaky.sh"),
+        "echo 'database unreachable' >&2\nexit 3\n",
+    )
+    .unwrap();
+    assert!(
+        sv_in(&home, &["save", "flaky.sh", "--yes"])
+            .status
+            .success()
+    );
+
+    let info = sv_in(&home, &["info", "flaky"]);
+    assert!(!String::from_utf8_lossy(&info.stdout).contains("Last run failed"));
+
+    assert!(!sv_in(&home, &["run", "flaky"]).status.success());
+    let info = sv_in(&home, &["info", "flaky"]);
+    let stdout = String::from_utf8_lossy(&info.stdout);
+    assert!(stdout.contains("Last run failed"));
+    assert!(stdout.contains("exit code 3"));
+    assert!(stdout.contains("database unreachable"));
+}
+
+#[cfg(unix)]
+#[test]
+fn test_info_history_lists_recent_runs() {
+    let home = TempDir::new().unwrap();
+    std::fs::write(home.path().join("code.sh"), "exit \"$1\"\n").unwrap();
+    assert!(sv_in(&home, &["save", "code.sh", "--yes"]).status.success());
+    for code in ["0", "1", "2", "3"] {
+        sv_in(&home, &["run", "code", code]);
+    }
+
+    let info = sv_in(&home, &["info", "code", "--history", "2"]);
+    assert!(info.status.success());
+    let stdout = String::from_utf8_lossy(&info.stdout);
+    let runs: Vec<Vec<&str>> = stdout
+        .lines()
+        .skip_while(|line| !line.contains("Recent runs:"))
+        .skip(1)
+        .take_while(|line| !line.trim().is_empty())
+        .map(|line| line.split_whitespace().collect())
+        .collect();
+    assert_eq!(runs.len(), 2, "{stdout}");
+    assert_eq!(runs[0][2], "3");
+    assert_eq!(runs[1][2], "2");
+    assert!(runs.iter().all(|run| run[3].ends_with('s')));
+
+    let info = sv_in(&home, &["info", "code"]);
+    assert!(!String::from_utf8_lossy(&info.stdout).contains("Recent runs:"));
+}
+
+#[test]
+fn test_save_and_delete_append_audit_events() {
+    let home = TempDir::new().unwrap();
+    let events_path = home.path().join("events.jsonl");
+    let events = || -> Vec<serde_json::Value> {
+        std::fs::read_to_string(&events_path)
+            .unwrap_or_default()
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect()
+    };
+    std::fs::write(home.path().join("deploy.sh"), "echo deploy\n").unwrap();
+
+    assert!(
+        sv_in(&home, &["save", "deploy.sh", "--yes"])
+            .status
+            .success()
+    );
+    let saved = events();
+    assert_eq!(saved.len(), 1);
+    assert_eq!(saved[0]["action"], "save");
+    assert_eq!(saved[0]["script"], "deploy");
+    assert_eq!(saved[0]["detail"], "v1.0.0");
+    assert!(saved[0]["user"].as_str().is_some_and(|u| !u.is_empty()));
+    assert!(chrono::DateTime::parse_from_rfc3339(saved[0]["at"].as_str().unwrap()).is_ok());
+
+    // Nothing changed, so nothing is logged.
+    assert!(
+        sv_in(&home, &["save", "deploy.sh", "--yes"])
+            .status
+            .success()
+    );
+    assert_eq!(events().len(), 1);
+    assert!(
+        sv_in(&home, &["delete", "deploy", "--dry-run"])
+            .status
+            .success()
+    );
+    assert_eq!(events().len(), 1);
+
+    assert!(
+        sv_in(&home, &["delete", "deploy", "--yes"])
+            .status
+            .success()
+    );
+    let all = events();
+    assert_eq!(all.len(), 2);
+    assert_eq!(all[1]["action"], "delete");
+    assert_eq!(all[1]["script"], "deploy");
+    assert_eq!(all[1]["script_id"], all[0]["script_id"]);
+
+    let audit = sv_in(&home, &["audit", "deploy", "--action", "delete"]);
+    let stdout = String::from_utf8_lossy(&audit.stdout);
+    assert!(audit.status.success());
+    let rows: Vec<&str> = stdout.lines().filter(|l| l.contains("deploy")).collect();
+    assert_eq!(rows.len(), 1, "{stdout}");
+    assert!(rows[0].contains("delete"));
+}
+
+#[test]
+fn test_merge_import_and_edits_append_audit_events() {
+    let read_events = |home: &TempDir| -> Vec<(String, String, String)> {
+        std::fs::read_to_string(home.path().join("events.jsonl"))
+            .unwrap_or_default()
+            .lines()
+            .map(|line| {
+                let event: serde_json::Value = serde_json::from_str(line).unwrap();
+                let field = |key: &str| event[key].as_str().unwrap_or_default().to_string();
+                (field("action"), field("script"), field("detail"))
+            })
+            .collect()
+    };
+    let event = |action: &str, script: &str, detail: &str| {
+        (action.to_string(), script.to_string(), detail.to_string())
+    };
+
+    let home = TempDir::new().unwrap();
+    std::fs::write(home.path().join("build.sh"), "echo build\n").unwrap();
+    std::fs::write(home.path().join("test.sh"), "echo test\n").unwrap();
+    for args in [
+        &["save", "build.sh", "--yes"][..],
+        &["save", "test.sh", "--yes"],
+        &["merge", "build", "test", "--name", "ci", "--yes"],
+        &["copy", "build", "build-copy"],
+    ] {
+        let out = sv_in(&home, args);
+        assert!(
+            out.status.success(),
+            "{args:?}: {}",
+            String::from_utf8_lossy(&out.stderr)
+        );
+    }
+    std::fs::write(home.path().join("build.sh"), "echo build --release\n").unwrap();
+    assert!(sv_in(&home, &["update", "build.sh"]).status.success());
+    assert!(sv_in(&home, &["checkout", "build@v1.0.0"]).status.success());
+
+    let events = read_events(&home);
+    assert_eq!(
+        events[2..],
+        [
+            event("merge", "ci", "build + test"),
+            event("save", "build-copy", "copy of build"),
+            event("save", "build", "v1.0.1"),
+            event("checkout", "build", "v1.0.0 as v1.0.2"),
+        ]
+    );
+
+    let export = home.path().join("all.json");
+    assert!(
+        sv_in(
+            &home,
+            &[
+                "export",
+                "--format",
+                "json",
+                "--output",
+                export.to_str().unwrap()
+            ]
+        )
+        .status
+        .success()
+    );
+    let other = TempDir::new().unwrap();
+    assert!(
+        sv_in(&other, &["import", export.to_str().unwrap()])
+            .status
+            .success()
+    );
+    let imported = read_events(&other);
+    assert_eq!(imported.len(), 4);
+    assert!(imported.iter().all(|(action, _, _)| action == "import"));
+    assert!(imported.contains(&event("import", "build", "v1.0.2")));
+
+    let dir = other.path().join("scripts");
+    std::fs::create_dir(&dir).unwrap();
+    std::fs::write(dir.join("lint.sh"), "echo lint\n").unwrap();
+    assert!(
+        sv_in(&other, &["import-dir", dir.to_str().unwrap()])
+            .status
+            .success()
+    );
+    assert_eq!(
+        read_events(&other).last(),
+        Some(&event("import", "lint", "lint.sh"))
+    );
+
+    // Adapting from another directory and bulk tagging change scripts too.
+    let home_dir = home.path().to_str().unwrap();
+    std::fs::write(home.path().join("where.sh"), format!("cd {home_dir}\n")).unwrap();
+    assert!(
+        sv_in(&home, &["save", "where.sh", "--yes"])
+            .status
+            .success()
+    );
+    let elsewhere = home.path().join("elsewhere");
+    std::fs::create_dir(&elsewhere).unwrap();
+    let adapt = std::process::Command::new(env!("CARGO_BIN_EXE_sv"))
+        .args(["adapt", "where", "--yes"])
+        .env("SCRIPTVAULT_HOME", home.path())
+        .env("SCRIPTVAULT_CI", "1")
+        .current_dir(&elsewhere)
+        .output()
+        .unwrap();
+    assert!(
+        adapt.status.success(),
+        "{}",
+        String::from_utf8_lossy(&adapt.stderr)
+    );
+    assert!(
+        sv_in(&home, &["find", "where", "--tag-all", "ops", "--yes"])
+            .status
+            .success()
+    );
+    let events = read_events(&home);
+    assert_eq!(
+        events[events.len() - 2..],
+        [
+            event("save", "where", "v1.0.1"),
+            event("save", "where", "tagged ops"),
+        ]
+    );
+}
+
+#[cfg(unix)]
+#[test]
+fn test_run_record_only() {
+    let home = TempDir::new().unwrap();
+    std::fs::write(home.path().join("seed.sh"), "touch ran.marker\n").unwrap();
+    assert!(sv_in(&home, &["save", "seed.sh", "--yes"]).status.success());
+
+    let run = sv_in(&home, &["run", "seed", "--record-only"]);
+    assert!(run.status.success());
+    assert!(!home.path().join("ran.marker").exists());
+
+    let history = std::fs::read_to_string(home.path().join("history.jsonl")).unwrap();
+    let record: serde_json::Value = serde_json::from_str(history.lines().last().unwrap()).unwrap();
+    assert_eq!(record["exit_code"], -1);
+    assert_eq!(record["duration_ms"], 0);
+
+    let failed = sv_in(&home, &["history", "--failed", "--count"]);
+    assert_eq!(String::from_utf8_lossy(&failed.stdout).trim(), "0");
+
+    let both = sv_in(&home, &["run", "seed", "--record-only", "--dry-run"]);
+    assert!(!both.status.success());
+
+    // Nothing runs, so a missing interpreter is fine and nothing prompts.
+    let seeded = std::process::Command::new(env!("CARGO_BIN_EXE_sv"))
+        .args(["run", "seed", "--record-only", "--confirm"])
+        .args(["--interpreter", "sv-test-no-such-interpreter"])
+        .env("SCRIPTVAULT_HOME", home.path())
+        .current_dir(home.path())
+        .stdin(std::process::Stdio::null())
+        .output()
+        .unwrap();
+    assert!(
+        seeded.status.success(),
+        "{}",
+        String::from_utf8_lossy(&seeded.stderr)
+    );
+    let history = std::fs::read_to_string(home.path().join("history.jsonl")).unwrap();
+    assert_eq!(history.lines().count(), 2);
+}
+
+#[cfg(unix)]
+#[test]
+fn test_run_no_record() {
+    let home = TempDir::new().unwrap();
+    std::fs::write(home.path().join("seed.sh"), "touch ran.marker\n").unwrap();
+    assert!(sv_in(&home, &["save", "seed.sh", "--yes"]).status.success());
+    assert!(sv_in(&home, &["run", "seed"]).status.success());
+    let history_path = home.path().join("history.jsonl");
+    let before = std::fs::read_to_string(&history_path).unwrap();
+    std::fs::remove_file(home.path().join("ran.marker")).unwrap();
+
+    let run = sv_in(&home, &["run", "seed", "--no-record"]);
+    assert!(run.status.success());
+    assert!(home.path().join("ran.marker").exists());
+    assert_eq!(std::fs::read_to_string(&history_path).unwrap(), before);
+
+    let info = sv_in(&home, &["info", "seed"]);
+    assert!(String::from_utf8_lossy(&info.stdout).contains("1 runs, 100.0% success"));
+
+    let both = sv_in(&home, &["run", "seed", "--no-record", "--record-only"]);
+    assert!(!both.status.success());
+}
+
+#[cfg(unix)]
+#[test]
+fn test_save_from_template() {
+    let home = TempDir::new().unwrap();
+    std::fs::write(
+        home.path().join("greet.sh"),
+        "echo '{{greeting}}, {{name}}!' > {{name}}.out\n",
+    )
+    .unwrap();
+    assert!(
+        sv_in(&home, &["template", "add", "greet.sh"])
+            .status
+            .success()
+    );
+
+    let list = sv_in(&home, &["template", "list"]);
+    let stdout = String::from_utf8_lossy(&list.stdout);
+    assert!(stdout.contains("greet"));
+    assert!(stdout.contains("python-argparse"));
+
+    let missing = sv_in(
+        &home,
+        &[
+            "save",
+            "--from-template",
+            "greet",
+            "--name",
+            "hello",
+            "--yes",
+        ],
+    );
+    assert!(!missing.status.success());
+    assert!(String::from_utf8_lossy(&missing.stderr).contains("greeting"));
+
+    let save = sv_in(
+        &home,
+        &[
+            "save",
+            "--from-template",
+            "greet",
+            "--name",
+            "hello",
+            "--var",
+            "greeting=Hi",
+            "--yes",
+        ],
+    );
+    assert!(save.status.success());
+
+    let cat = sv_in(&home, &["cat", "hello"]);
+    assert_eq!(
+        String::from_utf8_lossy(&cat.stdout).trim(),
+        "echo 'Hi, hello!' > hello.out"
+    );
+
+    let builtin = sv_in(
+        &home,
+        &[
+            "save",
+            "--from-template",
+            "python-argparse",
+            "--name",
+            "tool",
+            "--description",
+            "A tool",
+            "--yes",
+        ],
+    );
+    assert!(builtin.status.success());
+    let info = sv_in(&home, &["info", "tool"]);
+    assert!(String::from_utf8_lossy(&info.stdout).contains("python"));
+}
+
+#[cfg(unix)]
+#[test]
+fn test_save_warns_on_duplicate_content() {
+    let home = TempDir::new().unwrap();
+    std::fs::write(home.path().join("deploy.sh"), "echo deploying\n").unwrap();
+    std::fs::write(home.path().join("ship.sh"), "echo deploying\n").unwrap();
+    assert!(
+        sv_in(&home, &["save", "deploy.sh", "--yes"])
+            .status
+            .success()
+    );
+
+    let dup = sv_in(&home, &["save", "ship.sh", "--yes"]);
+    assert!(!dup.status.success());
+    let stderr = String::from_utf8_lossy(&dup.stderr);
+    assert!(stderr.contains("deploy has identical content"));
+    assert!(stderr.contains("--force"));
+    assert!(!sv_in(&home, &["cat", "ship"]).status.success());
+
+    let forced = sv_in(&home, &["save", "ship.sh", "--yes", "--force"]);
+    assert!(forced.status.success());
+    assert!(sv_in(&home, &["cat", "ship"]).status.success());
+}
+
+#[cfg(unix)]
+#[test]
+fn test_merge_scripts() {
+    let home = TempDir::new().unwrap();
+    std::fs::write(home.path().join("one.sh"), "echo one >> out.txt\n").unwrap();
+    std::fs::write(home.path().join("two.sh"), "echo two >> out.txt\n").unwrap();
+    std::fs::write(home.path().join("three.py"), "print('three')\n").unwrap();
+    for file in ["one.sh", "two.sh", "three.py"] {
+        assert!(sv_in(&home, &["save", file, "--yes"]).status.success());
+    }
+
+    let merge = sv_in(&home, &["merge", "one", "two", "--name", "both", "--yes"]);
+    assert!(merge.status.success());
+    assert!(sv_in(&home, &["run", "both"]).status.success());
+    assert_eq!(
+        std::fs::read_to_string(home.path().join("out.txt")).unwrap(),
+        "one\ntwo\n"
+    );
+
+    let mixed = sv_in(
+        &home,
+        &["merge", "one", "three", "--name", "mixed", "--yes"],
+    );
+    assert!(!mixed.status.success());
+    assert!(String::from_utf8_lossy(&mixed.stderr).contains("same language"));
+    assert!(!sv_in(&home, &["cat", "mixed"]).status.success());
+}
+
+#[cfg(unix)]
+#[test]
+fn test_run_without_env_inherit() {
+    let home = TempDir::new().unwrap();
+    std::fs::write(
+        home.path().join("env.sh"),
+        "echo \"${LANG:-unset} ${DEPLOY_ENV:-unset}\" > env.out\n",
+    )
+    .unwrap();
+    assert!(sv_in(&home, &["save", "env.sh", "--yes"]).status.success());
+
+    let run = |args: &[&str]| {
+        let status = std::process::Command::new(env!("CARGO_BIN_EXE_sv"))
+            .args(args)
+            .env("SCRIPTVAULT_HOME", home.path())
+            .env("SCRIPTVAULT_CI", "1")
+            .env("LANG", "C.UTF-8")
+            .env("DEPLOY_ENV", "staging")
+            .current_dir(home.path())
+            .status()
+            .unwrap();
+        assert!(status.success());
+        std::fs::read_to_string(home.path().join("env.out")).unwrap()
+    };
+
+    assert_eq!(run(&["run", "env"]), "C.UTF-8 unset\n");
+    assert_eq!(run(&["run", "env", "--env-inherit=false"]), "unset unset\n");
+    assert_eq!(
+        run(&[
+            "run",
+            "env",
+            "--env-inherit=false",
+            "--env-allow",
+            "DEPLOY_ENV"
+        ]),
+        "unset staging\n"
+    );
+}
+
+#[cfg(unix)]
+#[test]
+fn test_run_refuses_tampered_script() {
+    let home = TempDir::new().unwrap();
+    std::fs::write(home.path().join("tool.sh"), "echo original\n").unwrap();
+    assert!(sv_in(&home, &["save", "tool.sh", "--yes"]).status.success());
+
+    let path = std::fs::read_dir(home.path().join("vault"))
+        .unwrap()
+        .map(|e| e.unwrap().path())
+        .find(|p| p.extension().is_some_and(|e| e == "json") && !p.ends_with("index.json"))
+        .unwrap();
+    let mut stored: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+    stored["content"] = "touch tampered.marker\n".into();
+    std::fs::write(&path, stored.to_string()).unwrap();
+
+    let run = sv_in(&home, &["run", "tool"]);
+    assert!(!run.status.success());
+    assert!(String::from_utf8_lossy(&run.stderr).contains("Integrity check failed"));
+    assert!(!home.path().join("tampered.marker").exists());
+
+    let config_path = home.path().join("config.json");
+    let mut config: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(&config_path).unwrap()).unwrap();
+    config["verify_integrity"] = false.into();
+    std::fs::write(&config_path, config.to_string()).unwrap();
+
+    assert!(sv_in(&home, &["run", "tool"]).status.success());
+    assert!(home.path().join("tampered.marker").exists());
+}
+
+#[cfg(unix)]
+#[test]
+fn test_history_stats_rollup() {
+    let home = TempDir::new().unwrap();
+    std::fs::write(home.path().join("ok.sh"), "exit 0\n").unwrap();
+    std::fs::write(home.path().join("bad.sh"), "exit 4\n").unwrap();
+    for file in ["ok.sh", "bad.sh"] {
+        assert!(sv_in(&home, &["save", file, "--yes"]).status.success());
+    }
+    for script in ["ok", "ok", "bad"] {
+        sv_in(&home, &["run", script]);
+    }
+
+    let out = sv_in(&home, &["history", "--stats", "--no-color"]);
+    assert!(out.status.success());
+    let stdout = String::from_utf8_lossy(&out.stdout);
+    let row = |name: &str| -> Vec<String> {
+        stdout
+            .lines()
+            .find(|l| l.starts_with(&format!("{} ", name)))
+            .unwrap()
+            .split_whitespace()
+            .map(str::to_string)
+            .collect()
+    };
+    assert_eq!(row("ok")[1..3], ["2", "0"]);
+    assert_eq!(row("bad")[1..3], ["1", "1"]);
+
+    let failed = sv_in(&home, &["history", "--stats", "--failed", "--no-color"]);
+    let stdout = String::from_utf8_lossy(&failed.stdout);
+    assert!(stdout.lines().any(|l| l.starts_with("bad ")));
+    assert!(!stdout.lines().any(|l| l.starts_with("ok ")));
+}
+
+#[cfg(unix)]
+#[test]
+fn test_find_not_run_and_stale() {
+    let home = TempDir::new().unwrap();
+    std::fs::write(home.path().join("used.sh"), "exit 0\n").unwrap();
+    std::fs::write(home.path().join("unused.sh"), "true\n").unwrap();
+    for file in ["used.sh", "unused.sh"] {
+        assert!(sv_in(&home, &["save", file, "--yes"]).status.success());
+    }
+    assert!(sv_in(&home, &["run", "used"]).status.success());
+
+    let names = |args: &[&str]| -> Vec<String> {
+        let out = sv_in(&home, args);
+        assert!(out.status.success());
+        let stdout = String::from_utf8_lossy(&out.stdout).to_string();
+        // Rows start with the last-run glyph, then the name.
+        ["used", "unused"]
+            .into_iter()
+            .filter(|n| {
+                stdout
+                    .lines()
+                    .any(|l| l.split_whitespace().nth(1) == Some(n))
+            })
+            .map(str::to_string)
+            .collect()
+    };
+
+    assert_eq!(names(&["find", "--not-run", "--no-color"]), ["unused"]);
+    assert_eq!(names(&["find", "--stale", "30d", "--no-color"]), ["unused"]);
+
+    let bad = sv_in(&home, &["find", "--stale", "soon"]);
+    assert!(!bad.status.success());
+}
+
+#[cfg(unix)]
+#[test]
+fn test_import_reports_invalid_scripts() {
+    let home = TempDir::new().unwrap();
+    std::fs::write(home.path().join("good.sh"), "echo good\n").unwrap();
+    assert!(sv_in(&home, &["save", "good.sh", "--yes"]).status.success());
+    assert!(
+        sv_in(
+            &home,
+            &["export", "--format", "json", "--output", "all.json"]
+        )
+        .status
+        .success()
+    );
+    assert!(sv_in(&home, &["delete", "good", "--yes"]).status.success());
+
+    let path = home.path().join("all.json");
+    let mut export: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+    let mut broken = export["scripts"][0].clone();
+    broken["name"] = "".into();
+    broken["language"] = "Fortran".into();
+    export["scripts"].as_array_mut().unwrap().push(broken);
+    std::fs::write(&path, export.to_string()).unwrap();
+
+    let strict = sv_in(&home, &["import", "all.json"]);
+    assert!(!strict.status.success());
+    let stderr = String::from_utf8_lossy(&strict.stderr);
+    assert!(stderr.contains("1 of 2 scripts"));
+    assert!(stderr.contains("[1] name: must be a non-empty string; language: unknown language"));
+    assert!(!sv_in(&home, &["cat", "good"]).status.success());
+
+    let lenient = sv_in(&home, &["import", "all.json", "--skip-invalid"]);
+    assert!(lenient.status.success());
+    assert!(String::from_utf8_lossy(&lenient.stdout).contains("1 invalid"));
+    assert!(sv_in(&home, &["cat", "good"]).status.success());
+}
+
+#[cfg(unix)]
+#[test]
+fn test_run_refuses_unknown_language() {
+    let home = TempDir::new().unwrap();
+    std::fs::write(home.path().join("notes.txt"), "touch ran.marker\n").unwrap();
+    assert!(
+        sv_in(&home, &["save", "notes.txt", "--yes"])
+            .status
+            .success()
+    );
+
+    let run = sv_in(&home, &["run", "notes"]);
+    assert!(!run.status.success());
+    assert!(String::from_utf8_lossy(&run.stderr).contains("language is unknown"));
+    assert!(!home.path().join("ran.marker").exists());
+
+    let explicit = sv_in(&home, &["run", "notes", "--interpreter", "sh"]);
+    assert!(explicit.status.success());
+    assert!(home.path().join("ran.marker").exists());
+}
+
+#[cfg(unix)]
+#[test]
+fn test_run_continue_from_line() {
+    let home = TempDir::new().unwrap();
+    std::fs::write(
+        home.path().join("steps.sh"),
+        "#!/bin/sh\necho one >> out.txt\necho two >> out.txt\necho three >> out.txt\n",
+    )
+    .unwrap();
+    assert!(
+        sv_in(&home, &["save", "steps.sh", "--yes"])
+            .status
+            .success()
+    );
+
+    assert!(
+        sv_in(&home, &["run", "steps", "--continue-from", "3"])
+            .status
+            .success()
+    );
+    assert_eq!(
+        std::fs::read_to_string(home.path().join("out.txt")).unwrap(),
+        "two\nthree\n"
+    );
+
+    let out_of_range = sv_in(&home, &["run", "steps", "--continue-from", "9"]);
+    assert!(!out_of_range.status.success());
+    assert!(String::from_utf8_lossy(&out_of_range.stderr).contains("out of range"));
+}
+
+#[test]
+fn test_storage_migrate_switches_to_new_vault() {
+    let home = TempDir::new().unwrap();
+    std::fs::write(home.path().join("deploy.sh"), "echo deploy\n").unwrap();
+    std::fs::write(home.path().join("backup.sh"), "echo backup\n").unwrap();
+    for file in ["deploy.sh", "backup.sh"] {
+        assert!(sv_in(&home, &["save", file, "--yes"]).status.success());
+    }
+
+    let keep = sv_in(
+        &home,
+        &["storage", "migrate", "--to", "copy", "--no-switch"],
+    );
+    assert!(keep.status.success());
+    assert!(String::from_utf8_lossy(&keep.stdout).contains("Copied and verified 2 script(s)"));
+    let copy = LocalStorage::new(home.path().join("copy")).unwrap();
+    assert_eq!(copy.list_scripts().unwrap().len(), 2);
+
+    let moved = sv_in(&home, &["storage", "migrate", "--to", "moved", "--switch"]);
+    assert!(moved.status.success());
+    let config: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(home.path().join("config.json")).unwrap())
+            .unwrap();
+    let moved_dir = home.path().join("moved");
+    assert_eq!(config["storage"]["path"], moved_dir.to_str().unwrap());
+
+    // The new vault is now the one sv reads from.
+    std::fs::remove_dir_all(home.path().join("vault")).unwrap();
+    let cat = sv_in(&home, &["cat", "deploy"]);
+    assert!(cat.status.success());
+    assert!(String::from_utf8_lossy(&cat.stdout).contains("echo deploy"));
+}
+
+#[cfg(unix)]
+#[test]
+fn test_read_only_vault_refuses_changes() {
+    let home = TempDir::new().unwrap();
+    std::fs::write(home.path().join("deploy.sh"), "echo deploying\n").unwrap();
+    assert!(
+        sv_in(&home, &["save", "deploy.sh", "--yes"])
+            .status
+            .success()
+    );
+    std::fs::write(home.path().join("other.sh"), "echo other\n").unwrap();
+
+    let save = sv_in(&home, &["--read-only", "save", "other.sh", "--yes"]);
+    assert!(!save.status.success());
+    assert!(String::from_utf8_lossy(&save.stderr).contains("The vault is read-only"));
+
+    let config_path = home.path().join("config.json");
+    let mut config: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(&config_path).unwrap()).unwrap();
+    config["read_only"] = true.into();
+    std::fs::write(&config_path, config.to_string()).unwrap();
+
+    let delete = sv_in(&home, &["delete", "deploy", "--yes"]);
+    assert!(!delete.status.success());
+    assert!(String::from_utf8_lossy(&delete.stderr).contains("The vault is read-only"));
+
+    let find = sv_in(&home, &["find", "deploy"]);
+    assert!(find.status.success());
+    assert!(String::from_utf8_lossy(&find.stdout).contains("deploy"));
+    assert!(sv_in(&home, &["cat", "deploy"]).status.success());
+
+    // Running still works, but leaves no trace.
+    let run = sv_in(&home, &["run", "deploy"]);
+    assert!(run.status.success());
+    assert!(String::from_utf8_lossy(&run.stdout).contains("this run was not recorded"));
+    assert!(!home.path().join("history.jsonl").exists());
+}
+
+#[cfg(unix)]
+#[test]
+fn test_script_env_defaults_and_run_overrides() {
+    let home = TempDir::new().unwrap();
+    std::fs::write(
+        home.path().join("greet.sh"),
+        "echo \"$GREETING $TARGET\" > greet.out\n",
+    )
+    .unwrap();
+    assert!(
+        sv_in(
+            &home,
+            &[
+                "save",
+                "greet.sh",
+                "--script-env",
+                "GREETING=hello",
+                "--script-env",
+                "TARGET=world",
+                "--yes",
+            ],
+        )
+        .status
+        .success()
+    );
+    let out = home.path().join("greet.out");
+
+    assert!(sv_in(&home, &["run", "greet"]).status.success());
+    assert_eq!(std::fs::read_to_string(&out).unwrap(), "hello world\n");
+
+    assert!(
+        sv_in(&home, &["run", "greet", "--env", "TARGET=there"])
+            .status
+            .success()
+    );
+    assert_eq!(std::fs::read_to_string(&out).unwrap(), "hello there\n");
+
+    let info = sv_in(&home, &["info", "greet"]);
+    assert!(String::from_utf8_lossy(&info.stdout).contains("GREETING=hello"));
+}
+
+#[test]
+fn test_run_print_only() {
+    let home = TempDir::new().unwrap();
+    std::fs::write(home.path().join("tidy.sh"), "touch ran.marker\n").unwrap();
+    assert!(sv_in(&home, &["save", "tidy.sh", "--yes"]).status.success());
+
+    let out = sv_in(&home, &["run", "tidy", "--print-only"]);
+    assert!(out.status.success());
+    assert_eq!(
+        String::from_utf8_lossy(&out.stdout),
+        "#!/bin/sh\ntouch ran.marker\n"
+    );
+    assert!(!home.path().join("ran.marker").exists());
+    assert!(!home.path().join("history.jsonl").exists());
+}
+
+#[test]
+fn test_diff_against_working_file() {
+    let home = TempDir::new().unwrap();
+    let file = home.path().join("deploy.sh");
+    std::fs::write(&file, "set -e\necho build\necho push\n").unwrap();
+    assert!(
+        sv_in(&home, &["save", "deploy.sh", "--yes"])
+            .status
+            .success()
+    );
+
+    let same = sv_in(&home, &["diff", "deploy", "--file", "deploy.sh"]);
+    assert!(same.status.success());
+    assert!(String::from_utf8_lossy(&same.stdout).contains("No differences"));
+
+    std::fs::write(&file, "set -e\necho test\necho build\n").unwrap();
+    let diff = sv_in(&home, &["diff", "deploy", "--file", "deploy.sh"]);
+    assert!(diff.status.success());
+    let stdout = String::from_utf8_lossy(&diff.stdout);
+    assert!(stdout.contains("+++ deploy.sh"));
+    assert!(stdout.contains("\n+echo test\n"));
+    assert!(stdout.contains("\n-echo push\n"));
+    assert!(stdout.contains("\n echo build\n"));
+
+    let missing = sv_in(&home, &["diff", "deploy", "--file", "nope.sh"]);
+    assert!(!missing.status.success());
+    assert!(String::from_utf8_lossy(&missing.stderr).contains("File not found: nope.sh"));
+
+    assert!(!sv_in(&home, &["diff", "deploy"]).status.success());
+}
+
+#[test]
+fn test_import_dir_with_mixed_files() {
+    let home = TempDir::new().unwrap();
+    let dir = home.path().join("scripts");
+    std::fs::create_dir_all(dir.join("nested")).unwrap();
+    std::fs::write(dir.join("deploy.sh"), "echo deploy\n").unwrap();
+    std::fs::write(dir.join("report.py"), "print('report')\n").unwrap();
+    std::fs::write(dir.join("backup"), "#!/usr/bin/env bash\necho backup\n").unwrap();
+    std::fs::write(dir.join("README.md"), "# scripts\n").unwrap();
+    std::fs::write(dir.join("notes"), "not a script\n").unwrap();
+    std::fs::write(dir.join("nested/clean.rb"), "puts 'clean'\n").unwrap();
+
+    let flat = sv_in(&home, &["import-dir", "scripts", "--tag", "ops"]);
+    assert!(flat.status.success());
+    assert!(String::from_utf8_lossy(&flat.stdout).contains("Imported 3 scripts, skipped 2"));
+
+    let list = sv_in(&home, &["find", "--tag", "ops"]);
+    let stdout = String::from_utf8_lossy(&list.stdout);
+    for name in ["deploy", "report", "backup"] {
+        assert!(stdout.contains(name), "missing {name}");
+    }
+    assert!(!stdout.contains("clean"));
+    assert!(!sv_in(&home, &["cat", "README"]).status.success());
+
+    let info = sv_in(&home, &["info", "backup"]);
+    assert!(String::from_utf8_lossy(&info.stdout).contains("bash"));
+
+    let recursive = sv_in(&home, &["import-dir", "scripts", "--recursive"]);
+    assert!(recursive.status.success());
+    assert!(String::from_utf8_lossy(&recursive.stdout).contains("Imported 1 scripts, skipped 5"));
+    assert!(sv_in(&home, &["cat", "clean"]).status.success());
+
+    assert!(!sv_in(&home, &["import-dir", "missing"]).status.success());
+}
+
+#[test]
+fn test_import_dir_honors_gitignore() {
+    let home = TempDir::new().unwrap();
+    let dir = home.path().join("scripts");
+    std::fs::create_dir_all(dir.join("build")).unwrap();
+    std::fs::write(dir.join(".gitignore"), "build/\n").unwrap();
+    std::fs::write(dir.join("deploy.sh"), "echo deploy\n").unwrap();
+    std::fs::write(dir.join("build/generated.sh"), "echo generated\n").unwrap();
+
+    let out = sv_in(&home, &["import-dir", "scripts", "--recursive"]);
+    assert!(out.status.success());
+    assert!(String::from_utf8_lossy(&out.stdout).contains("Imported 1 scripts, skipped 0"));
+    assert!(!sv_in(&home, &["cat", "generated"]).status.success());
+
+    let out = sv_in(
+        &home,
+        &["import-dir", "scripts", "--recursive", "--no-ignore"],
+    );
+    assert!(out.status.success());
+    assert!(String::from_utf8_lossy(&out.stdout).contains("Imported 1 scripts, skipped 1"));
+    assert!(sv_in(&home, &["cat", "generated"]).status.success());
+}
+
+#[cfg(unix)]
+#[test]
+fn test_sv_run_capture_json_on_failure() {
+    let home = TempDir::new().unwrap();
+    std::fs::write(home.path().join("fails.sh"), "echo oops >&2\nexit 3\n").unwrap();
+    assert!(
+        sv_in(&home, &["save", "fails.sh", "--name", "fails", "--yes"])
+            .status
+            .success()
+    );
+
+    let out = sv_in(&home, &["run", "fails", "--capture-json", "run.json"]);
+    assert_eq!(out.status.code(), Some(3));
+
+    let json = std::fs::read_to_string(home.path().join("run.json")).unwrap();
+    let record: ExecutionRecord = serde_json::from_str(&json).unwrap();
+    assert_eq!(record.exit_code, 3);
+    assert!(!record.was_successful());
+    assert!(record.error.unwrap().contains("oops"));
+}
+
+#[test]
+fn test_find_porcelain_output() {
+    let home = TempDir::new().unwrap();
+    std::fs::write(home.path().join("deploy.sh"), "echo deploy\n").unwrap();
+    std::fs::write(home.path().join("report.py"), "print('report')\n").unwrap();
+    std::fs::write(home.path().join("cleanup.sh"), "echo cleanup\n").unwrap();
+    for (file, tags) in [
+        ("deploy.sh", "ops"),
+        ("report.py", "ops"),
+        ("cleanup.sh", "misc"),
+    ] {
+        assert!(
+            sv_in(&home, &["save", file, "--tags", tags, "--yes"])
+                .status
+                .success()
+        );
+    }
+    assert!(sv_in(&home, &["run", "deploy"]).status.success());
+
+    let out = sv_in(&home, &["find", "--tag", "ops", "--porcelain"]);
+    assert!(out.status.success());
+    let stdout = String::from_utf8(out.stdout).unwrap();
+    let rows: Vec<Vec<&str>> = stdout.lines().map(|l| l.split('\t').collect()).collect();
+    assert_eq!(rows.len(), 2);
+    assert_eq!(rows[0][..4], ["deploy", "v1.0.0", "shell", "1"]);
+    assert!(chrono::DateTime::parse_from_rfc3339(rows[0][4]).is_ok());
+    assert_eq!(rows[1], ["report", "v1.0.0", "python", "0", ""]);
+
+    let none = sv_in(&home, &["find", "nothing-matches", "--porcelain"]);
+    assert!(none.status.success());
+    assert!(none.stdout.is_empty());
+}
+
+#[test]
+fn test_find_json_lines_streams_matching_scripts() {
+    let home = TempDir::new().unwrap();
+    for (file, tags) in [
+        ("deploy.sh", "ops"),
+        ("report.py", "ops"),
+        ("cleanup.sh", "misc"),
+        ("backup.sh", "ops"),
+    ] {
+        std::fs::write(home.path().join(file), format!("echo {file}\n")).unwrap();
+        assert!(
+            sv_in(&home, &["save", file, "--tags", tags, "--yes"])
+                .status
+                .success()
+        );
+    }
+
+    let count = sv_in(
+        &home,
+        &["find", "--tag", "ops", "--language", "shell", "--count"],
+    );
+    let expected: usize = String::from_utf8_lossy(&count.stdout)
+        .trim()
+        .parse()
+        .unwrap();
+    assert_eq!(expected, 2);
+    let alias = sv_in(
+        &home,
+        &["find", "--tag", "ops", "--language", "SH", "--count"],
+    );
+    assert_eq!(String::from_utf8_lossy(&alias.stdout).trim(), "2");
+    let unknown = sv_in(&home, &["find", "--language", "cobol"]);
+    assert!(!unknown.status.success());
+    assert!(
+        String::from_utf8_lossy(&unknown.stderr)
+            .contains("'cobol' is not a language; expected one of bash, shell, python")
+    );
+
+    let out = sv_in(
+        &home,
+        &[
+            "find",
+            "--tag",
+            "ops",
+            "--language",
+            "shell",
+            "--json-lines",
+        ],
+    );
+    assert!(out.status.success());
+    let stdout = String::from_utf8(out.stdout).unwrap();
+    let scripts: Vec<Script> = stdout
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect();
+    assert_eq!(scripts.len(), expected);
+    let names: Vec<&str> = scripts.iter().map(|s| s.name.as_str()).collect();
+    assert_eq!(names, ["backup", "deploy"]);
+    assert!(scripts.iter().all(|s| s.tags == ["ops"]));
+    assert_eq!(scripts[0].content, "echo backup.sh\n");
+
+    let none = sv_in(&home, &["find", "nothing-matches", "--json-lines"]);
+    assert!(none.status.success());
+    assert!(none.stdout.is_empty());
+    assert!(
+        !sv_in(&home, &["find", "--json-lines", "--porcelain"])
+            .status
+            .success()
+    );
+}
+
+#[test]
+fn test_find_table_truncates_names_to_terminal_width() {
+    let home = TempDir::new().unwrap();
+    let long = "a-very-long-script-name-that-will-not-fit-a-narrow-terminal";
+    std::fs::write(home.path().join(format!("{long}.sh")), "echo long\n").unwrap();
+    assert!(
+        sv_in(&home, &["save", &format!("{long}.sh"), "--yes"])
+            .status
+            .success()
+    );
+
+    let find = |columns: Option<&str>| {
+        let mut cmd = std::process::Command::new(env!("CARGO_BIN_EXE_sv"));
+        cmd.arg("find")
+            .env("SCRIPTVAULT_HOME", home.path())
+            .env("SCRIPTVAULT_CI", "1")
+            .env_remove("COLUMNS")
+            .current_dir(home.path());
+        if let Some(columns) = columns {
+            cmd.env("COLUMNS", columns);
+        }
+        String::from_utf8(cmd.output().unwrap().stdout).unwrap()
+    };
+
+    let narrow = find(Some("70"));
+    assert!(!narrow.contains(long));
+    assert!(narrow.contains("a-very-long-script-nam…"));
+    assert!(narrow.lines().all(|line| line.chars().count() <= 70));
+
+    // Piped output with no width keeps full names.
+    assert!(find(None).contains(long));
+}
+
+#[test]
+fn test_find_tag_all_tags_only_matches() {
+    let home = TempDir::new().unwrap();
+    std::fs::write(home.path().join("deploy-api.sh"), "echo api\n").unwrap();
+    std::fs::write(home.path().join("deploy-web.sh"), "echo web\n").unwrap();
+    std::fs::write(home.path().join("cleanup.sh"), "echo cleanup\n").unwrap();
+    for (file, tags) in [
+        ("deploy-api.sh", "prod"),
+        ("deploy-web.sh", "ops prod"),
+        ("cleanup.sh", "misc"),
+    ] {
+        assert!(
+            sv_in(&home, &["save", file, "--tags", tags, "--yes"])
+                .status
+                .success()
+        );
+    }
+
+    let out = sv_in(&home, &["find", "deploy", "--tag-all", "ops", "--yes"]);
+    let stdout = String::from_utf8_lossy(&out.stdout);
+    assert!(out.status.success(), "{stdout}");
+    assert!(stdout.contains("Tagged 1 script(s) with 'ops'"), "{stdout}");
+
+    let storage = LocalStorage::new(home.path().join("vault")).unwrap();
+    let tags = |name: &str| storage.load_script_by_name(name).unwrap().tags;
+    assert_eq!(tags("deploy-api"), ["prod", "ops"]);
+    assert_eq!(tags("deploy-web"), ["ops", "prod"]);
+    assert_eq!(tags("cleanup"), ["misc"]);
+
+    let again = sv_in(&home, &["find", "deploy", "--tag-all", "ops", "--yes"]);
+    assert!(again.status.success());
+    assert!(String::from_utf8_lossy(&again.stdout).contains("No matching scripts"));
+
+    let bad = sv_in(&home, &["find", "--tag-all", "two words", "--yes"]);
+    assert!(!bad.status.success());
+}
+
+#[test]
+fn test_save_non_utf8_file_fails_clearly() {
+    let home = TempDir::new().unwrap();
+    std::fs::write(home.path().join("blob.sh"), b"echo \xff\xfe\x00\n").unwrap();
+
+    let out = sv_in(&home, &["save", "blob.sh", "--yes"]);
+    assert!(!out.status.success());
+    let stderr = String::from_utf8_lossy(&out.stderr);
+    assert!(
+        stderr.contains("blob.sh: file is not valid UTF-8; binary scripts are unsupported"),
+        "{stderr}"
+    );
+    assert!(!sv_in(&home, &["cat", "blob"]).status.success());
+}
+
+#[test]
+fn test_list_shows_sizes_and_vault_totals() {
+    let home = TempDir::new().unwrap();
+    std::fs::write(home.path().join("one.sh"), "echo one\n").unwrap();
+    std::fs::write(home.path().join("two.py"), "print('a')\nprint('b')\n").unwrap();
+    std::fs::write(home.path().join("three.sh"), "x".repeat(2048) + "\n").unwrap();
+    for file in ["one.sh", "two.py", "three.sh"] {
+        assert!(sv_in(&home, &["save", file, "--yes"]).status.success());
+    }
+
+    let out = sv_in(&home, &["list"]);
+    assert!(out.status.success());
+    let stdout = String::from_utf8_lossy(&out.stdout);
+    assert!(stdout.contains("1 line, 9 B"), "{stdout}");
+    assert!(stdout.contains("2 lines, 22 B"), "{stdout}");
+    assert!(stdout.contains("1 line, 2.0 KB"), "{stdout}");
+
+    let storage = LocalStorage::new(home.path().join("vault")).unwrap();
+    let scripts = storage.list_scripts().unwrap();
+    let total: usize = scripts.iter().map(|s| s.metadata.size_bytes).sum();
+    assert_eq!(total, 9 + 22 + 2049);
+    assert!(stdout.contains(&format!("Vault: {} scripts, 2.0 KB", scripts.len())));
+    assert_eq!(
+        storage.get_metadata().unwrap().total_size_bytes,
+        total as u64
+    );
+}
+
+#[cfg(unix)]
+#[test]
+fn test_run_success_codes_whitelist() {
+    let home = TempDir::new().unwrap();
+    std::fs::write(home.path().join("check.sh"), "exit 1\n").unwrap();
+    assert!(
+        sv_in(
+            &home,
+            &["save", "check.sh", "--success-codes", "0,1", "--yes"]
+        )
+        .status
+        .success()
+    );
+
+    let run = sv_in(&home, &["run", "check"]);
+    assert_eq!(run.status.code(), Some(1));
+    assert!(String::from_utf8_lossy(&run.stdout).contains("counted as success"));
+
+    let history = std::fs::read_to_string(home.path().join("history.jsonl")).unwrap();
+    let record: ExecutionRecord = serde_json::from_str(history.lines().last().unwrap()).unwrap();
+    assert_eq!(record.exit_code, 1);
+    assert!(record.was_successful());
+
+    let info = String::from_utf8_lossy(&sv_in(&home, &["info", "check"]).stdout).to_string();
+    assert!(info.contains("1 runs, 100.0% success"), "{info}");
+    assert!(info.contains("Success codes"));
+
+    // Re-saving without the flag keeps the list; other codes still fail.
+    std::fs::write(home.path().join("check.sh"), "exit 2\n").unwrap();
+    assert!(
+        sv_in(&home, &["save", "check.sh", "--yes"])
+            .status
+            .success()
+    );
+    assert_eq!(sv_in(&home, &["run", "check"]).status.code(), Some(2));
+    let info = String::from_utf8_lossy(&sv_in(&home, &["info", "check"]).stdout).to_string();
+    assert!(info.contains("2 runs, 50.0% success"), "{info}");
+}
+
+#[test]
+fn test_save_applies_default_tags() {
+    let home = TempDir::new().unwrap();
+    assert!(sv_in(&home, &["list"]).status.success());
+    let config_path = home.path().join("config.json");
+    let mut config: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(&config_path).unwrap()).unwrap();
+    config["default_tags"] = serde_json::json!(["team-ops", "deploy"]);
+    std::fs::write(&config_path, config.to_string()).unwrap();
+
+    std::fs::write(home.path().join("release.sh"), "echo release\n").unwrap();
+    std::fs::write(home.path().join("plain.sh"), "echo plain\n").unwrap();
+    assert!(
+        sv_in(
+            &home,
+            &["save", "release.sh", "--tags", "deploy prod", "--yes"]
+        )
+        .status
+        .success()
+    );
+    assert!(
+        sv_in(&home, &["save", "plain.sh", "--yes"])
+            .status
+            .success()
+    );
+
+    let storage = LocalStorage::new(home.path().join("vault")).unwrap();
+    assert_eq!(
+        storage.load_script_by_name("release").unwrap().tags,
+        ["deploy", "prod", "team-ops"]
+    );
+    assert_eq!(
+        storage.load_script_by_name("plain").unwrap().tags,
+        ["team-ops", "deploy"]
+    );
+}
+
+#[cfg(unix)]
+#[test]
+fn test_run_until_success_retries() {
+    let home = TempDir::new().unwrap();
+    std::fs::write(
+        home.path().join("flaky.sh"),
+        "n=$(cat attempts 2>/dev/null || echo 0)\nn=$((n + 1))\necho $n > attempts\n[ $n -ge 3 ]\n",
+    )
+    .unwrap();
+    assert!(
+        sv_in(&home, &["save", "flaky.sh", "--yes"])
+            .status
+            .success()
+    );
+
+    let out = sv_in(
+        &home,
+        &["run", "flaky", "--until-success", "--retry-delay", "0"],
+    );
+    assert!(out.status.success());
+    let stdout = String::from_utf8_lossy(&out.stdout);
+    assert!(stdout.contains("Attempt 3/5"), "{stdout}");
+    assert!(!stdout.contains("Attempt 4/5"));
+
+    let history = std::fs::read_to_string(home.path().join("history.jsonl")).unwrap();
+    let codes: Vec<i32> = history
+        .lines()
+        .map(|l| {
+            serde_json::from_str::<ExecutionRecord>(l)
+                .unwrap()
+                .exit_code
+        })
+        .collect();
+    assert_eq!(codes, [1, 1, 0]);
+    let info = String::from_utf8_lossy(&sv_in(&home, &["info", "flaky"]).stdout).to_string();
+    assert!(info.contains("3 runs"), "{info}");
+
+    // The cap stops a script that never succeeds.
+    std::fs::write(home.path().join("attempts"), "-10\n").unwrap();
+    let capped = sv_in(
+        &home,
+        &[
+            "run",
+            "flaky",
+            "--until-success",
+            "--max-attempts",
+            "2",
+            "--retry-delay",
+            "0",
+        ],
+    );
+    assert_eq!(capped.status.code(), Some(1));
+    assert!(String::from_utf8_lossy(&capped.stdout).contains("Attempt 2/2"));
+
+    let without = sv_in(&home, &["run", "flaky", "--retry-delay", "0"]);
+    assert!(String::from_utf8_lossy(&without.stderr).contains("--until-success"));
+}
+
+#[test]
+fn test_trust_dangerous_skips_safety_prompt() {
+    let home = TempDir::new().unwrap();
+    // Without SCRIPTVAULT_CI, so the safety prompt is live. With no terminal
+    // to answer it, a prompted run fails.
+    let sv_interactive = |args: &[&str]| {
+        std::process::Command::new(env!("CARGO_BIN_EXE_sv"))
+            .args(args)
+            .env("SCRIPTVAULT_HOME", home.path())
+            .env_remove("SCRIPTVAULT_CI")
+            .current_dir(home.path())
+            .stdin(std::process::Stdio::null())
+            .output()
+            .unwrap()
+    };
+    std::fs::write(
+        home.path().join("image.sh"),
+        "echo dd if=/dev/zero of=disk.img\necho imaged\n",
+    )
+    .unwrap();
+    assert!(
+        sv_in(&home, &["save", "image.sh", "--yes"])
+            .status
+            .success()
+    );
+    assert!(sv_in(&home, &["trust", "image"]).status.success());
+
+    let prompted = sv_interactive(&["run", "image"]);
+    assert!(!prompted.status.success());
+    assert!(!String::from_utf8_lossy(&prompted.stdout).contains("imaged"));
+
+    assert!(sv_in(&home, &["trust-dangerous", "image"]).status.success());
+    let out = sv_interactive(&["run", "image"]);
+    let stdout = String::from_utf8_lossy(&out.stdout);
+    assert!(out.status.success(), "{stdout}");
+    assert!(stdout.contains("imaged"), "{stdout}");
+
+    let info = String::from_utf8_lossy(&sv_in(&home, &["info", "image"]).stdout).to_string();
+    assert!(info.contains("Dangerous: dd if= (acknowledged)"), "{info}");
+
+    // Changing the content withdraws the acknowledgment.
+    std::fs::write(
+        home.path().join("image.sh"),
+        "echo dd if=/dev/zero of=other.img\n",
+    )
+    .unwrap();
+    assert!(
+        sv_in(&home, &["update", "image.sh", "--name", "image"])
+            .status
+            .success()
+    );
+    let info = String::from_utf8_lossy(&sv_in(&home, &["info", "image"]).stdout).to_string();
+    assert!(info.contains("Dangerous: dd if="), "{info}");
+    assert!(!info.contains("(acknowledged)"), "{info}");
+}
+
+#[test]
+fn test_run_verbose_shows_interpreter_invocation() {
+    let home = TempDir::new().unwrap();
+    std::fs::write(home.path().join("hello.sh"), "echo hello\n").unwrap();
+    assert!(
+        sv_in(&home, &["save", "hello.sh", "--yes"])
+            .status
+            .success()
+    );
+
+    let out = sv_in(&home, &["run", "hello", "--verbose", "--", "a1"]);
+    let stdout = String::from_utf8_lossy(&out.stdout);
+    assert!(out.status.success(), "{stdout}");
+    assert!(stdout.contains("Interpreter: sh"), "{stdout}");
+    assert!(stdout.contains("Arguments:   a1"), "{stdout}");
+    assert!(stdout.contains("Working dir: "), "{stdout}");
+    assert!(stdout.contains("Timing: write "), "{stdout}");
+    assert!(stdout.contains("hello"));
+
+    let quiet = sv_in(&home, &["run", "hello"]);
+    let stdout = String::from_utf8_lossy(&quiet.stdout);
+    assert!(!stdout.contains("Interpreter:"), "{stdout}");
+    assert!(!stdout.contains("Timing:"), "{stdout}");
+}
+
+#[cfg(unix)]
+#[test]
+fn test_run_records_resource_usage_and_measure_only() {
+    let home = TempDir::new().unwrap();
+    std::fs::write(home.path().join("hello.sh"), "echo hello\n").unwrap();
+    assert!(
+        sv_in(&home, &["save", "hello.sh", "--yes"])
+            .status
+            .success()
+    );
+
+    assert!(sv_in(&home, &["run", "hello"]).status.success());
+    let history = std::fs::read_to_string(home.path().join("history.jsonl")).unwrap();
+    let record: ExecutionRecord = serde_json::from_str(history.lines().next().unwrap()).unwrap();
+    let usage = record.resource_usage.expect("resource usage recorded");
+    assert!(usage.max_rss_kb > 0, "{usage:?}");
+
+    let out = sv_in(&home, &["run", "hello", "--measure-only"]);
+    let stdout = String::from_utf8_lossy(&out.stdout);
+    assert!(out.status.success(), "{stdout}");
+    assert!(stdout.contains("Resources: max RSS "), "{stdout}");
+    // The measured run is recorded like any other, usage included.
+    let history = std::fs::read_to_string(home.path().join("history.jsonl")).unwrap();
+    assert_eq!(history.lines().count(), 2);
+    let record: ExecutionRecord = serde_json::from_str(history.lines().last().unwrap()).unwrap();
+    assert!(record.resource_usage.is_some_and(|u| u.max_rss_kb > 0));
+
+    assert!(
+        sv_in(&home, &["run", "hello", "--measure-only", "--no-record"])
+            .status
+            .success()
+    );
+    let history = std::fs::read_to_string(home.path().join("history.jsonl")).unwrap();
+    assert_eq!(history.lines().count(), 2);
+}
+
+#[cfg(unix)]
+#[test]
+fn test_run_capture_timing_records_phases() {
+    let home = TempDir::new().unwrap();
+    std::fs::write(home.path().join("slowstart.sh"), "sleep 0.1\necho ready\n").unwrap();
+    assert!(
+        sv_in(&home, &["save", "slowstart.sh", "--yes"])
+            .status
+            .success()
+    );
+
+    assert!(sv_in(&home, &["run", "slowstart"]).status.success());
+    let out = sv_in(&home, &["run", "slowstart", "--capture-timing"]);
+    let stdout = String::from_utf8_lossy(&out.stdout);
+    assert!(out.status.success(), "{stdout}");
+    assert!(stdout.contains("Timing: script written "), "{stdout}");
+
+    let history = std::fs::read_to_string(home.path().join("history.jsonl")).unwrap();
+    let records: Vec<ExecutionRecord> = history
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect();
+    assert_eq!(records.len(), 2);
+    assert!(records[0].timings.is_none());
+    assert!(!history.lines().next().unwrap().contains("timings"));
+    let timings = records[1].timings.expect("timings recorded");
+    let first_output = timings.first_output_us.expect("first output recorded");
+    assert!(timings.write_us <= timings.spawn_us, "{timings:?}");
+    assert!(timings.spawn_us < first_output, "{timings:?}");
+    assert!(first_output <= timings.total_us, "{timings:?}");
+    assert!(first_output >= 100_000, "{timings:?}");
+    assert!(timings.total_us <= records[1].duration_ms * 1000 + 1000);
+
+    let stats = sv_in(&home, &["stats", "slowstart"]);
+    let stdout = String::from_utf8_lossy(&stats.stdout);
+    assert!(
+        stdout.contains("Timing (average of 1 runs with --capture-timing):"),
+        "{stdout}"
+    );
+    assert!(stdout.contains("First output:"), "{stdout}");
+}
+
+#[test]
+fn test_run_passes_args_after_separator_verbatim() {
+    let home = TempDir::new().unwrap();
+    std::fs::write(
+        home.path().join("echoargs.sh"),
+        "for a in \"$@\"; do echo \"arg[$a]\"; done\n",
+    )
+    .unwrap();
+    assert!(
+        sv_in(&home, &["save", "echoargs.sh", "--yes"])
+            .status
+            .success()
+    );
+
+    let out = sv_in(&home, &["run", "echoargs", "--", "--verbose", "-h", "--"]);
+    let stdout = String::from_utf8_lossy(&out.stdout);
+    assert!(out.status.success(), "{stdout}");
+    assert!(
+        stdout.contains("arg[--verbose]\narg[-h]\narg[--]\n"),
+        "{stdout}"
+    );
+    assert!(!stdout.contains("Interpreter:"), "{stdout}");
+
+    // sv's own flag before the separator still applies to sv.
+    let out = sv_in(&home, &["run", "echoargs", "--verbose", "--", "--verbose"]);
+    let stdout = String::from_utf8_lossy(&out.stdout);
+    assert!(out.status.success(), "{stdout}");
+    assert!(stdout.contains("Interpreter: sh"), "{stdout}");
+    assert!(stdout.contains("arg[--verbose]"), "{stdout}");
+}
+
+#[cfg(unix)]
+#[test]
+fn test_run_timeout_stops_and_records_script() {
+    let home = TempDir::new().unwrap();
+    std::fs::write(home.path().join("hang.sh"), "echo started\nsleep 30\n").unwrap();
+    assert!(sv_in(&home, &["save", "hang.sh", "--yes"]).status.success());
+
+    let start = std::time::Instant::now();
+    let out = sv_in(
+        &home,
+        &["run", "hang", "--timeout", "1", "--kill-grace", "1"],
+    );
+    assert!(start.elapsed() < std::time::Duration::from_secs(10));
+    assert_eq!(out.status.code(), Some(124));
+    let stderr = String::from_utf8_lossy(&out.stderr);
+    assert!(stderr.contains("Script timed out after 1s"), "{stderr}");
+
+    let history = std::fs::read_to_string(home.path().join("history.jsonl")).unwrap();
+    let record: ExecutionRecord = serde_json::from_str(history.lines().next().unwrap()).unwrap();
+    assert_eq!(record.exit_code, 124);
+    assert_eq!(record.output.as_deref(), Some("started\n"));
+
+    let bad = sv_in(&home, &["run", "hang", "--kill-signal", "KILL"]);
+    assert!(!bad.status.success());
+}
+
+#[test]
+fn test_run_check_permissions_denies_other_users() {
+    let home = TempDir::new().unwrap();
+    std::fs::write(home.path().join("mine.sh"), "echo ran\n").unwrap();
+    assert!(sv_in(&home, &["save", "mine.sh", "--yes"]).status.success());
+    let author = LocalStorage::new(home.path().join("vault"))
+        .unwrap()
+        .load_script_by_name("mine")
+        .unwrap()
+        .author;
+
+    let set_username = |name: &str| {
+        let path = home.path().join("config.json");
+        let mut config: serde_json::Value =
+            serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+        config["username"] = name.into();
+        std::fs::write(&path, config.to_string()).unwrap();
+    };
+
+    set_username(&author);
+    let out = sv_in(&home, &["run", "mine", "--check-permissions"]);
+    assert!(out.status.success());
+    assert!(String::from_utf8_lossy(&out.stdout).contains("ran"));
+
+    set_username("mallory");
+    let out = sv_in(&home, &["run", "mine", "--check-permissions"]);
+    assert!(!out.status.success());
+    let stderr = String::from_utf8_lossy(&out.stderr);
+    assert!(stderr.contains("Permission denied: mallory"), "{stderr}");
+    assert!(!String::from_utf8_lossy(&out.stdout).contains("ran"));
+
+    // Without the flag, visibility is not enforced.
+    assert!(sv_in(&home, &["run", "mine"]).status.success());
+}
+
+#[cfg(unix)]
+#[test]
+fn test_run_uses_scriptvault_tmpdir() {
+    let home = TempDir::new().unwrap();
+    let scratch = home.path().join("scratch");
+    std::fs::write(home.path().join("where.sh"), "echo \"path=$0\"\n").unwrap();
+    assert!(
+        sv_in(&home, &["save", "where.sh", "--yes"])
+            .status
+            .success()
+    );
+
+    let out = std::process::Command::new(env!("CARGO_BIN_EXE_sv"))
+        .args(["run", "where"])
+        .env("SCRIPTVAULT_HOME", home.path())
+        .env("SCRIPTVAULT_CI", "1")
+        .env("SCRIPTVAULT_TMPDIR", &scratch)
+        .current_dir(home.path())
+        .output()
+        .unwrap();
+    let stdout = String::from_utf8_lossy(&out.stdout);
+    assert!(out.status.success(), "{stdout}");
+    let expected = format!("path={}", scratch.join("scriptvault").display());
+    assert!(stdout.contains(&expected), "{stdout}");
+}
+
+#[test]
+fn test_run_args_file_appends_after_positional_args() {
+    let home = TempDir::new().unwrap();
+    std::fs::write(
+        home.path().join("show.sh"),
+        "for a in \"$@\"; do echo \"[$a]\"; done\n",
+    )
+    .unwrap();
+    std::fs::write(
+        home.path().join("args.txt"),
+        "--title 'two words'\n# skipped\n\"quoted \\\"inner\\\"\" last\n",
+    )
+    .unwrap();
+    assert!(sv_in(&home, &["save", "show.sh", "--yes"]).status.success());
+
+    let out = sv_in(
+        &home,
+        &["run", "show", "--args-file", "args.txt", "--", "first"],
+    );
+    let stdout = String::from_utf8_lossy(&out.stdout);
+    assert!(out.status.success(), "{stdout}");
+    let printed: Vec<&str> = stdout.lines().filter(|l| l.starts_with('[')).collect();
+    assert_eq!(
+        printed,
+        [
+            "[first]",
+            "[--title]",
+            "[two words]",
+            "[quoted \"inner\"]",
+            "[last]"
+        ]
+    );
+
+    let missing = sv_in(&home, &["run", "show", "--args-file", "nope.txt"]);
+    assert!(!missing.status.success());
+    assert!(String::from_utf8_lossy(&missing.stderr).contains("Failed to read args file nope.txt"));
+}
+
+#[test]
+fn test_list_tree_groups_by_project() {
+    let home = TempDir::new().unwrap();
+    std::fs::write(home.path().join("here.sh"), "echo here\n").unwrap();
+    assert!(sv_in(&home, &["save", "here.sh", "--yes"]).status.success());
+
+    let storage = LocalStorage::new(home.path().join("vault")).unwrap();
+    storage
+        .save_script(&make_script("loose", "echo loose\n"))
+        .unwrap();
+
+    let out = sv_in(&home, &["list", "--tree"]);
+    let stdout = String::from_utf8_lossy(&out.stdout);
+    assert!(out.status.success(), "{stdout}");
+    let lines: Vec<&str> = stdout.lines().collect();
+    let global = lines
+        .iter()
+        .position(|l| l.starts_with("Global (1)"))
+        .unwrap();
+    assert!(lines[global + 1].contains("└─") && lines[global + 1].contains("loose"));
+    let project = lines
+        .iter()
+        .position(|l| l.ends_with("(1)") && !l.starts_with("Global"))
+        .unwrap();
+    assert!(project < global, "{stdout}");
+    assert!(lines[project + 1].contains("here"), "{stdout}");
+}
+
+#[test]
+fn test_list_and_find_show_last_run_status() {
+    let home = TempDir::new().unwrap();
+    std::fs::write(home.path().join("good.sh"), "exit 0\n").unwrap();
+    std::fs::write(home.path().join("flaky.sh"), "[ -f pass ]\n").unwrap();
+    std::fs::write(home.path().join("idle.sh"), "true\n").unwrap();
+    for file in ["good.sh", "flaky.sh", "idle.sh"] {
+        assert!(sv_in(&home, &["save", file, "--yes"]).status.success());
+    }
+    sv_in(&home, &["run", "good"]);
+    std::fs::write(home.path().join("pass"), "").unwrap();
+    sv_in(&home, &["run", "flaky"]);
+    std::fs::remove_file(home.path().join("pass")).unwrap();
+    sv_in(&home, &["run", "flaky"]);
+
+    for command in ["list", "find"] {
+        let out = sv_in(&home, &[command, "--no-color"]);
+        let stdout = String::from_utf8_lossy(&out.stdout).to_string();
+        let glyph = |name: &str| {
+            stdout
+                .lines()
+                .map(|l| l.split_whitespace().collect::<Vec<_>>())
+                .find(|words| words.get(1) == Some(&name))
+                .map(|words| words[0].to_string())
+        };
+        assert_eq!(glyph("good").as_deref(), Some("✓"), "{stdout}");
+        assert_eq!(glyph("flaky").as_deref(), Some("✗"), "{stdout}");
+        assert_eq!(glyph("idle").as_deref(), Some("·"), "{stdout}");
+    }
+}
+
+#[test]
+fn test_save_visibility_flag_and_config_default() {
+    let home = TempDir::new().unwrap();
+    let visibility = |name: &str| {
+        LocalStorage::new(home.path().join("vault"))
+            .unwrap()
+            .load_script_by_name(name)
+            .unwrap()
+            .visibility
+    };
+    for name in ["a", "b", "c"] {
+        std::fs::write(
+            home.path().join(format!("{name}.sh")),
+            format!("echo {name}\n"),
+        )
+        .unwrap();
+    }
+
+    assert!(sv_in(&home, &["save", "a.sh", "--yes"]).status.success());
+    assert_eq!(visibility("a"), Visibility::Private);
+
+    let out = sv_in(&home, &["save", "b.sh", "--yes", "--visibility", "team"]);
+    assert!(out.status.success());
+    assert_eq!(visibility("b"), Visibility::Team);
+
+    let bad = sv_in(&home, &["save", "b.sh", "--yes", "--visibility", "world"]);
+    assert!(!bad.status.success());
+    assert!(String::from_utf8_lossy(&bad.stderr).contains("expected private, team or public"));
+
+    let path = home.path().join("config.json");
+    let mut config: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+    config["default_visibility"] = "public".into();
+    std::fs::write(&path, config.to_string()).unwrap();
+
+    assert!(sv_in(&home, &["save", "c.sh", "--yes"]).status.success());
+    assert_eq!(visibility("c"), Visibility::Public);
+    // Re-saving keeps the stored visibility rather than the config default.
+    std::fs::write(home.path().join("b.sh"), "echo b2\n").unwrap();
+    assert!(sv_in(&home, &["save", "b.sh", "--yes"]).status.success());
+    assert_eq!(visibility("b"), Visibility::Team);
+}
+
+#[test]
+fn test_save_reads_directive_comments() {
+    let home = TempDir::new().unwrap();
+    std::fs::write(
+        home.path().join("deploy.sh"),
+        "#!/bin/sh\n\
+         # sv:tags deploy,prod\n\
+         # sv:description Deploy the API\n\
+         # sv:visibility team\n\
+         # sv:success-codes 0,3\n\
+         # sv:timeout 30\n\
+         echo deploy\n",
+    )
+    .unwrap();
+
+    let out = sv_in(&home, &["save", "deploy.sh", "--yes"]);
+    assert!(out.status.success());
+    let stderr = String::from_utf8_lossy(&out.stderr);
+    assert!(
+        stderr.contains("Ignoring unknown directive sv:timeout"),
+        "{stderr}"
+    );
+
+    let storage = LocalStorage::new(home.path().join("vault")).unwrap();
+    let script = storage.load_script_by_name("deploy").unwrap();
+    assert_eq!(script.tags, ["deploy", "prod"]);
+    assert_eq!(script.description.as_deref(), Some("Deploy the API"));
+    assert_eq!(script.visibility, Visibility::Team);
+    assert_eq!(script.success_exit_codes, [0, 3]);
+
+    // Flags still win over directives.
+    std::fs::write(
+        home.path().join("deploy.sh"),
+        "# sv:tags deploy\necho deploy v2\n",
+    )
+    .unwrap();
+    let out = sv_in(
+        &home,
+        &[
+            "save",
+            "deploy.sh",
+            "--yes",
+            "--tags",
+            "ops",
+            "--visibility",
+            "public",
+        ],
+    );
+    assert!(out.status.success());
+    let script = storage.load_script_by_name("deploy").unwrap();
+    assert_eq!(script.tags, ["ops"]);
+    assert_eq!(script.visibility, Visibility::Public);
+    assert_eq!(script.description.as_deref(), Some("Deploy the API"));
+}
+
+#[test]
+fn test_save_upgrades_sh_with_bashisms_to_bash() {
+    let home = TempDir::new().unwrap();
+    std::fs::write(
+        home.path().join("check.sh"),
+        "if [[ -n $x ]]; then echo set; fi\n",
+    )
+    .unwrap();
+    std::fs::write(home.path().join("plain.sh"), "[ -n \"$x\" ] && echo set\n").unwrap();
+
+    let out = sv_in(&home, &["save", "check.sh", "--yes"]);
+    assert!(out.status.success());
+    let stdout = String::from_utf8_lossy(&out.stdout);
+    assert!(
+        stdout.contains("Uses bash-only syntax ([[ ]] tests); saving as bash"),
+        "{stdout}"
+    );
+    assert!(
+        sv_in(&home, &["save", "plain.sh", "--yes"])
+            .status
+            .success()
+    );
+
+    let storage = LocalStorage::new(home.path().join("vault")).unwrap();
+    let check = storage.load_script_by_name("check").unwrap();
+    assert_eq!(check.language, ScriptLanguage::Bash);
+    let plain = storage.load_script_by_name("plain").unwrap();
+    assert_eq!(plain.language, ScriptLanguage::Shell);
+}
+
+#[cfg(unix)]
+#[test]
+fn test_run_missing_interpreter_is_reported_and_recorded() {
+    let home = TempDir::new().unwrap();
+    std::fs::write(home.path().join("hi.sh"), "echo hi\n").unwrap();
+    assert!(sv_in(&home, &["save", "hi.sh", "--yes"]).status.success());
+
+    // The interpreter is found up front, but the script's PATH hides it.
+    let out = sv_in(&home, &["run", "hi", "--env", "PATH=/nonexistent"]);
+    assert!(!out.status.success());
+    let stderr = String::from_utf8_lossy(&out.stderr);
+    assert!(
+        stderr.contains("Required interpreter 'sh' not found in PATH"),
+        "{stderr}"
+    );
+    assert!(stderr.contains("sv doctor"), "{stderr}");
+
+    let history = std::fs::read_to_string(home.path().join("history.jsonl")).unwrap();
+    let record: ExecutionRecord = serde_json::from_str(history.lines().last().unwrap()).unwrap();
+    assert_eq!(record.exit_code, -2);
+    assert!(!record.was_successful());
+    let history = String::from_utf8_lossy(&sv_in(&home, &["history"]).stdout).to_string();
+    assert!(history.contains("no interp"), "{history}");
+
+    let missing = sv_in(
+        &home,
+        &["run", "hi", "--interpreter", "sv-no-such-interpreter"],
+    );
+    assert!(!missing.status.success());
+    assert!(
+        String::from_utf8_lossy(&missing.stderr)
+            .contains("Required interpreter 'sv-no-such-interpreter' not found")
+    );
+}
+
+#[cfg(unix)]
+#[test]
+fn test_run_parses_each_script_file_once() {
+    let home = TempDir::new().unwrap();
+    for (name, body) in [
+        ("deploy", "echo deploy"),
+        ("backup", "echo backup"),
+        ("lint", "echo lint"),
+    ] {
+        std::fs::write(home.path().join(format!("{name}.sh")), format!("{body}\n")).unwrap();
+        assert!(
+            sv_in(&home, &["save", &format!("{name}.sh"), "--yes"])
+                .status
+                .success()
+        );
+    }
+
+    // `sv run` lists the vault, then updates the script's run counts.
+    let out = std::process::Command::new(env!("CARGO_BIN_EXE_sv"))
+        .args(["run", "deploy"])
+        .env("SCRIPTVAULT_HOME", home.path())
+        .env("SCRIPTVAULT_CI", "1")
+        .env("SCRIPTVAULT_LOG", "trace")
+        .env("NO_COLOR", "1")
+        .current_dir(home.path())
+        .output()
+        .unwrap();
+    let stderr = String::from_utf8_lossy(&out.stderr);
+    assert!(out.status.success(), "{stderr}");
+    assert_eq!(stderr.matches("parsing script file").count(), 3, "{stderr}");
+}